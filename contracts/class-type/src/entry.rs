@@ -2,17 +2,19 @@ use alloc::vec::Vec;
 use ckb_std::{
     ckb_constants::Source,
     ckb_types::{bytes::Bytes, packed::*, prelude::*},
-    high_level::{load_cell_data, load_script},
+    high_level::{load_cell_data, load_cell_lock_hash, load_script},
 };
 use core::result::Result;
 use script_utils::{
-    class::{Class, CLASS_TYPE_ARGS_LEN},
+    class::{Class, CLASS_DYN_FIELDS_COUNT, CLASS_FIXED_LEN, CLASS_TYPE_ARGS_LEN},
     error::Error,
     helper::{
-        check_group_input_witness_is_none_with_type, count_cells_by_type, count_cells_by_type_hash,
-        load_cell_data_by_type_hash, load_output_type_args_ids, Action,
+        load_cell_data_fields, load_cell_data_from, load_cell_data_header, load_declared_action,
+        positions_by_type, Action, TxIndex,
     },
-    issuer::{Issuer, ISSUER_TYPE_ARGS_LEN},
+    issuer::{DelegateQuota, Issuer, DELEGATE_QUOTA_ARGS_LEN, ISSUER_TYPE_ARGS_LEN},
+    lock_policy::{check_group_locks, check_lock_policy},
+    trace,
 };
 
 fn check_issuer_id<'a>(class_args: &'a Bytes) -> impl Fn(&[u8]) -> bool + 'a {
@@ -32,80 +34,209 @@ fn check_class_type<'a>(class_type: &'a Script) -> impl Fn(&Script) -> bool + 'a
     }
 }
 
+// The opaque ext info of the version 0 class is skipped with the partial loading, and the ext info
+// records of the version 1 class are loaded to be validated
 fn load_class_data(source: Source) -> Result<Vec<u8>, Error> {
-    load_cell_data(0, source).map_err(|_| Error::ClassDataInvalid)
+    let (header, _) = load_cell_data_header(0, source, 1).map_err(|_| Error::ClassDataInvalid)?;
+    let data = match header.first() {
+        Some(0) => load_cell_data_fields(0, source, CLASS_FIXED_LEN, CLASS_DYN_FIELDS_COUNT),
+        _ => load_cell_data_from(0, source, 0),
+    };
+    data.map_err(|_| Error::ClassDataInvalid)
 }
 
-fn parse_class_action(class_type: &Script) -> Result<Action, Error> {
-    let class_inputs_count = count_cells_by_type(Source::Input, &check_class_type(class_type));
+fn parse_class_action(tx_index: &TxIndex, class_type: &Script) -> Result<Action, Error> {
+    let class_inputs_count =
+        tx_index.count_cells_by_type(Source::Input, &check_class_type(class_type));
     if class_inputs_count == 0 {
         return Ok(Action::Create);
     }
-    let class_outputs_count = count_cells_by_type(Source::Output, &check_class_type(class_type));
+    let class_outputs_count =
+        tx_index.count_cells_by_type(Source::Output, &check_class_type(class_type));
     if class_outputs_count == 0 {
         return Ok(Action::Destroy);
     }
     if class_inputs_count == class_outputs_count {
         return Ok(Action::Update);
     }
+    trace!(
+        "class cells count: {} inputs at {:?}, {} outputs at {:?}",
+        class_inputs_count,
+        tx_index.positions_by_type(Source::Input, &check_class_type(class_type)),
+        class_outputs_count,
+        tx_index.positions_by_type(Source::Output, &check_class_type(class_type))
+    );
     Err(Error::ClassCellsCountError)
 }
 
-fn handle_creation(class_type: &Script) -> Result<(), Error> {
+// The class ids created by the owner of the issuer cell
+fn load_issuer_class_ids_range(
+    tx_index: &TxIndex,
+    class_args: &Bytes,
+) -> Result<(u32, u32), Error> {
+    let load_issuer = |source| match tx_index
+        .load_cell_data_by_type_hash(source, &check_issuer_id(class_args))?
+    {
+        Some(data) => Ok(Issuer::from_data(&data)?),
+        None => Err(Error::IssuerDataInvalid),
+    };
+    let input_issuer = load_issuer(Source::Input)?;
+    let output_issuer = load_issuer(Source::Output)?;
+
+    if output_issuer.class_count <= input_issuer.class_count {
+        trace!(
+            "issuer class count of input {:?} not increased: {} -> {}",
+            tx_index.positions_by_type_hash(Source::Input, &check_issuer_id(class_args)),
+            input_issuer.class_count,
+            output_issuer.class_count
+        );
+        return Err(Error::IssuerClassCountError);
+    }
+    Ok((input_issuer.class_count, output_issuer.class_count))
+}
+
+// The class ids created by a delegate with its quota cell instead of the issuer cell. The issuer
+// cell is referenced by the cell deps to pin the type of the quota cell and to check the delegates.
+fn load_delegate_quota_class_ids_range(
+    tx_index: &TxIndex,
+    class_args: &Bytes,
+) -> Result<(u32, u32), Error> {
+    let issuer_index = tx_index
+        .position_by_type_hash(Source::CellDep, &check_issuer_id(class_args))
+        .ok_or(Error::IssuerCellsCountError)?;
+    let issuer_type = tx_index
+        .load_type(Source::CellDep, issuer_index)
+        .ok_or(Error::IssuerDataInvalid)?;
+    let issuer = Issuer::from_data(&load_cell_data(issuer_index, Source::CellDep)?)?;
+
+    let check_quota_type = |type_: &Script| {
+        let type_args: Bytes = type_.args().unpack();
+        type_.code_hash().as_slice() == issuer_type.code_hash().as_slice()
+            && type_.hash_type().as_slice() == issuer_type.hash_type().as_slice()
+            && type_args.len() == DELEGATE_QUOTA_ARGS_LEN
+            && type_args[0..ISSUER_TYPE_ARGS_LEN] == class_args[0..ISSUER_TYPE_ARGS_LEN]
+    };
+    if tx_index.count_cells_by_type(Source::Input, &check_quota_type) != 1 {
+        return Err(Error::DelegateQuotaInvalid);
+    }
+    let input_index = tx_index
+        .position_by_type(Source::Input, &check_quota_type)
+        .ok_or(Error::DelegateQuotaInvalid)?;
+    let quota_type = tx_index
+        .load_type(Source::Input, input_index)
+        .ok_or(Error::DelegateQuotaInvalid)?;
+    let check_same_quota = |type_: &Script| type_.as_slice() == quota_type.as_slice();
+    if tx_index.count_cells_by_type(Source::Output, &check_same_quota) != 1 {
+        return Err(Error::DelegateQuotaInvalid);
+    }
+    let output_index = tx_index
+        .position_by_type(Source::Output, &check_same_quota)
+        .ok_or(Error::DelegateQuotaInvalid)?;
+
+    if !issuer.is_delegate(&load_cell_lock_hash(input_index, Source::Input)?) {
+        trace!(
+            "delegate quota cell {} is not held by a delegate",
+            input_index
+        );
+        return Err(Error::DelegateNotAllowed);
+    }
+
+    let load_quota = |index, source| {
+        DelegateQuota::from_data(
+            &load_cell_data(index, source).map_err(|_| Error::DelegateQuotaInvalid)?,
+        )
+    };
+    let input_quota = load_quota(input_index, Source::Input)?;
+    let output_quota = load_quota(output_index, Source::Output)?;
+    if output_quota.next_class_id <= input_quota.next_class_id {
+        trace!(
+            "delegate quota next class id of input {} not increased: {} -> {}",
+            input_index,
+            input_quota.next_class_id,
+            output_quota.next_class_id
+        );
+        return Err(Error::DelegateQuotaInvalid);
+    }
+    Ok((input_quota.next_class_id, output_quota.next_class_id))
+}
+
+// The issuer cell and the created class ids are all filtered by the issuer id of the class args,
+// so several issuers can advance their class counts in one transaction and every issuer checks
+// the continuity of its own class ids. Without the issuer cell in the inputs, the class ids are
+// taken from the delegate quota cell of the issuer.
+fn handle_creation(tx_index: &TxIndex, class_type: &Script) -> Result<(), Error> {
     let class = Class::from_data(&load_class_data(Source::GroupOutput)?)?;
     if class.issued != 0 {
         return Err(Error::ClassIssuedInvalid);
     }
+    if let Some(policy) = &class.lock_policy {
+        check_lock_policy(policy, Source::GroupOutput)?;
+    }
 
     let class_args: Bytes = class_type.args().unpack();
     let issuer_inputs_count =
-        count_cells_by_type_hash(Source::Input, &check_issuer_id(&class_args));
-    if issuer_inputs_count != 1 {
-        return Err(Error::IssuerCellsCountError);
-    }
-
-    let load_issuer =
-        |source| match load_cell_data_by_type_hash(source, &check_issuer_id(&class_args)) {
-            Some(data) => Ok(Issuer::from_data(&data)?),
-            None => Err(Error::IssuerDataInvalid),
-        };
-    let input_issuer = load_issuer(Source::Input)?;
-    let output_issuer = load_issuer(Source::Output)?;
-
-    if output_issuer.class_count <= input_issuer.class_count {
-        return Err(Error::IssuerClassCountError);
-    }
+        tx_index.count_cells_by_type_hash(Source::Input, &check_issuer_id(&class_args));
+    let (start_class_id, end_class_id) = match issuer_inputs_count {
+        1 => load_issuer_class_ids_range(tx_index, &class_args)?,
+        0 => load_delegate_quota_class_ids_range(tx_index, &class_args)?,
+        _ => {
+            trace!(
+                "issuer inputs count of the class: {} at {:?}",
+                issuer_inputs_count,
+                tx_index.positions_by_type_hash(Source::Input, &check_issuer_id(&class_args))
+            );
+            return Err(Error::IssuerCellsCountError);
+        }
+    };
 
     let outputs_class_ids =
-        load_output_type_args_ids(ISSUER_TYPE_ARGS_LEN, &check_class_type(&class_type));
-    let class_outputs_increased_count =
-        (output_issuer.class_count - input_issuer.class_count) as usize;
+        tx_index.load_output_type_args_ids(ISSUER_TYPE_ARGS_LEN, &check_class_type(&class_type));
+    let class_outputs_increased_count = (end_class_id - start_class_id) as usize;
     if class_outputs_increased_count != outputs_class_ids.len() {
+        trace!(
+            "class ids increased by {} with {} class outputs at {:?}",
+            class_outputs_increased_count,
+            outputs_class_ids.len(),
+            tx_index.positions_by_type(Source::Output, &check_class_type(&class_type))
+        );
         return Err(Error::ClassCellsCountError);
     }
 
     let mut issuer_cell_class_ids = Vec::new();
-    for class_id in input_issuer.class_count..output_issuer.class_count {
+    for class_id in start_class_id..end_class_id {
         issuer_cell_class_ids.push(class_id);
     }
 
     if outputs_class_ids != issuer_cell_class_ids {
+        trace!(
+            "class ids {:?} of outputs {:?} differ from the reserved class ids {:?}",
+            outputs_class_ids,
+            tx_index.positions_by_type(Source::Output, &check_class_type(&class_type)),
+            issuer_cell_class_ids
+        );
         return Err(Error::ClassIdIncreaseError);
     }
     Ok(())
 }
 
 fn handle_update(class_type: &Script) -> Result<(), Error> {
-    // Disable anyone-can-pay lock
-    if check_group_input_witness_is_none_with_type(class_type)? {
-        return Err(Error::GroupInputWitnessNoneError);
-    }
     let load_class = |source| Class::from_data(&load_class_data(source)?[..]);
 
     let input_class = load_class(Source::GroupInput)?;
     let output_class = load_class(Source::GroupOutput)?;
+    check_group_locks(class_type, &input_class.lock_policy, &[
+        Source::GroupInput,
+        Source::GroupOutput,
+    ])?;
 
     if output_class.issued < input_class.issued {
+        trace!(
+            "class issued of input {:?} decreased: {} -> {}",
+            positions_by_type(Source::Input, &|type_| type_.as_slice()
+                == class_type.as_slice()),
+            input_class.issued,
+            output_class.issued
+        );
         return Err(Error::ClassIssuedInvalid);
     }
 
@@ -115,13 +246,38 @@ fn handle_update(class_type: &Script) -> Result<(), Error> {
     Ok(())
 }
 
-fn handle_destroying(class_type: &Script) -> Result<(), Error> {
-    // Disable anyone-can-pay lock
-    if check_group_input_witness_is_none_with_type(class_type)? {
-        return Err(Error::GroupInputWitnessNoneError);
-    }
+// The version 1 issuer counts the classes destroyed along with its update, so its cell must be
+// spent to destroy its classes. Without the issuer input, the issuer in the cell deps must be
+// version 0, and only the version 0 class can be destroyed without referencing its issuer.
+fn handle_destroying(tx_index: &TxIndex, class_type: &Script) -> Result<(), Error> {
+    let class_args: Bytes = class_type.args().unpack();
     let input_class = Class::from_data(&load_class_data(Source::GroupInput)?[..])?;
+    let issuer_inputs_count =
+        tx_index.count_cells_by_type_hash(Source::Input, &check_issuer_id(&class_args));
+    if issuer_inputs_count == 0 {
+        let issuer_version = match tx_index
+            .load_cell_data_by_type_hash(Source::CellDep, &check_issuer_id(&class_args))?
+        {
+            Some(data) => Issuer::from_data(&data)?.version,
+            None => input_class.version,
+        };
+        if issuer_version != 0 {
+            trace!(
+                "the issuer of version {} is not spent with the destroyed class",
+                issuer_version
+            );
+            return Err(Error::IssuerCellsCountError);
+        }
+    }
+
+    check_group_locks(class_type, &input_class.lock_policy, &[Source::GroupInput])?;
     if input_class.issued > 0 {
+        trace!(
+            "class of input {:?} with {} issued cannot be destroyed",
+            positions_by_type(Source::Input, &|type_| type_.as_slice()
+                == class_type.as_slice()),
+            input_class.issued
+        );
         return Err(Error::ClassCellCannotDestroyed);
     }
     Ok(())
@@ -134,9 +290,11 @@ pub fn main() -> Result<(), Error> {
         return Err(Error::TypeArgsInvalid);
     }
 
-    match parse_class_action(&class_type)? {
-        Action::Create => handle_creation(&class_type),
+    let tx_index = TxIndex::load();
+    match load_declared_action(parse_class_action(&tx_index, &class_type)?)?.0 {
+        Action::Create => handle_creation(&tx_index, &class_type),
         Action::Update => handle_update(&class_type),
-        Action::Destroy => handle_destroying(&class_type),
+        Action::Destroy => handle_destroying(&tx_index, &class_type),
+        _ => Err(Error::ActionDeclarationInvalid),
     }
 }
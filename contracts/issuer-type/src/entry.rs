@@ -1,52 +1,79 @@
+use crate::quota;
 use alloc::vec::Vec;
 use blake2b_rs::Blake2bBuilder;
 use ckb_std::{
     ckb_constants::Source,
     ckb_types::{bytes::Bytes, packed::*, prelude::*},
-    high_level::{load_cell_data, load_input, load_script},
+    high_level::{
+        load_cell_data, load_cell_lock_hash, load_input, load_input_since, load_script, QueryIter,
+    },
 };
 use core::result::Result;
 use script_utils::{
     error::Error,
     helper::{
-        check_group_input_witness_is_none_with_type, count_cells_by_type,
-        load_output_index_by_type, Action,
+        blake2b_256, check_class_type_of_issuer, count_cells_by_type, load_declared_action,
+        load_output_index_by_type, positions_by_type, since_absolute_block_number, Action, TxIndex,
     },
-    issuer::{Issuer, ISSUER_TYPE_ARGS_LEN},
+    issuer::{DelegateQuota, Issuer, DELEGATE_QUOTA_ARGS_LEN, ISSUER_TYPE_ARGS_LEN},
+    lock_policy::{check_group_locks, check_lock_policy},
+    trace,
 };
 
 fn load_issuer_data(source: Source) -> Result<Vec<u8>, Error> {
     load_cell_data(0, source).map_err(|_| Error::IssuerDataInvalid)
 }
 
+fn check_issuer_type<'a>(issuer_type: &'a Script) -> impl Fn(&Script) -> bool + 'a {
+    move |type_: &Script| type_.as_slice() == issuer_type.as_slice()
+}
+
 fn parse_issuer_action(issuer_type: &Script) -> Result<Action, Error> {
-    let count_cells = |source| {
-        count_cells_by_type(source, &|type_: &Script| {
-            type_.as_slice() == issuer_type.as_slice()
-        })
-    };
+    let count_cells = |source| count_cells_by_type(source, &check_issuer_type(issuer_type));
     let issuer_cells_count = (count_cells(Source::Input), count_cells(Source::Output));
     match issuer_cells_count {
         (0, 1) => Ok(Action::Create),
         (1, 1) => Ok(Action::Update),
         (1, 0) => Ok(Action::Destroy),
-        _ => Err(Error::IssuerCellsCountError),
+        (inputs, outputs) => {
+            trace!(
+                "issuer cells count: {} inputs at {:?}, {} outputs at {:?}",
+                inputs,
+                positions_by_type(Source::Input, &check_issuer_type(issuer_type)),
+                outputs,
+                positions_by_type(Source::Output, &check_issuer_type(issuer_type))
+            );
+            Err(Error::IssuerCellsCountError)
+        }
     }
 }
 
-fn handle_creation(issuer_type: &Script) -> Result<(), Error> {
-    let first_input = load_input(0, Source::Input)?;
-    let first_output_index = load_output_index_by_type(issuer_type).ok_or(Error::Encoding)?;
+// The type-id-style args of the issuer created at the output index
+fn derive_issuer_args(first_input: &CellInput, output_index: usize) -> [u8; 32] {
     let mut blake2b = Blake2bBuilder::new(32)
         .personal(b"ckb-default-hash")
         .build();
     blake2b.update(first_input.as_slice());
-    blake2b.update(&(first_output_index as u64).to_le_bytes());
+    blake2b.update(&(output_index as u64).to_le_bytes());
     let mut ret = [0; 32];
     blake2b.finalize(&mut ret);
+    ret
+}
+
+// Several issuers can be created in one transaction, and every issuer script group checks that
+// its args are derived from the first input and the index of its own output
+fn handle_creation(issuer_type: &Script) -> Result<(), Error> {
+    let first_input = load_input(0, Source::Input)?;
+    let output_index = load_output_index_by_type(issuer_type).ok_or(Error::Encoding)?;
+    let ret = derive_issuer_args(&first_input, output_index);
 
     let issuer_args: Bytes = issuer_type.args().unpack();
     if issuer_args[..] != ret[0..ISSUER_TYPE_ARGS_LEN] {
+        trace!(
+            "issuer type args {:?} do not match the output {}",
+            &issuer_args[..],
+            output_index
+        );
         return Err(Error::TypeArgsInvalid);
     }
     let issuer = Issuer::from_data(&load_issuer_data(Source::GroupOutput)?[..])?;
@@ -56,33 +83,221 @@ fn handle_creation(issuer_type: &Script) -> Result<(), Error> {
     if issuer.set_count != 0 {
         return Err(Error::IssuerSetCountError);
     }
+    if let Some(policy) = &issuer.lock_policy {
+        check_lock_policy(policy, Source::GroupOutput)?;
+    }
     Ok(())
 }
 
-fn handle_update(issuer_type: &Script) -> Result<(), Error> {
-    // Disable anyone-can-pay lock
-    if check_group_input_witness_is_none_with_type(issuer_type)? {
-        return Err(Error::GroupInputWitnessNoneError);
+// The ids of the classes of the issuer which are consumed but not recreated in the transaction
+fn load_destroyed_class_ids(tx_index: &TxIndex, issuer_type: &Script) -> Vec<u32> {
+    let issuer_id = blake2b_256(issuer_type.as_slice());
+    let check_class_type = check_class_type_of_issuer(&issuer_id[0..ISSUER_TYPE_ARGS_LEN]);
+    let output_class_ids =
+        tx_index.load_type_args_ids(Source::Output, ISSUER_TYPE_ARGS_LEN, &check_class_type);
+    tx_index
+        .load_type_args_ids(Source::Input, ISSUER_TYPE_ARGS_LEN, &check_class_type)
+        .into_iter()
+        .filter(|class_id| !output_class_ids.contains(class_id))
+        .collect()
+}
+
+// The class ids left unused in the delegate quota cells of the issuer which are destroyed in the
+// transaction are released, and they are counted as the destroyed classes
+fn load_released_quota_class_count(tx_index: &TxIndex, issuer_type: &Script) -> Result<u32, Error> {
+    let issuer_id = blake2b_256(issuer_type.as_slice());
+    let check_quota_type = |type_: &Script| {
+        let type_args: Bytes = type_.args().unpack();
+        type_.code_hash().as_slice() == issuer_type.code_hash().as_slice()
+            && type_.hash_type().as_slice() == issuer_type.hash_type().as_slice()
+            && type_args.len() == DELEGATE_QUOTA_ARGS_LEN
+            && type_args[0..ISSUER_TYPE_ARGS_LEN] == issuer_id[0..ISSUER_TYPE_ARGS_LEN]
+    };
+    let mut released_class_count = 0;
+    for index in tx_index.positions_by_type(Source::Input, &check_quota_type) {
+        let quota_type = tx_index
+            .load_type(Source::Input, index)
+            .ok_or(Error::DelegateQuotaInvalid)?;
+        let check_same_quota = |type_: &Script| type_.as_slice() == quota_type.as_slice();
+        if tx_index.count_cells_by_type(Source::Output, &check_same_quota) > 0 {
+            continue;
+        }
+        let quota = DelegateQuota::from_data(
+            &load_cell_data(index, Source::Input).map_err(|_| Error::DelegateQuotaInvalid)?,
+        )?;
+        released_class_count += quota.end_class_id - quota.next_class_id;
     }
+    Ok(released_class_count)
+}
+
+// The count of the classes destroyed in the transaction along with the released quota class ids
+fn load_destroyed_class_count(issuer_type: &Script) -> Result<u32, Error> {
+    let tx_index = TxIndex::load();
+    let destroyed_class_count = load_destroyed_class_ids(&tx_index, issuer_type).len() as u32;
+    Ok(destroyed_class_count + load_released_quota_class_count(&tx_index, issuer_type)?)
+}
+
+fn handle_update(issuer_type: &Script) -> Result<(), Error> {
     let load_issuer = |source| Issuer::from_data(&load_issuer_data(source)?[..]);
     let input_issuer = load_issuer(Source::GroupInput)?;
     let output_issuer = load_issuer(Source::GroupOutput)?;
+    check_group_locks(issuer_type, &input_issuer.lock_policy, &[
+        Source::GroupInput,
+        Source::GroupOutput,
+    ])?;
+    if !input_issuer.immutable_equal(&output_issuer) {
+        return Err(Error::IssuerImmutableFieldsNotSame);
+    }
     if output_issuer.set_count < input_issuer.set_count {
+        trace!(
+            "issuer set count of input {:?} decreased: {} -> {}",
+            positions_by_type(Source::Input, &check_issuer_type(issuer_type)),
+            input_issuer.set_count,
+            output_issuer.set_count
+        );
         return Err(Error::IssuerSetCountError);
     }
     if output_issuer.class_count < input_issuer.class_count {
+        trace!(
+            "issuer class count of input {:?} decreased: {} -> {}",
+            positions_by_type(Source::Input, &check_issuer_type(issuer_type)),
+            input_issuer.class_count,
+            output_issuer.class_count
+        );
+        return Err(Error::IssuerClassCountError);
+    }
+    // The classes destroyed along with the version 1 issuer update are counted
+    if input_issuer.version == 1 {
+        let destroyed_class_count = load_destroyed_class_count(issuer_type)?;
+        if output_issuer.destroyed_class_count
+            != input_issuer.destroyed_class_count + destroyed_class_count
+        {
+            trace!(
+                "issuer destroyed class count of input {:?}: {} -> {} with {} classes destroyed",
+                positions_by_type(Source::Input, &check_issuer_type(issuer_type)),
+                input_issuer.destroyed_class_count,
+                output_issuer.destroyed_class_count,
+                destroyed_class_count
+            );
+            return Err(Error::IssuerClassCountError);
+        }
+    }
+    validate_issuer_transfer(&input_issuer, &output_issuer)
+}
+
+// The issuer cell with the pending owner is moved in two steps: the owner records the pending
+// owner, then the pending owner signs with one of its inputs to take the issuer cell or decline
+// it. The owner can only drop or replace the pending owner after the expiry.
+//
+// The type script cannot unlock the issuer input, so the completion must still be signed by the
+// owner lock as well as by the pending owner. The pending record only makes the pending owner
+// accept the issuer cell explicitly, and keeps the owner from moving it elsewhere meanwhile.
+fn validate_issuer_transfer(input_issuer: &Issuer, output_issuer: &Issuer) -> Result<(), Error> {
+    let pending_owner = match &input_issuer.pending_owner {
+        Some(pending_owner) => pending_owner,
+        None => return Ok(()),
+    };
+    let input_lock_hash = load_cell_lock_hash(0, Source::GroupInput)?;
+    let output_lock_hash = load_cell_lock_hash(0, Source::GroupOutput)?;
+    if output_issuer.pending_owner.as_ref() == Some(pending_owner) {
+        if input_lock_hash != output_lock_hash {
+            trace!("issuer cell moved while the transfer is pending");
+            return Err(Error::IssuerTransferInvalid);
+        }
+        return Ok(());
+    }
+
+    let signed_by_pending_owner = QueryIter::new(load_cell_lock_hash, Source::Input)
+        .any(|lock_hash| lock_hash == pending_owner.lock_hash);
+    if signed_by_pending_owner
+        && output_issuer.pending_owner.is_none()
+        && (output_lock_hash == pending_owner.lock_hash || output_lock_hash == input_lock_hash)
+    {
+        return Ok(());
+    }
+
+    let since = load_input_since(0, Source::GroupInput)?;
+    let expired = since_absolute_block_number(since)
+        .map_or(false, |block_number| block_number >= pending_owner.expiry);
+    if expired && output_lock_hash == input_lock_hash {
+        return Ok(());
+    }
+    trace!(
+        "issuer transfer is not completed by the pending owner or cancelled after {}",
+        pending_owner.expiry
+    );
+    Err(Error::IssuerTransferInvalid)
+}
+
+// The version 0 issuer is migrated to version 1 to count its destroyed classes. The classes
+// destroyed before the migration were not counted, so the owner declares their count in the
+// migrated issuer, and the class and set counts are kept.
+fn handle_migration(issuer_type: &Script) -> Result<(), Error> {
+    let load_issuer = |source| Issuer::from_data(&load_issuer_data(source)?[..]);
+    let input_issuer = load_issuer(Source::GroupInput)?;
+    let output_issuer = load_issuer(Source::GroupOutput)?;
+    if input_issuer.version != 0 || output_issuer.version != 1 {
+        trace!(
+            "issuer of input {:?} migrated from version {} to {}",
+            positions_by_type(Source::Input, &check_issuer_type(issuer_type)),
+            input_issuer.version,
+            output_issuer.version
+        );
+        return Err(Error::VersionInvalid);
+    }
+    check_group_locks(issuer_type, &input_issuer.lock_policy, &[
+        Source::GroupInput,
+    ])?;
+    if let Some(policy) = &output_issuer.lock_policy {
+        check_lock_policy(policy, Source::GroupOutput)?;
+    }
+    if output_issuer.set_count != input_issuer.set_count {
+        return Err(Error::IssuerSetCountError);
+    }
+    if output_issuer.class_count != input_issuer.class_count
+        || output_issuer.destroyed_class_count > output_issuer.class_count
+    {
+        trace!(
+            "issuer migrated with {} of {} classes destroyed, {} classes before",
+            output_issuer.destroyed_class_count,
+            output_issuer.class_count,
+            input_issuer.class_count
+        );
         return Err(Error::IssuerClassCountError);
     }
+    if output_issuer.pending_owner.is_some() {
+        return Err(Error::IssuerTransferInvalid);
+    }
     Ok(())
 }
 
 fn handle_destroying(issuer_type: &Script) -> Result<(), Error> {
-    // Disable anyone-can-pay lock
-    if check_group_input_witness_is_none_with_type(issuer_type)? {
-        return Err(Error::GroupInputWitnessNoneError);
-    }
     let input_issuer = Issuer::from_data(&load_issuer_data(Source::GroupInput)?[..])?;
-    if input_issuer.class_count != 0 || input_issuer.set_count != 0 {
+    check_group_locks(issuer_type, &input_issuer.lock_policy, &[
+        Source::GroupInput,
+    ])?;
+    // The live classes and the unused quota class ids must all be released in the same
+    // transaction
+    let live_class_count = input_issuer
+        .class_count
+        .saturating_sub(input_issuer.destroyed_class_count);
+    let tx_index = TxIndex::load();
+    let destroyed_class_ids = load_destroyed_class_ids(&tx_index, issuer_type);
+    let destroyed_class_count =
+        destroyed_class_ids.len() as u32 + load_released_quota_class_count(&tx_index, issuer_type)?;
+    if destroyed_class_count != live_class_count
+        || destroyed_class_ids
+            .iter()
+            .any(|class_id| *class_id >= input_issuer.class_count)
+        || input_issuer.set_count != 0
+    {
+        trace!(
+            "issuer {:?} cannot be destroyed with {} live classes, {} destroyed classes, {} sets",
+            positions_by_type(Source::Input, &check_issuer_type(issuer_type)),
+            live_class_count,
+            destroyed_class_count,
+            input_issuer.set_count
+        );
         return Err(Error::IssuerCellCannotDestroyed);
     }
     Ok(())
@@ -91,13 +306,19 @@ fn handle_destroying(issuer_type: &Script) -> Result<(), Error> {
 pub fn main() -> Result<(), Error> {
     let issuer_type = load_script()?;
     let issuer_args: Bytes = issuer_type.args().unpack();
+    // The delegate quota cells share the issuer type code with the longer args
+    if issuer_args.len() == DELEGATE_QUOTA_ARGS_LEN {
+        return quota::main(&issuer_type);
+    }
     if issuer_args.len() != ISSUER_TYPE_ARGS_LEN {
         return Err(Error::TypeArgsInvalid);
     }
 
-    match parse_issuer_action(&issuer_type)? {
+    match load_declared_action(parse_issuer_action(&issuer_type)?)?.0 {
         Action::Create => handle_creation(&issuer_type),
         Action::Update => handle_update(&issuer_type),
+        Action::Migrate => handle_migration(&issuer_type),
         Action::Destroy => handle_destroying(&issuer_type),
+        _ => Err(Error::ActionDeclarationInvalid),
     }
 }
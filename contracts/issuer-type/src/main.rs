@@ -6,6 +6,7 @@
 #![feature(panic_info_message)]
 
 mod entry;
+mod quota;
 use ckb_std::default_alloc;
 use core::arch::asm;
 
@@ -15,6 +16,10 @@ default_alloc!();
 fn program_entry() -> i8 {
     match entry::main() {
         Ok(_) => 0,
-        Err(err) => err as i8,
+        Err(err) => {
+            let code = err as i8;
+            script_utils::trace!("issuer-type failed with error {}", code);
+            code
+        }
     }
 }
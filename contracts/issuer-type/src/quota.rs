@@ -0,0 +1,145 @@
+use alloc::vec::Vec;
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{bytes::Bytes, packed::*, prelude::*},
+    high_level::{load_cell_data, load_cell_lock, load_cell_lock_hash},
+};
+use core::result::Result;
+use script_utils::{
+    error::Error,
+    helper::{
+        check_class_type_of_issuer, count_cells_by_type, load_declared_action, positions_by_type,
+        u32_from_slice, Action, TxIndex,
+    },
+    issuer::{DelegateQuota, Issuer, ISSUER_TYPE_ARGS_LEN},
+    lock_policy::check_group_locks,
+    trace,
+};
+
+fn check_issuer_id<'a>(quota_args: &'a Bytes) -> impl Fn(&[u8]) -> bool + 'a {
+    move |type_hash: &[u8]| {
+        type_hash[0..ISSUER_TYPE_ARGS_LEN] == quota_args[0..ISSUER_TYPE_ARGS_LEN]
+    }
+}
+
+fn load_quota_data(source: Source) -> Result<Vec<u8>, Error> {
+    load_cell_data(0, source).map_err(|_| Error::DelegateQuotaInvalid)
+}
+
+fn parse_quota_action(quota_type: &Script) -> Result<Action, Error> {
+    let check_quota_type = |type_: &Script| type_.as_slice() == quota_type.as_slice();
+    let count_cells = |source| count_cells_by_type(source, &check_quota_type);
+    match (count_cells(Source::Input), count_cells(Source::Output)) {
+        (0, 1) => Ok(Action::Create),
+        (1, 1) => Ok(Action::Update),
+        (1, 0) => Ok(Action::Destroy),
+        (inputs, outputs) => {
+            trace!(
+                "delegate quota cells count: {} inputs at {:?}, {} outputs at {:?}",
+                inputs,
+                positions_by_type(Source::Input, &check_quota_type),
+                outputs,
+                positions_by_type(Source::Output, &check_quota_type)
+            );
+            Err(Error::DelegateQuotaInvalid)
+        }
+    }
+}
+
+// The quota is carved out of the class ids of the issuer: the issuer owner advances the class
+// count of the issuer cell over the whole quota, and the quota cell is held by a delegate. The
+// class ids advanced by the issuer cell are all reserved for the quota, so no class of the issuer
+// can be created in the same transaction.
+fn handle_creation(quota_type: &Script) -> Result<(), Error> {
+    let quota_args: Bytes = quota_type.args().unpack();
+    let tx_index = TxIndex::load();
+    let load_issuer = |source| match tx_index
+        .load_cell_data_by_type_hash(source, &check_issuer_id(&quota_args))?
+    {
+        Some(data) => Issuer::from_data(&data),
+        None => Err(Error::IssuerCellsCountError),
+    };
+    let input_issuer = load_issuer(Source::Input)?;
+    let output_issuer = load_issuer(Source::Output)?;
+
+    let quota = DelegateQuota::from_data(&load_quota_data(Source::GroupOutput)?)?;
+    let first_class_id = u32_from_slice(&quota_args[ISSUER_TYPE_ARGS_LEN..]);
+    if first_class_id != input_issuer.class_count
+        || quota.next_class_id != first_class_id
+        || quota.end_class_id != output_issuer.class_count
+        || quota.end_class_id <= first_class_id
+    {
+        trace!(
+            "delegate quota [{}, {}) of output {:?} differs from the issuer class count {} -> {}",
+            positions_by_type(Source::Output, &|type_| type_.as_slice()
+                == quota_type.as_slice()),
+            first_class_id,
+            quota.end_class_id,
+            input_issuer.class_count,
+            output_issuer.class_count
+        );
+        return Err(Error::DelegateQuotaInvalid);
+    }
+
+    let check_class_type = check_class_type_of_issuer(&quota_args[0..ISSUER_TYPE_ARGS_LEN]);
+    if tx_index.count_cells_by_type(Source::Output, &check_class_type) > 0 {
+        trace!(
+            "class outputs {:?} created with the class ids of the delegate quota",
+            tx_index.positions_by_type(Source::Output, &check_class_type)
+        );
+        return Err(Error::DelegateQuotaInvalid);
+    }
+
+    if !output_issuer.is_delegate(&load_cell_lock_hash(0, Source::GroupOutput)?) {
+        return Err(Error::DelegateNotAllowed);
+    }
+    Ok(())
+}
+
+// The class type checks the classes created with the quota, and the quota cell can only advance
+// its next class id under the same delegate lock
+fn handle_update(quota_type: &Script) -> Result<(), Error> {
+    check_group_locks(quota_type, &None, &[Source::GroupInput])?;
+    let input_quota = DelegateQuota::from_data(&load_quota_data(Source::GroupInput)?)?;
+    let output_quota = DelegateQuota::from_data(&load_quota_data(Source::GroupOutput)?)?;
+    if input_quota.end_class_id != output_quota.end_class_id
+        || output_quota.next_class_id < input_quota.next_class_id
+    {
+        return Err(Error::DelegateQuotaInvalid);
+    }
+    let input_lock = load_cell_lock(0, Source::GroupInput)?;
+    let output_lock = load_cell_lock(0, Source::GroupOutput)?;
+    if input_lock.as_slice() != output_lock.as_slice() {
+        return Err(Error::DelegateNotAllowed);
+    }
+    Ok(())
+}
+
+// The class ids left in the destroyed quota are released to the issuer, which counts them as the
+// destroyed classes, so the issuer cell must be spent along with the quota
+fn handle_destroying(quota_type: &Script) -> Result<(), Error> {
+    check_group_locks(quota_type, &None, &[Source::GroupInput])?;
+    DelegateQuota::from_data(&load_quota_data(Source::GroupInput)?)?;
+    let quota_args: Bytes = quota_type.args().unpack();
+    let tx_index = TxIndex::load();
+    let issuer_inputs_count =
+        tx_index.count_cells_by_type_hash(Source::Input, &check_issuer_id(&quota_args));
+    if issuer_inputs_count != 1 {
+        trace!(
+            "issuer inputs count of the destroyed delegate quota: {} at {:?}",
+            issuer_inputs_count,
+            tx_index.positions_by_type_hash(Source::Input, &check_issuer_id(&quota_args))
+        );
+        return Err(Error::IssuerCellsCountError);
+    }
+    Ok(())
+}
+
+pub fn main(quota_type: &Script) -> Result<(), Error> {
+    match load_declared_action(parse_quota_action(quota_type)?)?.0 {
+        Action::Create => handle_creation(quota_type),
+        Action::Update => handle_update(quota_type),
+        Action::Destroy => handle_destroying(quota_type),
+        _ => Err(Error::ActionDeclarationInvalid),
+    }
+}
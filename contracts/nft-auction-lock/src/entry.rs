@@ -0,0 +1,343 @@
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{bytes::Bytes, packed::*, prelude::*},
+    high_level::{
+        load_cell_capacity, load_cell_lock, load_cell_lock_hash, load_cell_type, load_script,
+        load_script_hash, QueryIter,
+    },
+};
+use core::result::Result;
+use script_utils::{
+    error::Error,
+    helper::{
+        cell_data_range_equal, load_cell_data_header, u128_from_slice, u64_from_slice, HASH_LEN,
+    },
+    market::{
+        check_nft_transferable, inputs_have_lock_hash, load_header_deps_timestamp,
+        load_input_committed_timestamp, load_nft_of_input, load_paid_amount, Currency, NftCode,
+    },
+    trace,
+};
+
+const ENGLISH_AUCTION: u8 = 0;
+const DUTCH_AUCTION: u8 = 1;
+
+// seller_lock_hash + reserve_price(u128) + end_time(u64) + auction_type(u8) + nft_code_hash +
+// nft_hash_type(u8)
+const AUCTION_ARGS_LEN: usize = 90;
+// AUCTION_ARGS_LEN + bidder_lock_hash + bid_amount(u128)
+const ENGLISH_AUCTION_WITH_BID_ARGS_LEN: usize = 138;
+// AUCTION_ARGS_LEN + start_price(u128) + start_time(u64)
+const DUTCH_AUCTION_ARGS_LEN: usize = 114;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Bid {
+    bidder_lock_hash: [u8; 32],
+    amount:           u128,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum AuctionKind {
+    English(Option<Bid>),
+    Dutch { start_price: u128, start_time: u64 },
+}
+
+/// Auction lock args structure:
+/// 1) seller_lock_hash: [u8; 32]
+/// 2) reserve_price: u128, in shannons
+/// 3) end_time: u64, the timestamp in milliseconds
+/// 4) auction_type: u8, 0 for the English auction and 1 for the Dutch auction
+/// 5) nft_code_hash: [u8; 32] + nft_hash_type: u8, the code of the nft type of the auctioned nfts
+/// 6) the English auction: <bidder_lock_hash: [u8; 32]> + <bid_amount: u128> of the highest bid,
+/// which is missing before the first bid
+/// 6) the Dutch auction: <start_price: u128> + <start_time: u64>, the price decreases linearly
+/// from the start price at the start time to the reserve price at the end time
+#[derive(Debug, Clone)]
+struct Auction {
+    seller_lock_hash: [u8; 32],
+    reserve_price:    u128,
+    end_time:         u64,
+    nft_code:         NftCode,
+    kind:             AuctionKind,
+}
+
+impl Auction {
+    fn from_args(args: &[u8]) -> Result<Self, Error> {
+        if args.len() < AUCTION_ARGS_LEN {
+            return Err(Error::AuctionLockArgsInvalid);
+        }
+        let mut seller_lock_hash = [0u8; HASH_LEN];
+        seller_lock_hash.copy_from_slice(&args[0..HASH_LEN]);
+        let reserve_price = u128_from_slice(&args[32..48]);
+        let end_time = u64_from_slice(&args[48..56]);
+        let nft_code = NftCode::from_slice(&args[57..AUCTION_ARGS_LEN])
+            .ok_or(Error::AuctionLockArgsInvalid)?;
+        let kind = match (args[56], args.len()) {
+            (ENGLISH_AUCTION, AUCTION_ARGS_LEN) => AuctionKind::English(None),
+            (ENGLISH_AUCTION, ENGLISH_AUCTION_WITH_BID_ARGS_LEN) => {
+                let mut bidder_lock_hash = [0u8; HASH_LEN];
+                bidder_lock_hash.copy_from_slice(&args[90..122]);
+                AuctionKind::English(Some(Bid {
+                    bidder_lock_hash,
+                    amount: u128_from_slice(&args[122..138]),
+                }))
+            }
+            (DUTCH_AUCTION, DUTCH_AUCTION_ARGS_LEN) => {
+                let start_price = u128_from_slice(&args[90..106]);
+                let start_time = u64_from_slice(&args[106..114]);
+                if start_price < reserve_price || start_time >= end_time {
+                    return Err(Error::AuctionLockArgsInvalid);
+                }
+                AuctionKind::Dutch {
+                    start_price,
+                    start_time,
+                }
+            }
+            _ => return Err(Error::AuctionLockArgsInvalid),
+        };
+        Ok(Auction {
+            seller_lock_hash,
+            reserve_price,
+            end_time,
+            nft_code,
+            kind,
+        })
+    }
+
+    // The auction of the same seller, reserve price, end time, nft code and type
+    fn same_auction(&self, other: &Auction) -> bool {
+        self.seller_lock_hash == other.seller_lock_hash
+            && self.reserve_price == other.reserve_price
+            && self.end_time == other.end_time
+            && self.nft_code == other.nft_code
+            && core::mem::discriminant(&self.kind) == core::mem::discriminant(&other.kind)
+    }
+
+    // The price of the Dutch auction at the time
+    fn dutch_price(&self, start_price: u128, start_time: u64, now: u64) -> u128 {
+        if now <= start_time {
+            return start_price;
+        }
+        if now >= self.end_time {
+            return self.reserve_price;
+        }
+        let elapsed = (now - start_time) as u128;
+        let duration = (self.end_time - start_time) as u128;
+        start_price - (start_price - self.reserve_price) * elapsed / duration
+    }
+}
+
+// The nft cell keeps its type and data when it moves to the output
+fn nft_unchanged(input_index: usize, output_index: usize) -> Result<bool, Error> {
+    let type_equal = load_cell_type(input_index, Source::Input)?.map(|type_| type_.as_bytes())
+        == load_cell_type(output_index, Source::Output)?.map(|type_| type_.as_bytes());
+    let (_, input_len) = load_cell_data_header(input_index, Source::Input, 0)?;
+    let (_, output_len) = load_cell_data_header(output_index, Source::Output, 0)?;
+    if !type_equal || input_len != output_len {
+        return Ok(false);
+    }
+    cell_data_range_equal(
+        (input_index, Source::Input),
+        (output_index, Source::Output),
+        0,
+        input_len,
+    )
+}
+
+// The output auction cell of the same auction with a higher bid, and the previous bidder is
+// refunded by the output at the same index as the auction input. The header deps can only prove
+// that the end time has passed, so the bid must carry the header of the block committing the
+// auction input, and the bid proven after the end time is rejected. The bid with old header deps
+// on an auction cell committed before the end time cannot be bounded, so it is voided by the
+// settlement instead.
+fn validate_bid(
+    auction: &Auction,
+    next_auction: &Auction,
+    index: usize,
+    nft_index: usize,
+) -> Result<(), Error> {
+    let (bid, next_bid) = match (&auction.kind, &next_auction.kind) {
+        (AuctionKind::English(bid), AuctionKind::English(Some(next_bid))) => (bid, next_bid),
+        _ => return Err(Error::AuctionBidInvalid),
+    };
+    let committed = match load_input_committed_timestamp(index) {
+        Some(committed) => committed,
+        None => {
+            trace!(
+                "the header committing the auction input {} is missing",
+                index
+            );
+            return Err(Error::AuctionBidInvalid);
+        }
+    };
+    let now = load_header_deps_timestamp().map_or(committed, |now| now.max(committed));
+    if now >= auction.end_time {
+        trace!("bid at {} after the end time {}", now, auction.end_time);
+        return Err(Error::AuctionBidInvalid);
+    }
+    let min_amount = bid
+        .as_ref()
+        .map_or(auction.reserve_price, |bid| bid.amount + 1);
+    if !auction.same_auction(next_auction) || next_bid.amount < min_amount {
+        trace!("bid {} is lower than {}", next_bid.amount, min_amount);
+        return Err(Error::AuctionBidInvalid);
+    }
+    if !nft_unchanged(index, nft_index)? {
+        return Err(Error::AuctionBidInvalid);
+    }
+
+    // The bid is escrowed in the capacity of the auction cell
+    let escrowed = bid.as_ref().map_or(0, |bid| bid.amount);
+    let base_capacity =
+        (load_cell_capacity(index, Source::Input)? as u128).saturating_sub(escrowed);
+    if (load_cell_capacity(nft_index, Source::Output)? as u128) < base_capacity + next_bid.amount {
+        return Err(Error::AuctionBidInvalid);
+    }
+    if let Some(bid) = bid {
+        let refunded = load_paid_amount(index, &Currency::Ckb, &bid.bidder_lock_hash)?;
+        if refunded < bid.amount {
+            trace!(
+                "previous bid {} of input {} is refunded {}",
+                bid.amount,
+                index,
+                refunded
+            );
+            return Err(Error::AuctionPaymentNotEnough);
+        }
+    }
+    Ok(())
+}
+
+// After the end time proven by the header deps, the nft goes to the highest bidder and the bid is
+// paid to the seller by the output at the same index as the auction input, which is the same as
+// the sale lock and the Dutch auction, so that one payment cannot settle two listings. Without
+// bids, the nft goes back to the seller. The settlement takes priority over the bids after the end
+// time, so the bid committed at or after the end time is late: it is refunded to its bidder by the
+// output at the same index, and the nft goes back to the seller.
+fn validate_settlement(auction: &Auction, index: usize, nft_index: usize) -> Result<(), Error> {
+    let ended = load_header_deps_timestamp().map_or(false, |now| now >= auction.end_time);
+    if !ended {
+        return Err(Error::AuctionNotEnded);
+    }
+    let bid = match &auction.kind {
+        AuctionKind::English(bid) => bid,
+        _ => return Err(Error::AuctionLockArgsInvalid),
+    };
+    let late_bid = match bid {
+        Some(_) => {
+            let committed =
+                load_input_committed_timestamp(index).ok_or(Error::AuctionSettlementInvalid)?;
+            committed >= auction.end_time
+        }
+        None => false,
+    };
+    let winner_lock_hash = match bid {
+        Some(bid) if !late_bid => bid.bidder_lock_hash,
+        _ => auction.seller_lock_hash,
+    };
+    if load_cell_lock_hash(nft_index, Source::Output)? != winner_lock_hash
+        || !nft_unchanged(index, nft_index)?
+    {
+        return Err(Error::AuctionSettlementInvalid);
+    }
+    if let Some(bid) = bid {
+        let receiver_lock_hash = if late_bid {
+            &bid.bidder_lock_hash
+        } else {
+            &auction.seller_lock_hash
+        };
+        let paid = load_paid_amount(index, &Currency::Ckb, receiver_lock_hash)?;
+        if paid < bid.amount {
+            trace!(
+                "bid {} of input {} is paid {}, late: {}",
+                bid.amount,
+                index,
+                paid,
+                late_bid
+            );
+            return Err(Error::AuctionPaymentNotEnough);
+        }
+    }
+    Ok(())
+}
+
+// The buyer pays the price at the latest header deps timestamp to the seller by the output at the
+// same index, and the start price without header deps
+fn validate_dutch_purchase(
+    auction: &Auction,
+    start_price: u128,
+    start_time: u64,
+    index: usize,
+) -> Result<(), Error> {
+    let now = load_header_deps_timestamp().unwrap_or(start_time);
+    let price = auction.dutch_price(start_price, start_time, now);
+    let paid = load_paid_amount(index, &Currency::Ckb, &auction.seller_lock_hash)?;
+    if paid < price {
+        trace!(
+            "dutch auction at {} paid {} for the price {}",
+            now,
+            paid,
+            price
+        );
+        return Err(Error::AuctionPaymentNotEnough);
+    }
+    Ok(())
+}
+
+// The output of the nft with the same type as the auction input, and the nft type keeps the
+// token unique in the outputs
+fn load_nft_output_index(index: usize) -> Result<usize, Error> {
+    let nft_type = load_cell_type(index, Source::Input)?.ok_or(Error::NFTDataInvalid)?;
+    QueryIter::new(load_cell_type, Source::Output)
+        .position(|type_opt| {
+            type_opt.map_or(false, |type_| type_.as_slice() == nft_type.as_slice())
+        })
+        .ok_or(Error::AuctionSettlementInvalid)
+}
+
+// The nft output under the auction lock with the same code carries the next bid
+fn load_next_auction(script: &Script, nft_index: usize) -> Result<Option<Auction>, Error> {
+    let lock = load_cell_lock(nft_index, Source::Output)?;
+    if lock.code_hash().as_slice() != script.code_hash().as_slice()
+        || lock.hash_type().as_slice() != script.hash_type().as_slice()
+    {
+        return Ok(None);
+    }
+    let args: Bytes = lock.args().unpack();
+    Ok(Some(Auction::from_args(&args)?))
+}
+
+pub fn main() -> Result<(), Error> {
+    let script = load_script()?;
+    let args: Bytes = script.args().unpack();
+    let auction = Auction::from_args(&args)?;
+
+    // The seller cancels the auction without bids
+    let has_bid = matches!(auction.kind, AuctionKind::English(Some(_)));
+    if !has_bid && inputs_have_lock_hash(&auction.seller_lock_hash) {
+        return Ok(());
+    }
+
+    let auction_lock_hash = load_script_hash()?;
+    let auction_indexes = QueryIter::new(load_cell_lock_hash, Source::Input)
+        .enumerate()
+        .filter(|(_, lock_hash)| lock_hash == &auction_lock_hash)
+        .map(|(index, _)| index);
+    for index in auction_indexes {
+        check_nft_transferable(&load_nft_of_input(index, &auction.nft_code)?)?;
+        match &auction.kind {
+            AuctionKind::Dutch {
+                start_price,
+                start_time,
+            } => validate_dutch_purchase(&auction, *start_price, *start_time, index)?,
+            AuctionKind::English(_) => {
+                let nft_index = load_nft_output_index(index)?;
+                match load_next_auction(&script, nft_index)? {
+                    Some(next_auction) => validate_bid(&auction, &next_auction, index, nft_index)?,
+                    None => validate_settlement(&auction, index, nft_index)?,
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{bytes::Bytes, prelude::*},
+    high_level::{
+        load_cell_lock_hash, load_input_since, load_script, load_script_hash, load_witness_args,
+        QueryIter,
+    },
+};
+use core::result::Result;
+use script_utils::{
+    error::Error,
+    helper::{
+        blake2b_256, load_cell_data_header, new_blake2b, since_relative_block_number, HASH_LEN,
+    },
+    market::{
+        check_nft_claimable, check_nft_transferable, inputs_have_lock_hash, load_nft_of_input,
+        NftCode,
+    },
+    nft::{Nft, NFT_DATA_MIN_LEN},
+    trace,
+};
+
+// code_hash + owner_lock_hash + nft_code_hash + nft_hash_type(u8)
+const CLAIM_ARGS_LEN: usize = 97;
+// The blocks between the recipient commitment and the claim
+const CLAIM_COMMITMENT_MATURITY: u64 = 10;
+
+/// Claim lock args structure:
+/// 1) code_hash: [u8; 32], the blake2b hash of the claim code
+/// 2) owner_lock_hash: [u8; 32], the owner takes back the unclaimed nft with any of its inputs
+/// 3) nft_code_hash: [u8; 32] + nft_hash_type: u8, the code of the nft type of the claimed nfts
+struct ClaimLock {
+    code_hash:       [u8; 32],
+    owner_lock_hash: [u8; 32],
+    nft_code:        NftCode,
+}
+
+impl ClaimLock {
+    fn from_args(args: &[u8]) -> Result<Self, Error> {
+        if args.len() != CLAIM_ARGS_LEN {
+            return Err(Error::ClaimLockArgsInvalid);
+        }
+        let mut code_hash = [0u8; HASH_LEN];
+        code_hash.copy_from_slice(&args[0..HASH_LEN]);
+        let mut owner_lock_hash = [0u8; HASH_LEN];
+        owner_lock_hash.copy_from_slice(&args[HASH_LEN..HASH_LEN * 2]);
+        let nft_code =
+            NftCode::from_slice(&args[HASH_LEN * 2..]).ok_or(Error::ClaimLockArgsInvalid)?;
+        Ok(ClaimLock {
+            code_hash,
+            owner_lock_hash,
+            nft_code,
+        })
+    }
+}
+
+/// The witness lock of the claimed input: <recipient_lock_hash: [u8; 32]> + <claim_code>, and
+/// the recipient lock hash is bound to the revealed claim code by the recipient commitment
+fn load_recipient_lock_hash(index: usize, code_hash: &[u8; 32]) -> Result<[u8; 32], Error> {
+    let witness_lock: Option<Bytes> = load_witness_args(index, Source::Input)
+        .map_err(|_| Error::ClaimCodeInvalid)?
+        .lock()
+        .to_opt()
+        .map(|lock| lock.unpack());
+    let witness_lock = witness_lock.ok_or(Error::ClaimCodeInvalid)?;
+    if witness_lock.len() <= HASH_LEN || &blake2b_256(&witness_lock[HASH_LEN..]) != code_hash {
+        trace!("claim code of input {} does not match the code hash", index);
+        return Err(Error::ClaimCodeInvalid);
+    }
+    let mut recipient_lock_hash = [0u8; HASH_LEN];
+    recipient_lock_hash.copy_from_slice(&witness_lock[0..HASH_LEN]);
+    check_recipient_commitment(&witness_lock[HASH_LEN..], &recipient_lock_hash)?;
+    Ok(recipient_lock_hash)
+}
+
+// The claim code revealed in the mempool could be copied to claim the nft for another recipient,
+// so the recipient commits blake2b(<claim_code> + <recipient_lock_hash>) in the data of a cell
+// before revealing the code, and spends the commitment cell with a relative since of at least
+// CLAIM_COMMITMENT_MATURITY blocks. The copied code would need a commitment as old as that.
+fn check_recipient_commitment(
+    claim_code: &[u8],
+    recipient_lock_hash: &[u8; 32],
+) -> Result<(), Error> {
+    let mut blake2b = new_blake2b();
+    blake2b.update(claim_code);
+    blake2b.update(recipient_lock_hash);
+    let mut commitment = [0u8; HASH_LEN];
+    blake2b.finalize(&mut commitment);
+
+    for (index, since) in QueryIter::new(load_input_since, Source::Input).enumerate() {
+        let matured = since_relative_block_number(since)
+            .map_or(false, |blocks| blocks >= CLAIM_COMMITMENT_MATURITY);
+        if !matured {
+            continue;
+        }
+        let (data, data_len) = load_cell_data_header(index, Source::Input, HASH_LEN)?;
+        if data_len == HASH_LEN && data[..] == commitment[..] {
+            return Ok(());
+        }
+    }
+    trace!(
+        "no input matured for {} blocks commits the recipient",
+        CLAIM_COMMITMENT_MATURITY
+    );
+    Err(Error::ClaimCommitmentMissing)
+}
+
+// The output at the same index is the claimed nft under the recipient lock, and the nft type
+// validates the claim state flipping with validate_nft_claim
+fn check_claimed_output(index: usize, recipient_lock_hash: &[u8; 32]) -> Result<(), Error> {
+    if &load_cell_lock_hash(index, Source::Output)? != recipient_lock_hash {
+        return Err(Error::NFTClaimInvalid);
+    }
+    let (header, _) = load_cell_data_header(index, Source::Output, NFT_DATA_MIN_LEN)
+        .map_err(|_| Error::NFTClaimInvalid)?;
+    if !Nft::from_data(&header)?.is_claimed() {
+        return Err(Error::NFTClaimInvalid);
+    }
+    Ok(())
+}
+
+pub fn main() -> Result<(), Error> {
+    let script = load_script()?;
+    let args: Bytes = script.args().unpack();
+    let claim_lock = ClaimLock::from_args(&args)?;
+
+    // The owner takes back the unclaimed nft
+    if inputs_have_lock_hash(&claim_lock.owner_lock_hash) {
+        return Ok(());
+    }
+
+    let claim_lock_hash = load_script_hash()?;
+    let claimed_indexes = QueryIter::new(load_cell_lock_hash, Source::Input)
+        .enumerate()
+        .filter(|(_, lock_hash)| lock_hash == &claim_lock_hash)
+        .map(|(index, _)| index);
+    for index in claimed_indexes {
+        let recipient_lock_hash = load_recipient_lock_hash(index, &claim_lock.code_hash)?;
+        let nft = load_nft_of_input(index, &claim_lock.nft_code)?;
+        if nft.is_claimed() {
+            return Err(Error::NFTClaimInvalid);
+        }
+        check_nft_claimable(&nft)?;
+        check_nft_transferable(&nft)?;
+        check_claimed_output(index, &recipient_lock_hash)?;
+    }
+    Ok(())
+}
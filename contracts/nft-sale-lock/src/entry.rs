@@ -0,0 +1,91 @@
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{bytes::Bytes, prelude::*},
+    high_level::{load_cell_lock_hash, load_script, load_script_hash, QueryIter},
+};
+use core::result::Result;
+use script_utils::{
+    error::Error,
+    helper::{u128_from_slice, HASH_LEN},
+    market::{
+        check_nft_transferable, inputs_have_lock_hash, load_nft_of_input, load_paid_amount,
+        Currency, NftCode,
+    },
+    trace,
+};
+
+// SELLER_LOCK_HASH_LEN + price(u128)
+const SELLER_PRICE_LEN: usize = 48;
+// SELLER_PRICE_LEN + nft_code_hash + nft_hash_type(u8)
+const CKB_SALE_ARGS_LEN: usize = 81;
+// CKB_SALE_ARGS_LEN + sudt type hash
+const SUDT_SALE_ARGS_LEN: usize = 113;
+
+/// Sale lock args structure:
+/// 1) seller_lock_hash: [u8; 32]
+/// 2) price: u128
+/// 3) nft_code_hash: [u8; 32] + nft_hash_type: u8, the code of the nft type of the listed nfts
+/// 4) sudt_type_hash: [u8; 32], the price is in shannons without it
+struct Sale {
+    seller_lock_hash: [u8; 32],
+    price:            u128,
+    nft_code:         NftCode,
+    currency:         Currency,
+}
+
+impl Sale {
+    fn from_args(args: &[u8]) -> Result<Self, Error> {
+        let currency = match args.len() {
+            CKB_SALE_ARGS_LEN => Currency::Ckb,
+            SUDT_SALE_ARGS_LEN => {
+                let mut type_hash = [0u8; HASH_LEN];
+                type_hash.copy_from_slice(&args[CKB_SALE_ARGS_LEN..]);
+                Currency::Sudt(type_hash)
+            }
+            _ => return Err(Error::SaleLockArgsInvalid),
+        };
+        let mut seller_lock_hash = [0u8; HASH_LEN];
+        seller_lock_hash.copy_from_slice(&args[0..HASH_LEN]);
+        let nft_code = NftCode::from_slice(&args[SELLER_PRICE_LEN..CKB_SALE_ARGS_LEN])
+            .ok_or(Error::SaleLockArgsInvalid)?;
+        Ok(Sale {
+            seller_lock_hash,
+            price: u128_from_slice(&args[HASH_LEN..SELLER_PRICE_LEN]),
+            nft_code,
+            currency,
+        })
+    }
+}
+
+pub fn main() -> Result<(), Error> {
+    let script = load_script()?;
+    let args: Bytes = script.args().unpack();
+    let sale = Sale::from_args(&args)?;
+
+    // The seller cancels the listing with any of its inputs
+    if inputs_have_lock_hash(&sale.seller_lock_hash) {
+        return Ok(());
+    }
+
+    // The buyer pays the price to the seller with the output at the same index as every listed
+    // nft, so that one payment cannot settle two listings
+    let sale_lock_hash = load_script_hash()?;
+    let listed_indexes = QueryIter::new(load_cell_lock_hash, Source::Input)
+        .enumerate()
+        .filter(|(_, lock_hash)| lock_hash == &sale_lock_hash)
+        .map(|(index, _)| index);
+    for index in listed_indexes {
+        check_nft_transferable(&load_nft_of_input(index, &sale.nft_code)?)?;
+        let paid = load_paid_amount(index, &sale.currency, &sale.seller_lock_hash)?;
+        if paid < sale.price {
+            trace!(
+                "sale of input {} paid {} for the price {}",
+                index,
+                paid,
+                sale.price
+            );
+            return Err(Error::SalePaymentNotEnough);
+        }
+    }
+    Ok(())
+}
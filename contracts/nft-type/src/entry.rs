@@ -1,23 +1,29 @@
 use crate::validator::{
-    validate_immutable_nft_fields, validate_nft_claim, validate_nft_ext_info, validate_nft_lock,
-    validate_nft_transfer,
+    validate_immutable_nft_fields, validate_nft_characteristic_policies, validate_nft_claim,
+    validate_nft_ext_info, validate_nft_ext_info_records, validate_nft_lock,
+    validate_nft_migration, validate_nft_reveal, validate_nft_transfer,
 };
 use alloc::vec::Vec;
 use ckb_std::{
     ckb_constants::Source,
     ckb_types::{bytes::Bytes, packed::*, prelude::*},
-    high_level::{load_cell_data, load_script},
+    high_level::{load_cell_data, load_cell_lock, load_cell_lock_hash, load_script},
 };
 use core::result::Result;
 use script_utils::{
-    class::{Class, CLASS_TYPE_ARGS_LEN},
+    class::{Class, Ingredient, CLASS_TYPE_ARGS_LEN},
     error::Error,
+    ext_info::{Provenance, PROVENANCE_RECORD_LEN},
     helper::{
-        cell_deps_and_inputs_have_issuer_or_class_lock,
-        check_group_input_witness_is_none_with_type, count_cells_by_type, load_cell_data_by_type,
-        load_class_type, load_output_type_args_ids, Action,
+        blake2b_256, cell_deps_and_inputs_have_issuer_or_class_lock, load_cell_data_from,
+        load_cell_data_header, load_class_type, load_declared_action, u32_from_slice, Action,
+        TxIndex, HASH_LEN,
     },
-    nft::{Nft, NFT_TYPE_ARGS_LEN},
+    lock_policy::{check_group_locks, check_lock_policy},
+    nft::{reveal_characteristic, Nft, NFT_DATA_MIN_LEN, NFT_TYPE_ARGS_LEN},
+    redemption::{Receipt, ReceiptCode},
+    trace,
+    vault::{shares_type, Vault, VAULT_LOCK_ARGS_LEN},
 };
 
 fn check_class_type<'a>(nft_args: &'a Bytes) -> impl Fn(&Script) -> bool + 'a {
@@ -27,17 +33,81 @@ fn check_class_type<'a>(nft_args: &'a Bytes) -> impl Fn(&Script) -> bool + 'a {
 
 fn check_nft_type<'a>(nft_type: &'a Script) -> impl Fn(&Script) -> bool + 'a {
     let nft_args: Bytes = nft_type.args().unpack();
+    check_nft_type_of_class(nft_type, nft_args[0..CLASS_TYPE_ARGS_LEN].to_vec())
+}
+
+// The nft cells of the same nft code with any class, or with the class of the class args
+fn check_nft_code<'a>(nft_type: &'a Script) -> impl Fn(&Script) -> bool + 'a {
     move |type_: &Script| {
-        let type_args: Bytes = type_.args().unpack();
         type_.code_hash().as_slice() == nft_type.code_hash().as_slice()
             && type_.hash_type().as_slice() == nft_type.hash_type().as_slice()
-            && type_args.len() == NFT_TYPE_ARGS_LEN
-            && type_args[0..CLASS_TYPE_ARGS_LEN] == nft_args[0..CLASS_TYPE_ARGS_LEN]
+            && type_.args().raw_data().len() == NFT_TYPE_ARGS_LEN
+    }
+}
+
+fn check_nft_type_of_class<'a>(
+    nft_type: &'a Script,
+    class_args: Vec<u8>,
+) -> impl Fn(&Script) -> bool + 'a {
+    move |type_: &Script| {
+        let type_args: Bytes = type_.args().unpack();
+        check_nft_code(nft_type)(type_) && type_args[0..CLASS_TYPE_ARGS_LEN] == class_args[..]
+    }
+}
+
+// Only the header of the nft data is loaded, and the ext info is loaded or compared on demand.
+// Return the nft and the length of the whole nft data.
+fn load_nft(source: Source) -> Result<(Nft, usize), Error> {
+    let (header, data_len) =
+        load_cell_data_header(0, source, NFT_DATA_MIN_LEN).map_err(|_| Error::NFTDataInvalid)?;
+    Ok((Nft::from_data(&header[..])?, data_len))
+}
+
+fn load_nft_ext_info(source: Source, offset: usize) -> Result<Vec<u8>, Error> {
+    load_cell_data_from(0, source, offset).map_err(|_| Error::NFTDataInvalid)
+}
+
+// The provenance record leads the ext info of the nft of a class with provenance
+fn load_nft_provenance(source: Source) -> Result<Provenance, Error> {
+    let (header, _) = load_cell_data_header(0, source, NFT_DATA_MIN_LEN + PROVENANCE_RECORD_LEN)
+        .map_err(|_| Error::NFTDataInvalid)?;
+    header
+        .get(NFT_DATA_MIN_LEN..)
+        .and_then(Provenance::from_ext_info)
+        .ok_or(Error::NFTProvenanceInvalid)
+}
+
+// The ext info records start after the provenance record if the class has provenance
+fn ext_info_start(class: Option<&Class>) -> usize {
+    match class {
+        Some(class) if class.provenance => NFT_DATA_MIN_LEN + PROVENANCE_RECORD_LEN,
+        _ => NFT_DATA_MIN_LEN,
     }
 }
 
-fn load_nft_data(source: Source) -> Result<Vec<u8>, Error> {
-    load_cell_data(0, source).map_err(|_| Error::NFTDataInvalid)
+fn load_class_with_nft_args(tx_index: &TxIndex, nft_args: &Bytes) -> Result<Class, Error> {
+    let data =
+        match tx_index.load_cell_data_by_type(Source::CellDep, &check_class_type(nft_args))? {
+            Some(data) => data,
+            None => tx_index
+                .load_cell_data_by_type(Source::Input, &check_class_type(nft_args))?
+                .ok_or(Error::ClassCellsCountError)?,
+        };
+    Class::from_data(&data)
+}
+
+fn load_revealed_characteristic(
+    tx_index: &TxIndex,
+    nft_args: &Bytes,
+    seed: &[u8],
+) -> Result<[u8; 8], Error> {
+    let class = load_class_with_nft_args(tx_index, nft_args)?;
+    let seed_hash = class.seed_hash.ok_or(Error::NFTRevealSeedInvalid)?;
+    if blake2b_256(seed) != seed_hash {
+        return Err(Error::NFTRevealSeedInvalid);
+    }
+    let token_id = u32_from_slice(&nft_args[CLASS_TYPE_ARGS_LEN..]);
+    Ok(reveal_characteristic(seed, token_id))
 }
 
 fn issuer_or_class_lock_has_approved(nft_args: &Bytes) -> Result<bool, Error> {
@@ -47,13 +117,13 @@ fn issuer_or_class_lock_has_approved(nft_args: &Bytes) -> Result<bool, Error> {
     Ok(true)
 }
 
-fn parse_nft_action(nft_type: &Script) -> Result<Action, Error> {
-    let nft_inputs_count = count_cells_by_type(Source::Input, &check_nft_type(nft_type));
+fn parse_nft_action(tx_index: &TxIndex, nft_type: &Script) -> Result<Action, Error> {
+    let nft_inputs_count = tx_index.count_cells_by_type(Source::Input, &check_nft_type(nft_type));
     if nft_inputs_count == 0 {
         return Ok(Action::Create);
     }
 
-    let nft_outputs_count = count_cells_by_type(Source::Output, &check_nft_type(nft_type));
+    let nft_outputs_count = tx_index.count_cells_by_type(Source::Output, &check_nft_type(nft_type));
     if nft_outputs_count == 0 {
         return Ok(Action::Destroy);
     }
@@ -61,36 +131,191 @@ fn parse_nft_action(nft_type: &Script) -> Result<Action, Error> {
     if nft_inputs_count == nft_outputs_count {
         return Ok(Action::Update);
     }
+    trace!(
+        "nft cells count: {} inputs at {:?}, {} outputs at {:?}",
+        nft_inputs_count,
+        tx_index.positions_by_type(Source::Input, &check_nft_type(nft_type)),
+        nft_outputs_count,
+        tx_index.positions_by_type(Source::Output, &check_nft_type(nft_type))
+    );
     Err(Error::NFTCellsCountError)
 }
 
-fn handle_creation(nft_type: &Script) -> Result<(), Error> {
+// The minted or crafted nft must follow the version, the policies and the configure of its class
+fn validate_created_nft(class: &Class) -> Result<(), Error> {
+    let (nft, _) = load_nft(Source::GroupOutput)?;
+    if nft.version != class.version {
+        trace!(
+            "nft version {} differs from class version {}",
+            nft.version,
+            class.version
+        );
+        return Err(Error::VersionInvalid);
+    }
+    if let Some(policy) = &class.lock_policy {
+        check_lock_policy(policy, Source::GroupOutput)?;
+    }
+    // The nft of a class with provenance starts with no transfers
+    if class.provenance && load_nft_provenance(Source::GroupOutput)? != Provenance::default() {
+        return Err(Error::NFTProvenanceInvalid);
+    }
+    if nft.version == 1 {
+        let ext_info = load_nft_ext_info(Source::GroupOutput, ext_info_start(Some(class)))?;
+        validate_nft_ext_info_records(&ext_info, false)?;
+    }
+    if nft.configure != class.configure {
+        return Err(Error::NFTAndClassConfigureNotSame);
+    }
+
+    // The characteristics are revealed after minting if the class has committed to a seed
+    if class.seed_hash.is_some() && nft.characteristic != [0u8; 8] {
+        return Err(Error::NFTCharacteristicNotZero);
+    }
+    Ok(())
+}
+
+// The token ids of the nfts of the class which are destroyed in the transaction
+fn load_destroyed_token_ids(tx_index: &TxIndex, nft_type: &Script, class_args: &[u8]) -> Vec<u32> {
+    let check_class = check_nft_type_of_class(nft_type, class_args.to_vec());
+    let output_token_ids =
+        tx_index.load_type_args_ids(Source::Output, CLASS_TYPE_ARGS_LEN, &check_class);
+    tx_index
+        .load_type_args_ids(Source::Input, CLASS_TYPE_ARGS_LEN, &check_class)
+        .into_iter()
+        .filter(|token_id| !output_token_ids.contains(token_id))
+        .collect()
+}
+
+// The crafted nft types of the transaction: the nft outputs created without their class cell as
+// input, which are validated against the recipes of their classes
+fn load_crafted_types(tx_index: &TxIndex, nft_type: &Script) -> Vec<Script> {
+    tx_index
+        .load_types(Source::Output, &check_nft_code(nft_type))
+        .into_iter()
+        .filter(|type_| {
+            let type_args: Bytes = type_.args().unpack();
+            tx_index.count_cells_by_type(Source::Input, &check_class_type(&type_args)) == 0
+                && tx_index.count_cells_by_type(Source::Input, &|input_type: &Script| {
+                    input_type.as_slice() == type_.as_slice()
+                }) == 0
+        })
+        .collect()
+}
+
+// The nft of a class with a recipe is crafted without the class cell as input when the exact
+// ingredients are destroyed in the same transaction. The class with a recipe cannot mint, and the
+// crafted token id must be one of the destroyed token ids of the first ingredient which is not
+// issued by the class and within its total, so that every crafted token id is unique. Every
+// destroyed ingredient is consumed by exactly one crafted nft, so the destroyed count of each
+// ingredient class must equal the sum of the counts of all the recipes crafted in the transaction.
+fn handle_crafting(tx_index: &TxIndex, nft_type: &Script) -> Result<(), Error> {
+    let nft_args: Bytes = nft_type.args().unpack();
+    let class = load_class_with_nft_args(tx_index, &nft_args)?;
+    let recipe = class.recipe.as_ref().ok_or(Error::ClassCellsCountError)?;
+
+    let crafted_count = tx_index.count_cells_by_type(Source::Output, &check_nft_type(nft_type));
+    if crafted_count != 1 {
+        trace!(
+            "{} nfts are crafted with one recipe at {:?}",
+            crafted_count,
+            tx_index.positions_by_type(Source::Output, &check_nft_type(nft_type))
+        );
+        return Err(Error::NFTRecipeNotSatisfied);
+    }
+    let crafted_recipes: Vec<Vec<Ingredient>> = load_crafted_types(tx_index, nft_type)
+        .into_iter()
+        .filter_map(|crafted_type| {
+            let crafted_args: Bytes = crafted_type.args().unpack();
+            load_class_with_nft_args(tx_index, &crafted_args)
+                .ok()
+                .and_then(|class| class.recipe)
+        })
+        .collect();
+    for ingredient in recipe {
+        let destroyed_count =
+            load_destroyed_token_ids(tx_index, nft_type, &ingredient.class_args).len();
+        let required_count: usize = crafted_recipes
+            .iter()
+            .flatten()
+            .filter(|required| required.class_args == ingredient.class_args)
+            .map(|required| required.count as usize)
+            .sum();
+        if destroyed_count != required_count {
+            trace!(
+                "{} ingredient nfts destroyed for the count {} of all the crafted recipes",
+                destroyed_count,
+                required_count
+            );
+            return Err(Error::NFTRecipeNotSatisfied);
+        }
+    }
+    let token_id = u32_from_slice(&nft_args[CLASS_TYPE_ARGS_LEN..]);
+    if token_id < class.issued || (class.total > 0 && token_id >= class.total) {
+        trace!(
+            "crafted token id {} is issued or out of the total: {} / {}",
+            token_id,
+            class.issued,
+            class.total
+        );
+        return Err(Error::NFTRecipeNotSatisfied);
+    }
+    if !load_destroyed_token_ids(tx_index, nft_type, &recipe[0].class_args).contains(&token_id) {
+        return Err(Error::NFTRecipeNotSatisfied);
+    }
+    validate_created_nft(&class)
+}
+
+fn handle_creation(tx_index: &TxIndex, nft_type: &Script) -> Result<(), Error> {
     let nft_args: Bytes = nft_type.args().unpack();
-    let class_inputs_count = count_cells_by_type(Source::Input, &check_class_type(&nft_args));
+    let class_inputs_count =
+        tx_index.count_cells_by_type(Source::Input, &check_class_type(&nft_args));
+    if class_inputs_count == 0 {
+        return handle_crafting(tx_index, nft_type);
+    }
     if class_inputs_count != 1 {
+        trace!(
+            "class inputs count of the nft: {} at {:?}",
+            class_inputs_count,
+            tx_index.positions_by_type(Source::Input, &check_class_type(&nft_args))
+        );
         return Err(Error::ClassCellsCountError);
     }
 
-    let load_class = |source| match load_cell_data_by_type(source, &check_class_type(&nft_args)) {
-        Some(data) => Ok(Class::from_data(&data)?),
-        None => Err(Error::ClassDataInvalid),
-    };
+    let load_class =
+        |source| match tx_index.load_cell_data_by_type(source, &check_class_type(&nft_args))? {
+            Some(data) => Ok(Class::from_data(&data)?),
+            None => Err(Error::ClassDataInvalid),
+        };
     let input_class = load_class(Source::Input)?;
     let output_class = load_class(Source::Output)?;
 
+    if input_class.recipe.is_some() {
+        trace!("the nft of the class with a recipe can only be crafted");
+        return Err(Error::NFTRecipeNotSatisfied);
+    }
+
     if output_class.issued <= input_class.issued {
+        trace!(
+            "class issued of input {:?} not increased: {} -> {}",
+            tx_index.positions_by_type(Source::Input, &check_class_type(&nft_args)),
+            input_class.issued,
+            output_class.issued
+        );
         return Err(Error::ClassIssuedInvalid);
     }
 
-    let nft = Nft::from_data(&load_nft_data(Source::GroupOutput)?[..])?;
-    if nft.configure != input_class.configure {
-        return Err(Error::NFTAndClassConfigureNotSame);
-    }
+    validate_created_nft(&input_class)?;
 
     let outputs_token_ids =
-        load_output_type_args_ids(CLASS_TYPE_ARGS_LEN, &check_nft_type(nft_type));
+        tx_index.load_output_type_args_ids(CLASS_TYPE_ARGS_LEN, &check_nft_type(nft_type));
     let nft_outputs_increased_count = (output_class.issued - input_class.issued) as usize;
     if nft_outputs_increased_count != outputs_token_ids.len() {
+        trace!(
+            "class issued increased by {} with {} nft outputs at {:?}",
+            nft_outputs_increased_count,
+            outputs_token_ids.len(),
+            tx_index.positions_by_type(Source::Output, &check_nft_type(nft_type))
+        );
         return Err(Error::NFTCellsCountError);
     }
 
@@ -100,47 +325,211 @@ fn handle_creation(nft_type: &Script) -> Result<(), Error> {
     }
 
     if outputs_token_ids != class_cell_token_ids {
+        trace!(
+            "token ids {:?} of outputs {:?} differ from the class token ids {:?}",
+            outputs_token_ids,
+            tx_index.positions_by_type(Source::Output, &check_nft_type(nft_type)),
+            class_cell_token_ids
+        );
         return Err(Error::NFTTokenIdIncreaseError);
     }
 
     Ok(())
 }
 
-fn handle_update(nft_type: &Script) -> Result<(), Error> {
-    // Disable anyone-can-pay lock
-    if check_group_input_witness_is_none_with_type(nft_type)? {
-        return Err(Error::GroupInputWitnessNoneError);
+// The seed to reveal the characteristic is the params of the declared reveal action
+fn handle_update(
+    tx_index: &TxIndex,
+    nft_type: &Script,
+    reveal_seed: Option<Bytes>,
+) -> Result<(), Error> {
+    let (input_nft, input_len) = load_nft(Source::GroupInput)?;
+    let (output_nft, output_len) = load_nft(Source::GroupOutput)?;
+    let nfts = (input_nft, output_nft);
+    let nft_data_lens = (input_len, output_len);
+
+    if nfts.0.version != nfts.1.version {
+        trace!(
+            "nft version of output {:?} changed: {} -> {}",
+            tx_index.positions_by_type(Source::Output, &|type_: &Script| {
+                type_.as_slice() == nft_type.as_slice()
+            }),
+            nfts.0.version,
+            nfts.1.version
+        );
+        return Err(Error::VersionInvalid);
     }
-    let nft_data = (
-        load_nft_data(Source::GroupInput)?,
-        load_nft_data(Source::GroupOutput)?,
-    );
-    let nfts = (
-        Nft::from_data(&nft_data.0[..])?,
-        Nft::from_data(&nft_data.1[..])?,
-    );
-    validate_immutable_nft_fields(&nfts)?;
 
     let nft_args: Bytes = nft_type.args().unpack();
-    if !issuer_or_class_lock_has_approved(&nft_args)? {
+    // The lock policy and the provenance of the version 1 nft are defined by its class, so the
+    // class cell is required
+    let class = match nfts.0.version {
+        0 => None,
+        _ => Some(load_class_with_nft_args(tx_index, &nft_args)?),
+    };
+    check_group_locks(
+        nft_type,
+        &class.as_ref().and_then(|class| class.lock_policy.clone()),
+        &[Source::GroupInput, Source::GroupOutput],
+    )?;
+    let approved = issuer_or_class_lock_has_approved(&nft_args)?;
+    match reveal_seed {
+        Some(seed) => {
+            let characteristic = load_revealed_characteristic(tx_index, &nft_args, &seed)?;
+            validate_nft_reveal(&nfts, &characteristic)?;
+        }
+        None => {
+            validate_immutable_nft_fields(&nfts)?;
+            // The characteristic policies of the version 1 nft are defined by its class
+            let policies = class
+                .as_ref()
+                .and_then(|class| class.characteristic_policies.as_ref());
+            if let Some(policies) = policies {
+                if nfts.0.characteristic != nfts.1.characteristic {
+                    validate_nft_characteristic_policies(&nfts, policies, approved)?;
+                }
+            }
+        }
+    }
+
+    if !approved {
         validate_nft_claim(&nfts)?;
         validate_nft_lock(&nfts)?;
     }
-    validate_nft_transfer(&nfts.0)?;
-    validate_nft_ext_info(&nfts.0, &nft_data)?;
+    let provenances = match &class {
+        Some(class) if class.provenance => Some((
+            load_nft_provenance(Source::GroupInput)?,
+            load_nft_provenance(Source::GroupOutput)?,
+        )),
+        _ => None,
+    };
+    validate_nft_transfer(
+        &nfts.0,
+        provenances.as_ref(),
+        class
+            .as_ref()
+            .and_then(|class| class.transfer_cooldown.as_ref()),
+    )?;
+    let ext_info_start = ext_info_start(class.as_ref());
+    // The ext info can be rewritten or pruned with the approval of the issuer or class
+    if !approved {
+        validate_nft_ext_info(&nfts.0, nft_data_lens, ext_info_start)?;
+    }
+    // Only the appended records are checked unless the whole ext info is rewritten with approval
+    if nfts.1.version == 1 {
+        let records_index = if approved {
+            ext_info_start
+        } else {
+            nft_data_lens.0
+        };
+        let records = load_nft_ext_info(Source::GroupOutput, records_index)?;
+        validate_nft_ext_info_records(&records, approved)?;
+    }
     Ok(())
 }
 
-fn handle_destroying(nft_type: &Script) -> Result<(), Error> {
-    // Disable anyone-can-pay lock
-    if check_group_input_witness_is_none_with_type(nft_type)? {
-        return Err(Error::GroupInputWitnessNoneError);
+// The version 0 nft can be migrated to version 1 with the approval of the issuer or class, and
+// its opaque ext info is rewritten to the typed records
+fn handle_migration(tx_index: &TxIndex, nft_type: &Script) -> Result<(), Error> {
+    let nfts = (
+        load_nft(Source::GroupInput)?.0,
+        load_nft(Source::GroupOutput)?.0,
+    );
+
+    let nft_args: Bytes = nft_type.args().unpack();
+    let class = load_class_with_nft_args(tx_index, &nft_args)?;
+    check_group_locks(nft_type, &class.lock_policy, &[
+        Source::GroupInput,
+        Source::GroupOutput,
+    ])?;
+    if !issuer_or_class_lock_has_approved(&nft_args)? {
+        return Err(Error::NFTMigrationInvalid);
+    }
+    validate_nft_migration(&nfts, class.version)?;
+    // The migrated nft of a class with provenance starts its provenance with the migration
+    let provenances = if class.provenance {
+        Some((
+            Provenance::default(),
+            load_nft_provenance(Source::GroupOutput)?,
+        ))
+    } else {
+        None
+    };
+    validate_nft_transfer(
+        &nfts.0,
+        provenances.as_ref(),
+        class.transfer_cooldown.as_ref(),
+    )?;
+    let ext_info = load_nft_ext_info(Source::GroupOutput, ext_info_start(Some(&class)))?;
+    validate_nft_ext_info_records(&ext_info, true)
+}
+
+// The nft destroyed as an ingredient is approved by the recipe of the nft crafted in the same
+// transaction, and the crafted nft checks the counts of the ingredients
+fn recipe_has_approved(tx_index: &TxIndex, nft_type: &Script) -> Result<bool, Error> {
+    let nft_args: Bytes = nft_type.args().unpack();
+    for crafted_type in load_crafted_types(tx_index, nft_type) {
+        let crafted_args: Bytes = crafted_type.args().unpack();
+        let recipe = match load_class_with_nft_args(tx_index, &crafted_args) {
+            Ok(class) => class.recipe,
+            Err(_) => continue,
+        };
+        let is_ingredient = recipe.map_or(false, |recipe| {
+            recipe
+                .iter()
+                .any(|ingredient| ingredient.class_args[..] == nft_args[0..CLASS_TYPE_ARGS_LEN])
+        });
+        if is_ingredient {
+            return Ok(true);
+        }
     }
+    Ok(false)
+}
+
+// The nft of a class with the redemption option is burned with a receipt output carrying its
+// type hash and the lock hash of the redeemer, and the receipt type validates the receipt
+fn check_redemption_receipt(
+    tx_index: &TxIndex,
+    nft_type: &Script,
+    receipt_code: &ReceiptCode,
+) -> Result<(), Error> {
+    let nft_args: Bytes = nft_type.args().unpack();
+    let receipt_type = receipt_code.receipt_type(&nft_args[0..CLASS_TYPE_ARGS_LEN], nft_type);
+    let receipt_data = Receipt {
+        nft_type_hash:      blake2b_256(nft_type.as_slice()),
+        redeemer_lock_hash: load_cell_lock_hash(0, Source::GroupInput)?,
+    }
+    .to_data();
+    let receipt_indexes = tx_index.positions_by_type(Source::Output, &|type_: &Script| {
+        type_.as_slice() == receipt_type.as_slice()
+    });
+    for index in receipt_indexes {
+        if load_cell_data(index, Source::Output)? == receipt_data {
+            return Ok(());
+        }
+    }
+    Err(Error::RedemptionReceiptMissing)
+}
+
+fn handle_destroying(tx_index: &TxIndex, nft_type: &Script) -> Result<(), Error> {
     let nft_args: Bytes = nft_type.args().unpack();
-    if issuer_or_class_lock_has_approved(&nft_args)? {
+    let (input_nft, _) = load_nft(Source::GroupInput)?;
+    // The version 1 nft follows the lock policy and the redemption option of its class
+    let class = match input_nft.version {
+        0 => None,
+        _ => Some(load_class_with_nft_args(tx_index, &nft_args)?),
+    };
+    check_group_locks(
+        nft_type,
+        &class.as_ref().and_then(|class| class.lock_policy.clone()),
+        &[Source::GroupInput],
+    )?;
+    if let Some(receipt_code) = class.and_then(|class| class.redemption) {
+        check_redemption_receipt(tx_index, nft_type, &receipt_code)?;
+    }
+    if issuer_or_class_lock_has_approved(&nft_args)? || recipe_has_approved(tx_index, nft_type)? {
         return Ok(());
     }
-    let input_nft = Nft::from_data(&load_nft_data(Source::GroupInput)?[..])?;
     if input_nft.is_locked() {
         return Err(Error::LockedNFTCannotDestroy);
     }
@@ -153,6 +542,29 @@ fn handle_destroying(nft_type: &Script) -> Result<(), Error> {
     Ok(())
 }
 
+// The vault shares must be minted along with the nft locked into the vault whose lock args commit
+// to the nft and its code, otherwise the nft could never be released
+fn check_vault_shares_minted(tx_index: &TxIndex, nft_type: &Script) -> Result<(), Error> {
+    let lock = load_cell_lock(0, Source::GroupOutput)?;
+    let lock_args: Bytes = lock.args().unpack();
+    let nft_type_hash = blake2b_256(nft_type.as_slice());
+    if lock_args.len() != VAULT_LOCK_ARGS_LEN || lock_args[0..HASH_LEN] != nft_type_hash[..] {
+        return Ok(());
+    }
+    if !Vault::from_args(&lock_args)?.nft_code.is_nft_type(nft_type) {
+        return Ok(());
+    }
+    let shares_type = shares_type(&lock, &nft_type_hash);
+    let shares_count = tx_index.count_cells_by_type(Source::Output, &|type_: &Script| {
+        type_.as_slice() == shares_type.as_slice()
+    });
+    if shares_count == 0 {
+        trace!("the nft is locked into the vault without minting the shares");
+        return Err(Error::VaultSharesInvalid);
+    }
+    Ok(())
+}
+
 pub fn main() -> Result<(), Error> {
     let nft_type = load_script()?;
     let nft_args: Bytes = nft_type.args().unpack();
@@ -160,9 +572,13 @@ pub fn main() -> Result<(), Error> {
         return Err(Error::TypeArgsInvalid);
     }
 
-    match parse_nft_action(&nft_type)? {
-        Action::Create => handle_creation(&nft_type),
-        Action::Update => handle_update(&nft_type),
-        Action::Destroy => handle_destroying(&nft_type),
+    let tx_index = TxIndex::load();
+    match load_declared_action(parse_nft_action(&tx_index, &nft_type)?)? {
+        (Action::Create, _) => handle_creation(&tx_index, &nft_type)?,
+        (Action::Update, _) => handle_update(&tx_index, &nft_type, None)?,
+        (Action::Reveal, seed) => handle_update(&tx_index, &nft_type, Some(seed))?,
+        (Action::Migrate, _) => handle_migration(&tx_index, &nft_type)?,
+        (Action::Destroy, _) => return handle_destroying(&tx_index, &nft_type),
     }
+    check_vault_shares_minted(&tx_index, &nft_type)
 }
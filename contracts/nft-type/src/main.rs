@@ -17,6 +17,10 @@ default_alloc!();
 fn program_entry() -> i8 {
     match entry::main() {
         Ok(_) => 0,
-        Err(err) => err as i8,
+        Err(err) => {
+            let code = err as i8;
+            script_utils::trace!("nft-type failed with error {}", code);
+            code
+        }
     }
 }
@@ -1,13 +1,22 @@
-use alloc::vec::Vec;
-use ckb_std::{ckb_constants::Source, ckb_types::prelude::*, high_level::load_cell_lock};
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::prelude::*,
+    high_level::{load_cell_lock, load_cell_lock_hash, load_input_since, QueryIter},
+};
 use core::result::Result;
 use script_utils::{
+    class::{CharacteristicPolicy, TransferCooldown, UpdatePolicy},
     error::Error,
+    ext_info::{parse_ext_info, ExtInfoRecord, Provenance},
+    helper::cell_data_range_equal,
+    market::check_nft_claimable,
     nft::{Nft, NFT_DATA_MIN_LEN},
+    trace,
 };
 
 type Nfts = (Nft, Nft);
-type NftDataTuple = (Vec<u8>, Vec<u8>);
+// The lengths of the input and output nft data
+type NftDataLens = (usize, usize);
 
 pub fn validate_immutable_nft_fields((input_nft, output_nft): &Nfts) -> Result<(), Error> {
     if input_nft.characteristic != output_nft.characteristic {
@@ -24,17 +33,91 @@ pub fn validate_immutable_nft_fields((input_nft, output_nft): &Nfts) -> Result<(
     Ok(())
 }
 
-pub fn validate_nft_claim((input_nft, output_nft): &Nfts) -> Result<(), Error> {
-    match (input_nft.is_claimed(), output_nft.is_claimed()) {
-        (false, true) => {
-            if input_nft.is_locked() {
-                return Err(Error::LockedNFTCannotClaim);
+pub fn validate_nft_characteristic_policies(
+    (input_nft, output_nft): &Nfts,
+    policies: &[CharacteristicPolicy; 8],
+    approved: bool,
+) -> Result<(), Error> {
+    let characteristics = input_nft
+        .characteristic
+        .iter()
+        .zip(output_nft.characteristic.iter());
+    for (index, ((input, output), policy)) in characteristics.zip(policies.iter()).enumerate() {
+        if input == output {
+            continue;
+        }
+        trace!(
+            "nft characteristic {} updated: {} -> {}",
+            index,
+            input,
+            output
+        );
+        match policy.update {
+            UpdatePolicy::Immutable => return Err(Error::NFTCharacteristicImmutable),
+            UpdatePolicy::IncreaseOnly if output < input => {
+                return Err(Error::NFTCharacteristicNotIncreased)
             }
-            if !input_nft.allow_claim() {
-                return Err(Error::NFTDisallowClaimed);
+            UpdatePolicy::DecreaseOnly if output > input => {
+                return Err(Error::NFTCharacteristicNotDecreased)
             }
-            Ok(())
+            UpdatePolicy::IssuerOnly if !approved => {
+                return Err(Error::NFTCharacteristicIssuerOnly)
+            }
+            _ => (),
+        }
+        if *output < policy.min || *output > policy.max {
+            return Err(Error::NFTCharacteristicOutOfRange);
         }
+    }
+    Ok(())
+}
+
+pub fn validate_nft_reveal(
+    (input_nft, output_nft): &Nfts,
+    characteristic: &[u8; 8],
+) -> Result<(), Error> {
+    if input_nft.characteristic != [0u8; 8] {
+        return Err(Error::NFTCharacteristicNotZero);
+    }
+    if &output_nft.characteristic != characteristic {
+        trace!(
+            "revealed characteristic {:?} differs from {:?}",
+            output_nft.characteristic,
+            characteristic
+        );
+        return Err(Error::NFTRevealCharacteristicError);
+    }
+    if input_nft.configure != output_nft.configure {
+        return Err(Error::NFTConfigureNotSame);
+    }
+    Ok(())
+}
+
+pub fn validate_nft_migration(
+    (input_nft, output_nft): &Nfts,
+    class_version: u8,
+) -> Result<(), Error> {
+    if input_nft.version != 0 || output_nft.version != 1 || class_version != 1 {
+        trace!(
+            "nft migration from version {} to {} with class version {}",
+            input_nft.version,
+            output_nft.version,
+            class_version
+        );
+        return Err(Error::NFTMigrationInvalid);
+    }
+    if input_nft.characteristic != output_nft.characteristic
+        || input_nft.configure != output_nft.configure
+        || input_nft.state != output_nft.state
+    {
+        return Err(Error::NFTMigrationInvalid);
+    }
+    Ok(())
+}
+
+pub fn validate_nft_claim((input_nft, output_nft): &Nfts) -> Result<(), Error> {
+    match (input_nft.is_claimed(), output_nft.is_claimed()) {
+        (false, true) => check_nft_claimable(input_nft),
         (true, false) => Err(Error::NFTClaimedToUnclaimedError),
         _ => Ok(()),
     }
@@ -53,10 +136,43 @@ pub fn validate_nft_lock((input_nft, output_nft): &Nfts) -> Result<(), Error> {
     }
 }
 
-pub fn validate_nft_transfer(input_nft: &Nft) -> Result<(), Error> {
+// The input and output provenances of the nft of a class with provenance
+type Provenances = (Provenance, Provenance);
+
+// The provenance must count the transfer and roll the lock hash of the previous owner whenever
+// the lock changes, and must not change otherwise. The nft of a class with the transfer cooldown
+// can only change the lock after the cooldown proved by the since of the input.
+pub fn validate_nft_transfer(
+    input_nft: &Nft,
+    provenances: Option<&Provenances>,
+    cooldown: Option<&TransferCooldown>,
+) -> Result<(), Error> {
     let input_lock = load_cell_lock(0, Source::GroupInput)?;
     let output_lock = load_cell_lock(0, Source::GroupOutput)?;
-    if input_lock.as_slice() != output_lock.as_slice() {
+    let transferred = input_lock.as_slice() != output_lock.as_slice();
+    if let Some((input_provenance, output_provenance)) = provenances {
+        let expected_provenance = if transferred {
+            input_provenance
+                .transferred(&load_cell_lock_hash(0, Source::GroupInput)?)
+                .ok_or(Error::NFTProvenanceInvalid)?
+        } else {
+            input_provenance.clone()
+        };
+        if output_provenance != &expected_provenance {
+            trace!(
+                "nft provenance {:?} differs from {:?}",
+                output_provenance,
+                expected_provenance
+            );
+            return Err(Error::NFTProvenanceInvalid);
+        }
+    }
+    if transferred {
+        trace!(
+            "nft transferred with configure {} and state {}",
+            input_nft.configure,
+            input_nft.state
+        );
         if input_nft.is_locked() {
             return Err(Error::LockedNFTCannotTransfer);
         }
@@ -66,29 +182,41 @@ pub fn validate_nft_transfer(input_nft: &Nft) -> Result<(), Error> {
         if input_nft.is_claimed() && !input_nft.allow_transfer_after_claim() {
             return Err(Error::NFTCannotTransferAfterClaim);
         }
+        if let Some(cooldown) = cooldown {
+            let since = load_input_since(0, Source::GroupInput)?;
+            if !cooldown.is_passed(since) {
+                trace!("nft transferred with since {} before {:?}", since, cooldown);
+                return Err(Error::NFTTransferCooldownNotPassed);
+            }
+        }
     }
     Ok(())
 }
 
+// The ext info starts after the provenance record of the nft of a class with provenance
 pub fn validate_nft_ext_info(
     input_nft: &Nft,
-    (input_nft_data, output_nft_data): &NftDataTuple,
+    (input_len, output_len): NftDataLens,
+    ext_info_start: usize,
 ) -> Result<(), Error> {
-    let input_len = input_nft_data.len();
-    let output_len = output_nft_data.len();
-    if input_len == output_len
-        && input_nft_data[NFT_DATA_MIN_LEN..input_len]
-            == output_nft_data[NFT_DATA_MIN_LEN..input_len]
-    {
+    // The ext info is compared chunk by chunk instead of being loaded as a whole
+    let ext_info_equal = || {
+        cell_data_range_equal(
+            (0, Source::GroupInput),
+            (0, Source::GroupOutput),
+            ext_info_start,
+            input_len,
+        )
+    };
+    if input_len == output_len && ext_info_equal()? {
         return Ok(());
     }
+    trace!("nft data length changed: {} -> {}", input_len, output_len);
     if input_nft.allow_ext_info() {
         if input_len > output_len {
             return Err(Error::NFTExtInfoLenError);
         }
-        if input_nft_data[NFT_DATA_MIN_LEN..input_len]
-            != output_nft_data[NFT_DATA_MIN_LEN..input_len]
-        {
+        if !ext_info_equal()? {
             return Err(Error::NFTExtInfoCannotModify);
         }
         if input_nft.is_locked() {
@@ -100,3 +228,33 @@ pub fn validate_nft_ext_info(
 
     Ok(())
 }
+
+// The records appended by the holder must be whole and well-formed, and the signer of an annotation
+// must sign the transaction with one of the inputs. The provenance is maintained by the nft type,
+// so it can't be appended as a record even with approval.
+pub fn validate_nft_ext_info_records(records_data: &[u8], approved: bool) -> Result<(), Error> {
+    let records = parse_ext_info(records_data).ok_or_else(|| {
+        trace!("nft ext info records invalid: {:?}", records_data);
+        Error::NFTExtInfoRecordInvalid
+    })?;
+    if records
+        .iter()
+        .any(|record| matches!(record, ExtInfoRecord::Provenance(_)))
+    {
+        return Err(Error::NFTProvenanceInvalid);
+    }
+    if approved {
+        return Ok(());
+    }
+    for record in records {
+        if let ExtInfoRecord::SignedAnnotation { signer, .. } = record {
+            if !QueryIter::new(load_cell_lock_hash, Source::Input)
+                .any(|lock_hash| lock_hash == signer)
+            {
+                trace!("nft ext info signer {:?} missing in inputs", signer);
+                return Err(Error::NFTExtInfoSignerMissing);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use crate::shares;
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{bytes::Bytes, packed::*, prelude::*},
+    high_level::{
+        load_cell_data, load_cell_lock, load_cell_lock_hash, load_cell_type, load_cell_type_hash,
+        load_script, load_script_hash, QueryIter,
+    },
+};
+use core::result::Result;
+use script_utils::{
+    error::Error,
+    helper::blake2b_256,
+    market::{check_nft_transferable, load_nft_of_input, parse_sudt_amount},
+    trace,
+    vault::{shares_type, Vault, SHARES_TYPE_ARGS_LEN},
+};
+
+/// The hash of the shares type of the vault
+pub fn shares_type_hash(script: &Script, nft_type_hash: &[u8]) -> [u8; 32] {
+    blake2b_256(shares_type(script, nft_type_hash).as_slice())
+}
+
+/// Load the vault cell holding the nft under the vault lock of the same code, and return its index
+/// and the vault. The vaulted cell must be of the nft type code of the vault args.
+pub fn load_vault(
+    script: &Script,
+    source: Source,
+    nft_type_hash: &[u8; 32],
+) -> Result<Option<(usize, Vault)>, Error> {
+    let index = match QueryIter::new(load_cell_type_hash, source)
+        .position(|type_hash| type_hash.as_ref() == Some(nft_type_hash))
+    {
+        Some(index) => index,
+        None => return Ok(None),
+    };
+    let lock = load_cell_lock(index, source)?;
+    if lock.code_hash().as_slice() != script.code_hash().as_slice()
+        || lock.hash_type().as_slice() != script.hash_type().as_slice()
+    {
+        return Ok(None);
+    }
+    let args: Bytes = lock.args().unpack();
+    let vault = Vault::from_args(&args)?;
+    let nft_type = load_cell_type(index, source)?.ok_or(Error::VaultArgsInvalid)?;
+    if &vault.nft_type_hash != nft_type_hash || !vault.nft_code.is_nft_type(&nft_type) {
+        trace!("the vault args commit to another nft or nft type code");
+        return Err(Error::VaultArgsInvalid);
+    }
+    Ok(Some((index, vault)))
+}
+
+/// The sum of the shares amounts in the source, which are sUDT compatible
+pub fn sum_shares(shares_type_hash: &[u8; 32], source: Source) -> Result<u128, Error> {
+    QueryIter::new(load_cell_type_hash, source)
+        .enumerate()
+        .filter(|(_, type_hash)| type_hash.as_ref() == Some(shares_type_hash))
+        .try_fold(0u128, |sum, (index, _)| {
+            let data = load_cell_data(index, source)?;
+            let amount = parse_sudt_amount(&data).ok_or(Error::VaultSharesInvalid)?;
+            sum.checked_add(amount).ok_or(Error::VaultSharesInvalid)
+        })
+}
+
+pub fn main() -> Result<(), Error> {
+    let script = load_script()?;
+    let args: Bytes = script.args().unpack();
+    if args.len() == SHARES_TYPE_ARGS_LEN {
+        return shares::main(&script, &args);
+    }
+    let vault = Vault::from_args(&args)?;
+
+    // The nft is released by burning the full supply of the shares
+    let shares_type_hash = shares_type_hash(&script, &vault.nft_type_hash);
+    let burned = sum_shares(&shares_type_hash, Source::Input)?
+        .checked_sub(sum_shares(&shares_type_hash, Source::Output)?);
+    if burned != Some(vault.supply) {
+        trace!(
+            "vault release burned {:?} of the supply {}",
+            burned,
+            vault.supply
+        );
+        return Err(Error::VaultReleaseInvalid);
+    }
+
+    let vault_lock_hash = load_script_hash()?;
+    let vault_indexes = QueryIter::new(load_cell_lock_hash, Source::Input)
+        .enumerate()
+        .filter(|(_, lock_hash)| lock_hash == &vault_lock_hash)
+        .map(|(index, _)| index);
+    for index in vault_indexes {
+        if load_cell_type_hash(index, Source::Input)?.as_ref() == Some(&vault.nft_type_hash) {
+            check_nft_transferable(&load_nft_of_input(index, &vault.nft_code)?)?;
+        }
+    }
+    Ok(())
+}
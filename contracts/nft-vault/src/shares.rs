@@ -0,0 +1,44 @@
+use crate::entry::{load_vault, sum_shares};
+use ckb_std::{ckb_constants::Source, ckb_types::packed::*, high_level::load_script_hash};
+use core::result::Result;
+use script_utils::{
+    error::Error,
+    helper::{load_cell_data_header, HASH_LEN},
+    market::check_nft_transferable,
+    nft::{Nft, NFT_DATA_MIN_LEN},
+    trace,
+};
+
+/// The shares of the vault are sUDT compatible with the nft type hash as args:
+/// 1) locking the nft into a new vault mints the full supply of the shares
+/// 2) releasing the nft from the vault burns the full supply of the shares
+/// 3) the shares are only transferred otherwise
+pub fn main(script: &Script, args: &[u8]) -> Result<(), Error> {
+    let mut nft_type_hash = [0u8; HASH_LEN];
+    nft_type_hash.copy_from_slice(args);
+    let shares_type_hash = load_script_hash()?;
+    let input_amount = sum_shares(&shares_type_hash, Source::Input)?;
+    let output_amount = sum_shares(&shares_type_hash, Source::Output)?;
+
+    let input_vault = load_vault(script, Source::Input, &nft_type_hash)?;
+    let output_vault = load_vault(script, Source::Output, &nft_type_hash)?;
+    let valid = match (input_vault, output_vault) {
+        (None, Some((index, vault))) => {
+            // The vaulted nft must be releasable
+            let (header, _) = load_cell_data_header(index, Source::Output, NFT_DATA_MIN_LEN)?;
+            check_nft_transferable(&Nft::from_data(&header)?)?;
+            output_amount.checked_sub(input_amount) == Some(vault.supply)
+        }
+        (Some((_, vault)), None) => input_amount.checked_sub(output_amount) == Some(vault.supply),
+        _ => input_amount == output_amount,
+    };
+    if !valid {
+        trace!(
+            "vault shares from {} to {} are not minted or burned with the supply",
+            input_amount,
+            output_amount
+        );
+        return Err(Error::VaultSharesInvalid);
+    }
+    Ok(())
+}
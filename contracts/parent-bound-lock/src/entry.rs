@@ -0,0 +1,74 @@
+use alloc::vec::Vec;
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{bytes::Bytes, prelude::*},
+    high_level::{
+        load_cell_lock, load_cell_lock_hash, load_cell_type_hash, load_script, load_script_hash,
+        QueryIter,
+    },
+};
+use core::result::Result;
+use script_utils::{
+    error::Error,
+    nesting::{load_children_of_parent, position_of_parent, ParentBound},
+    trace,
+};
+
+/// The child nft is spent only along with its parent nft, so that the owner of the parent owns the
+/// children as well. The children which stay bound to the parent are moved with it, and the
+/// children detached from the parent go to any other lock.
+///
+/// The parent input alone does not prove the owner of the parent, because the parent under a
+/// market lock can be spent by anyone who pays for it, even if the parent keeps the lock. So the
+/// children are detached only when the parent is spent under the owner lock code of the args, and
+/// otherwise they must all stay bound to the parent in the outputs.
+pub fn main() -> Result<(), Error> {
+    let script = load_script()?;
+    let args: Bytes = script.args().unpack();
+    let parent_bound = ParentBound::from_args(&args)?;
+    let parent_type_hash = parent_bound.parent_type_hash;
+
+    let parent_input_index = match position_of_parent(&parent_type_hash, Source::Input) {
+        Some(index) => index,
+        None => return Err(Error::ParentInputMissing),
+    };
+
+    // The children bound to a destroyed parent could never be spent again
+    if position_of_parent(&parent_type_hash, Source::Output).is_none() {
+        let children = load_children_of_parent(&script, &parent_type_hash, Source::Output);
+        if !children.is_empty() {
+            trace!(
+                "{} children are bound to the destroyed parent",
+                children.len()
+            );
+            return Err(Error::ParentBoundChildOrphaned);
+        }
+    }
+
+    let parent_lock = load_cell_lock(parent_input_index, Source::Input)?;
+    let owner_signed = parent_bound.is_owner_lock(&parent_lock);
+    if !owner_signed && !children_stay_bound(&load_script_hash()?)? {
+        trace!(
+            "children are detached while the parent of input {} is not under the owner lock",
+            parent_input_index
+        );
+        return Err(Error::ParentBoundChildDetached);
+    }
+    Ok(())
+}
+
+// Every child of the group input is in the outputs under the same parent-bound lock
+fn children_stay_bound(lock_hash: &[u8; 32]) -> Result<bool, Error> {
+    let bound_type_hashes: Vec<[u8; 32]> = QueryIter::new(load_cell_lock_hash, Source::Output)
+        .enumerate()
+        .filter(|(_, output_lock_hash)| output_lock_hash == lock_hash)
+        .filter_map(|(index, _)| load_cell_type_hash(index, Source::Output).ok().flatten())
+        .collect();
+    for type_hash in QueryIter::new(load_cell_type_hash, Source::GroupInput) {
+        match type_hash {
+            Some(type_hash) if bound_type_hashes.contains(&type_hash) => (),
+            _ => return Ok(false),
+        }
+    }
+    Ok(true)
+}
@@ -0,0 +1,75 @@
+use alloc::vec::Vec;
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{bytes::Bytes, prelude::*},
+    high_level::{
+        load_cell_data, load_cell_lock_hash, load_cell_type, load_cell_type_hash, load_script,
+        QueryIter,
+    },
+};
+use core::result::Result;
+use script_utils::{
+    class::CLASS_TYPE_ARGS_LEN,
+    error::Error,
+    redemption::{Receipt, ReceiptArgs},
+    trace,
+};
+
+// The nft of the receipt is burned by the input of the nft type code, and the receipt belongs to
+// the class of the nft and to the lock of the burned nft
+fn check_burned_nft(receipt: &Receipt, receipt_args: &ReceiptArgs) -> Result<(), Error> {
+    let input_index = QueryIter::new(load_cell_type_hash, Source::Input)
+        .position(|type_hash| type_hash == Some(receipt.nft_type_hash))
+        .ok_or(Error::RedemptionReceiptInvalid)?;
+    let burned = QueryIter::new(load_cell_type_hash, Source::Output)
+        .all(|type_hash| type_hash != Some(receipt.nft_type_hash));
+    if !burned {
+        trace!("the nft of the receipt is not burned");
+        return Err(Error::RedemptionReceiptInvalid);
+    }
+
+    let nft_type = load_cell_type(input_index, Source::Input)?.ok_or(Error::ItemMissing)?;
+    if !receipt_args.is_nft_type(&nft_type) {
+        trace!("the burned cell of the receipt is not an nft");
+        return Err(Error::RedemptionReceiptInvalid);
+    }
+    let nft_args: Bytes = nft_type.args().unpack();
+    if nft_args.len() < CLASS_TYPE_ARGS_LEN
+        || nft_args[0..CLASS_TYPE_ARGS_LEN] != receipt_args.class_args
+        || load_cell_lock_hash(input_index, Source::Input)? != receipt.redeemer_lock_hash
+    {
+        return Err(Error::RedemptionReceiptInvalid);
+    }
+    Ok(())
+}
+
+/// The redemption receipts of a class are the records indexed by the merchants, so they cannot be
+/// changed, but their holders can transfer them, or destroy them to reclaim the capacity once the
+/// redemptions are fulfilled. Every new receipt needs its nft to be burned in the transaction, and
+/// an nft is redeemed once, so at most one receipt is created for an nft type hash.
+pub fn main() -> Result<(), Error> {
+    let script = load_script()?;
+    let args: Bytes = script.args().unpack();
+    let receipt_args = ReceiptArgs::from_args(&args)?;
+
+    // The receipts of the inputs are transferred with the same data or destroyed
+    let mut outputs_data: Vec<Vec<u8>> =
+        QueryIter::new(load_cell_data, Source::GroupOutput).collect();
+    for input_data in QueryIter::new(load_cell_data, Source::GroupInput) {
+        if let Some(index) = outputs_data.iter().position(|data| data == &input_data) {
+            outputs_data.remove(index);
+        }
+    }
+
+    let mut receipt_nft_type_hashes: Vec<[u8; 32]> = Vec::new();
+    for data in outputs_data {
+        let receipt = Receipt::from_data(&data)?;
+        if receipt_nft_type_hashes.contains(&receipt.nft_type_hash) {
+            trace!("more than one redemption receipt is created for the nft");
+            return Err(Error::RedemptionReceiptInvalid);
+        }
+        check_burned_nft(&receipt, &receipt_args)?;
+        receipt_nft_type_hashes.push(receipt.nft_type_hash);
+    }
+    Ok(())
+}
@@ -1,14 +1,188 @@
 use crate::error::Error;
-use crate::helper::{parse_dyn_vec_len, u32_from_slice, DYN_MIN_LEN};
+use crate::ext_info::parse_ext_info;
+use crate::helper::{
+    parse_dyn_vec_len, parse_records, since_relative_block_number, since_relative_epoch_number,
+    u32_from_slice, u64_from_slice, DYN_MIN_LEN, HASH_LEN,
+};
+use crate::lock_policy::{LockPolicy, LOCK_POLICY_RECORD};
+use crate::redemption::{ReceiptCode, REDEMPTION_RECORD};
 use alloc::vec::Vec;
 use core::result::Result;
 
-const FIXED_LEN: usize = 10;
+pub const CLASS_FIXED_LEN: usize = 10;
+// name, description and renderer
+pub const CLASS_DYN_FIELDS_COUNT: usize = 3;
 
-// FIXED_LEN + DYN_MIN_LEN * 3
+// CLASS_FIXED_LEN + DYN_MIN_LEN * 3
 const CLASS_DATA_MIN_LEN: usize = 16;
 pub const CLASS_TYPE_ARGS_LEN: usize = 24;
 
+// The kinds of class extension records
+pub const CONTENT_HASH_RECORD: u8 = 1;
+pub const SEED_HASH_RECORD: u8 = 2;
+pub const CHARACTERISTIC_POLICY_RECORD: u8 = 3;
+pub const RECIPE_RECORD: u8 = 5;
+pub const PROVENANCE_RECORD: u8 = 7;
+pub const TRANSFER_COOLDOWN_RECORD: u8 = 8;
+
+const MEDIA_HASH_MODE: u8 = 0;
+const MERKLE_ROOT_MODE: u8 = 1;
+// mode: u8 + hash: [u8; 32]
+const CONTENT_HASH_LEN: usize = 33;
+// (update: u8 + min: u8 + max: u8) * 8
+const CHARACTERISTIC_POLICY_LEN: usize = 24;
+// class_type_args + count: u8
+const RECIPE_INGREDIENT_LEN: usize = 25;
+const BLOCKS_METRIC: u8 = 0;
+const EPOCHS_METRIC: u8 = 1;
+// metric: u8 + period: u64
+const TRANSFER_COOLDOWN_LEN: usize = 9;
+// the max epoch number of the since
+const MAX_COOLDOWN_EPOCHS: u64 = 0x00ff_ffff;
+
+/// The commitment to the media of the tokens of a class
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContentHash {
+    /// The blake2b hash of the media shared by all the tokens
+    Media([u8; 32]),
+    /// The merkle root of the blake2b hashes of the per-token media
+    MerkleRoot([u8; 32]),
+}
+
+impl ContentHash {
+    /// Content hash record structure: <mode: u8> + <hash: [u8; 32]>
+    /// mode 0 means the media hash and mode 1 means the merkle root
+    pub fn from_record(content: &[u8]) -> Result<Self, Error> {
+        if content.len() != CONTENT_HASH_LEN {
+            return Err(Error::ClassDataInvalid);
+        }
+        let mut hash = [0u8; HASH_LEN];
+        hash.copy_from_slice(&content[1..]);
+        match content[0] {
+            MEDIA_HASH_MODE => Ok(ContentHash::Media(hash)),
+            MERKLE_ROOT_MODE => Ok(ContentHash::MerkleRoot(hash)),
+            _ => Err(Error::ClassDataInvalid),
+        }
+    }
+}
+
+/// The update policy of a characteristic byte
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UpdatePolicy {
+    Immutable,
+    IncreaseOnly,
+    DecreaseOnly,
+    Free,
+    IssuerOnly,
+}
+
+/// The policy of a characteristic byte, and the updated byte must be in the range of [min, max]
+#[derive(Debug, Clone, Copy)]
+pub struct CharacteristicPolicy {
+    pub update: UpdatePolicy,
+    pub min:    u8,
+    pub max:    u8,
+}
+
+impl CharacteristicPolicy {
+    fn from_slice(data: &[u8]) -> Result<Self, Error> {
+        let update = match data[0] {
+            0 => UpdatePolicy::Immutable,
+            1 => UpdatePolicy::IncreaseOnly,
+            2 => UpdatePolicy::DecreaseOnly,
+            3 => UpdatePolicy::Free,
+            4 => UpdatePolicy::IssuerOnly,
+            _ => return Err(Error::ClassDataInvalid),
+        };
+        let (min, max) = (data[1], data[2]);
+        if min > max {
+            return Err(Error::ClassDataInvalid);
+        }
+        Ok(CharacteristicPolicy { update, min, max })
+    }
+}
+
+fn parse_characteristic_policies(content: &[u8]) -> Result<[CharacteristicPolicy; 8], Error> {
+    if content.len() != CHARACTERISTIC_POLICY_LEN {
+        return Err(Error::ClassDataInvalid);
+    }
+    let mut policies = [CharacteristicPolicy {
+        update: UpdatePolicy::Free,
+        min:    u8::MIN,
+        max:    u8::MAX,
+    }; 8];
+    for (policy, data) in policies.iter_mut().zip(content.chunks(3)) {
+        *policy = CharacteristicPolicy::from_slice(data)?;
+    }
+    Ok(policies)
+}
+
+/// The ingredient of a recipe: the count of the nfts of the class destroyed to craft a token
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ingredient {
+    pub class_args: [u8; CLASS_TYPE_ARGS_LEN],
+    pub count:      u8,
+}
+
+fn parse_recipe(content: &[u8]) -> Result<Vec<Ingredient>, Error> {
+    if content.is_empty() || content.len() % RECIPE_INGREDIENT_LEN != 0 {
+        return Err(Error::ClassDataInvalid);
+    }
+    let mut recipe: Vec<Ingredient> = Vec::new();
+    for data in content.chunks(RECIPE_INGREDIENT_LEN) {
+        let mut class_args = [0u8; CLASS_TYPE_ARGS_LEN];
+        class_args.copy_from_slice(&data[0..CLASS_TYPE_ARGS_LEN]);
+        let count = data[CLASS_TYPE_ARGS_LEN];
+        if count == 0
+            || recipe
+                .iter()
+                .any(|ingredient| ingredient.class_args == class_args)
+        {
+            return Err(Error::ClassDataInvalid);
+        }
+        recipe.push(Ingredient { class_args, count });
+    }
+    Ok(recipe)
+}
+
+/// The minimum holding period of the nfts of a class before they can be transferred
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransferCooldown {
+    Blocks(u64),
+    Epochs(u64),
+}
+
+impl TransferCooldown {
+    /// Transfer cooldown record structure: <metric: u8> + <period: u64>
+    /// metric 0 means the blocks and metric 1 means the whole epochs
+    pub fn from_record(content: &[u8]) -> Result<Self, Error> {
+        if content.len() != TRANSFER_COOLDOWN_LEN {
+            return Err(Error::ClassDataInvalid);
+        }
+        let period = u64_from_slice(&content[1..]);
+        match content[0] {
+            BLOCKS_METRIC if period > 0 => Ok(TransferCooldown::Blocks(period)),
+            EPOCHS_METRIC if period > 0 && period <= MAX_COOLDOWN_EPOCHS => {
+                Ok(TransferCooldown::Epochs(period))
+            }
+            _ => Err(Error::ClassDataInvalid),
+        }
+    }
+
+    /// The relative since of the input with the same metric proves the cell has been live for
+    /// the period
+    pub fn is_passed(&self, since: u64) -> bool {
+        match self {
+            TransferCooldown::Blocks(period) => {
+                since_relative_block_number(since).map_or(false, |blocks| blocks >= *period)
+            }
+            TransferCooldown::Epochs(period) => {
+                since_relative_epoch_number(since).map_or(false, |epochs| epochs >= *period)
+            }
+        }
+    }
+}
+
 /// Class cell data structure
 /// This structure contains the following information:
 /// 1) version: u8
@@ -18,18 +192,49 @@ pub const CLASS_TYPE_ARGS_LEN: usize = 24;
 /// 5) name: <size: u16> + <content>
 /// 6) description: <size: u16> + <content>
 /// 7) renderer: <size: u16> + <content>
-/// 8) extinfo_data: <size: u16> + <content>
-/// The fields of 1), 2), 4), 5) and 6) cannot be changed after they are set and they cannot be
+/// 8) extension: <size: u16> + <records>, only for version 1
+/// 9) extinfo_data: <size: u16> + <content> for version 0, or <size: u16> + <records> for version 1
+/// The fields of 1), 2), 4), 5), 6) and 8) cannot be changed after they are set and they cannot be
 /// missing. The fields of 3) and 7) can be changed and it cannot be missing.
-/// The filed of 8) can be changed and it also can be missing and it will not be validated.
+/// The filed of 9) can be changed and it also can be missing. The version 0 ext info will not be
+/// validated, and the records of the version 1 ext info are defined in the ext_info module.
+///
+/// Each record of the extension is encoded as <kind: u8> + <size: u16> + <content> and every
+/// kind can appear at most once:
+/// 1) content hash: <mode: u8> + <hash: [u8; 32]>
+/// 2) seed hash: <hash: [u8; 32]>, the blake2b hash of the seed to reveal the characteristics
+/// 3) characteristic policy: (<update: u8> + <min: u8> + <max: u8>) * 8, the update policies of the
+/// characteristic bytes and the update values are 0: immutable, 1: increase-only, 2: decrease-only,
+/// 3: free and 4: issuer-only
+/// 4) lock policy: <mode: u8> + <code_hash: [u8; 32]> * n, the allow list(mode 0) or the deny
+/// list(mode 1) of the lock code hashes of the class and nft cells
+/// 5) recipe: (<class_type_args: [u8; 24]> + <count: u8>) * n, the nfts of the classes destroyed
+/// to craft a token without the class cell, the class cannot mint, and the crafted token id must be
+/// one of the destroyed token ids of the first class which is not issued and within the total
+/// 6) redemption: <code_hash: [u8; 32]> + <hash_type: u8>, the code of the receipt type, and every
+/// nft of the class is burned with a redemption receipt output
+/// 7) provenance: empty, the ext info of every nft of the class is led by the provenance record
+/// which counts the transfers and rolls the lock hashes of the previous owners
+/// 8) transfer cooldown: <metric: u8> + <period: u64>, the nft of the class can only change its
+/// lock when the relative since of the input proves it has been live for the blocks(metric 0) or
+/// the whole epochs(metric 1) of the period
 #[derive(Debug, Clone)]
 pub struct Class {
-    pub version:     u8,
-    pub total:       u32,
-    pub issued:      u32,
-    pub configure:   u8,
-    pub name:        Vec<u8>,
-    pub description: Vec<u8>,
+    pub version:                 u8,
+    pub total:                   u32,
+    pub issued:                  u32,
+    pub configure:               u8,
+    pub name:                    Vec<u8>,
+    pub description:             Vec<u8>,
+    pub extension:               Vec<u8>,
+    pub content_hash:            Option<ContentHash>,
+    pub seed_hash:               Option<[u8; 32]>,
+    pub characteristic_policies: Option<[CharacteristicPolicy; 8]>,
+    pub lock_policy:             Option<LockPolicy>,
+    pub recipe:                  Option<Vec<Ingredient>>,
+    pub redemption:              Option<ReceiptCode>,
+    pub provenance:              bool,
+    pub transfer_cooldown:       Option<TransferCooldown>,
 }
 
 impl Class {
@@ -39,7 +244,7 @@ impl Class {
         }
 
         let version: u8 = data[0];
-        if version != 0 {
+        if version > 1 {
             return Err(Error::VersionInvalid);
         }
 
@@ -52,14 +257,14 @@ impl Class {
 
         let configure: u8 = data[9];
 
-        let name_len = parse_dyn_vec_len(&data[FIXED_LEN..(FIXED_LEN + DYN_MIN_LEN)]);
+        let name_len = parse_dyn_vec_len(&data[CLASS_FIXED_LEN..(CLASS_FIXED_LEN + DYN_MIN_LEN)]);
         // DYN_MIN_LEN: the min length of description
-        if data.len() < FIXED_LEN + name_len + DYN_MIN_LEN {
+        if data.len() < CLASS_FIXED_LEN + name_len + DYN_MIN_LEN {
             return Err(Error::ClassDataInvalid);
         }
-        let name = data[FIXED_LEN..(FIXED_LEN + name_len)].to_vec();
+        let name = data[CLASS_FIXED_LEN..(CLASS_FIXED_LEN + name_len)].to_vec();
 
-        let description_index = FIXED_LEN + name_len;
+        let description_index = CLASS_FIXED_LEN + name_len;
         let description_len =
             parse_dyn_vec_len(&data[description_index..(description_index + DYN_MIN_LEN)]);
         // DYN_MIN_LEN: the min length of renderer
@@ -68,13 +273,90 @@ impl Class {
         }
         let description = data[description_index..(description_index + description_len)].to_vec();
 
-        let renderer_index = FIXED_LEN + name_len + description_len;
+        let renderer_index = CLASS_FIXED_LEN + name_len + description_len;
         let renderer_len = parse_dyn_vec_len(&data[renderer_index..(renderer_index + DYN_MIN_LEN)]);
 
         if data.len() < renderer_index + renderer_len {
             return Err(Error::ClassDataInvalid);
         }
 
+        let mut extension = Vec::new();
+        if version == 1 {
+            let extension_index = renderer_index + renderer_len;
+            // DYN_MIN_LEN: the min length of extension
+            if data.len() < extension_index + DYN_MIN_LEN {
+                return Err(Error::ClassDataInvalid);
+            }
+            let extension_len =
+                parse_dyn_vec_len(&data[extension_index..(extension_index + DYN_MIN_LEN)]);
+            if data.len() < extension_index + extension_len {
+                return Err(Error::ClassDataInvalid);
+            }
+            extension =
+                data[(extension_index + DYN_MIN_LEN)..(extension_index + extension_len)].to_vec();
+
+            // The ext info of version 1 can be missing, otherwise it must be the typed records
+            let extinfo_index = extension_index + extension_len;
+            if data.len() > extinfo_index {
+                if data.len() < extinfo_index + DYN_MIN_LEN {
+                    return Err(Error::ClassDataInvalid);
+                }
+                let extinfo_len =
+                    parse_dyn_vec_len(&data[extinfo_index..(extinfo_index + DYN_MIN_LEN)]);
+                if data.len() != extinfo_index + extinfo_len {
+                    return Err(Error::ClassDataInvalid);
+                }
+                parse_ext_info(&data[(extinfo_index + DYN_MIN_LEN)..])
+                    .ok_or(Error::ClassDataInvalid)?;
+            }
+        }
+
+        let mut content_hash = None;
+        let mut seed_hash = None;
+        let mut characteristic_policies = None;
+        let mut lock_policy = None;
+        let mut recipe = None;
+        let mut redemption = None;
+        let mut provenance = false;
+        let mut transfer_cooldown = None;
+        let records = parse_records(&extension).ok_or(Error::ClassDataInvalid)?;
+        for (kind, content) in records {
+            match kind {
+                CONTENT_HASH_RECORD if content_hash.is_none() => {
+                    content_hash = Some(ContentHash::from_record(content)?);
+                }
+                SEED_HASH_RECORD if seed_hash.is_none() => {
+                    if content.len() != HASH_LEN {
+                        return Err(Error::ClassDataInvalid);
+                    }
+                    let mut hash = [0u8; HASH_LEN];
+                    hash.copy_from_slice(content);
+                    seed_hash = Some(hash);
+                }
+                CHARACTERISTIC_POLICY_RECORD if characteristic_policies.is_none() => {
+                    characteristic_policies = Some(parse_characteristic_policies(content)?);
+                }
+                LOCK_POLICY_RECORD if lock_policy.is_none() => {
+                    lock_policy =
+                        Some(LockPolicy::from_record(content).ok_or(Error::ClassDataInvalid)?);
+                }
+                RECIPE_RECORD if recipe.is_none() => {
+                    recipe = Some(parse_recipe(content)?);
+                }
+                REDEMPTION_RECORD if redemption.is_none() => {
+                    redemption =
+                        Some(ReceiptCode::from_record(content).ok_or(Error::ClassDataInvalid)?);
+                }
+                PROVENANCE_RECORD if !provenance && content.is_empty() => {
+                    provenance = true;
+                }
+                TRANSFER_COOLDOWN_RECORD if transfer_cooldown.is_none() => {
+                    transfer_cooldown = Some(TransferCooldown::from_record(content)?);
+                }
+                _ => return Err(Error::ClassDataInvalid),
+            }
+        }
+
         Ok(Class {
             version,
             total,
@@ -82,13 +364,24 @@ impl Class {
             configure,
             name,
             description,
+            extension,
+            content_hash,
+            seed_hash,
+            characteristic_policies,
+            lock_policy,
+            recipe,
+            redemption,
+            provenance,
+            transfer_cooldown,
         })
     }
 
     pub fn immutable_equal(&self, other: &Class) -> bool {
-        self.total == other.total
+        self.version == other.version
+            && self.total == other.total
             && self.configure == other.configure
             && self.name == other.name
             && self.description == other.description
+            && self.extension == other.extension
     }
 }
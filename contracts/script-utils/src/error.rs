@@ -1,6 +1,10 @@
 use ckb_std::error::SysError;
 
 /// Error
+///
+/// The codes are stable once released, and the new errors are appended with the next free code.
+/// The codes from 1 to 4 are the syscall errors returned by the `?` on the ckb-std calls, and the
+/// codes from 120 to 127 are reserved for the syscall errors which have no dedicated code.
 #[repr(i8)]
 pub enum Error {
     IndexOutOfBound = 1,
@@ -43,6 +47,49 @@ pub enum Error {
     LockedNFTCannotDestroy,
     LockedNFTCannotUpdateCharacteristic,
     GroupInputWitnessNoneError = 40,
+    NFTCharacteristicNotZero,
+    NFTRevealSeedInvalid,
+    NFTRevealCharacteristicError,
+    NFTCharacteristicImmutable,
+    NFTCharacteristicNotIncreased = 45,
+    NFTCharacteristicNotDecreased,
+    NFTCharacteristicOutOfRange,
+    NFTCharacteristicIssuerOnly,
+    NFTExtInfoRecordInvalid,
+    NFTExtInfoSignerMissing = 50,
+    LockPolicyViolated,
+    IssuerImmutableFieldsNotSame,
+    ActionDeclarationInvalid,
+    ActionNotMatched,
+    NFTMigrationInvalid = 55,
+    DelegateQuotaInvalid,
+    DelegateNotAllowed,
+    IssuerTransferInvalid,
+    SaleLockArgsInvalid,
+    SalePaymentNotEnough = 60,
+    AuctionLockArgsInvalid,
+    AuctionBidInvalid,
+    AuctionNotEnded,
+    AuctionSettlementInvalid,
+    AuctionPaymentNotEnough = 65,
+    ClaimLockArgsInvalid,
+    ClaimCodeInvalid,
+    NFTClaimInvalid,
+    VaultArgsInvalid,
+    VaultSharesInvalid = 70,
+    VaultReleaseInvalid,
+    ParentBoundLockArgsInvalid,
+    ParentInputMissing,
+    ParentBoundChildOrphaned,
+    NFTRecipeNotSatisfied = 75,
+    RedemptionReceiptMissing,
+    RedemptionReceiptInvalid,
+    NFTProvenanceInvalid,
+    NFTTransferCooldownNotPassed,
+    ClaimCommitmentMissing = 80,
+    ParentBoundChildDetached,
+    MarketNFTTypeInvalid,
+    UnknownSysError = 120,
 }
 
 impl From<SysError> for Error {
@@ -53,7 +100,7 @@ impl From<SysError> for Error {
             ItemMissing => Self::ItemMissing,
             LengthNotEnough(_) => Self::LengthNotEnough,
             Encoding => Self::Encoding,
-            Unknown(err_code) => panic!("unexpected sys error {}", err_code),
+            Unknown(_) => Self::UnknownSysError,
         }
     }
 }
@@ -0,0 +1,126 @@
+use crate::error::Error;
+
+/// The name and the description of an error code returned by the contracts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorInfo {
+    pub code:        i8,
+    pub name:        &'static str,
+    pub description: &'static str,
+}
+
+macro_rules! error_table {
+    ($($name:ident => $description:literal,)*) => {
+        /// All the errors of the contracts ordered by code
+        pub const ERROR_TABLE: &[ErrorInfo] = &[
+            $(ErrorInfo {
+                code:        Error::$name as i8,
+                name:        stringify!($name),
+                description: $description,
+            },)*
+        ];
+
+        impl Error {
+            // The exhaustive match fails to compile once a variant is missing from the table
+            pub fn info(&self) -> ErrorInfo {
+                match self {
+                    $(Error::$name => ErrorInfo {
+                        code:        Error::$name as i8,
+                        name:        stringify!($name),
+                        description: $description,
+                    },)*
+                }
+            }
+        }
+    };
+}
+
+error_table! {
+    IndexOutOfBound => "The cell or witness index is out of bound",
+    ItemMissing => "The cell field is missing",
+    LengthNotEnough => "The loaded data is longer than the buffer",
+    Encoding => "The loaded data is not encoded correctly",
+    IssuerDataInvalid => "The issuer cell data is malformed",
+    IssuerCellsCountError => "The issuer cells count of the transaction is invalid",
+    TypeArgsInvalid => "The type args is malformed or does not match the type id",
+    IssuerClassCountError => "The class count of the issuer does not match the created classes",
+    IssuerSetCountError => "The set count of the issuer is decreased",
+    IssuerCellCannotDestroyed => "The issuer cell with classes or sets cannot be destroyed",
+    VersionInvalid => "The version is unknown or changed",
+    ClassDataInvalid => "The class cell data is malformed",
+    ClassTotalSmallerThanIssued => "The total of the class is smaller than the issued",
+    ClassCellsCountError => "The class cells count of the transaction is invalid",
+    ClassIssuedInvalid => "The issued of the class does not match the minted nfts",
+    ClassImmutableFieldsNotSame => "The immutable fields of the class are changed",
+    ClassCellCannotDestroyed => "The class cell with issued nfts cannot be destroyed",
+    ClassIdIncreaseError => "The ids of the created classes are not increased one by one",
+    NFTDataInvalid => "The nft cell data is malformed",
+    NFTCellsCountError => "The nft cells count of the transaction is invalid",
+    NFTTokenIdIncreaseError => "The token ids of the minted nfts are not increased one by one",
+    NFTAndClassConfigureNotSame => "The configure of the minted nft differs from the class",
+    NFTCharacteristicNotSame => "The characteristic of the nft is changed",
+    NFTConfigureNotSame => "The configure of the nft is changed",
+    NFTClaimedToUnclaimedError => "The claimed nft cannot be unclaimed",
+    NFTLockedToUnlockedError => "The locked nft cannot be unlocked",
+    NFTDisallowClaimed => "The nft is not allowed to be claimed",
+    NFTDisallowLocked => "The nft is not allowed to be locked",
+    NFTCannotTransferBeforeClaim => "The nft cannot be transferred before being claimed",
+    NFTCannotTransferAfterClaim => "The nft cannot be transferred after being claimed",
+    NFTExtInfoLenError => "The ext info of the nft is shortened or extended without permission",
+    NFTExtInfoCannotModify => "The existing ext info of the nft is modified",
+    NFTCannotDestroyBeforeClaim => "The nft cannot be destroyed before being claimed",
+    NFTCannotDestroyAfterClaim => "The nft cannot be destroyed after being claimed",
+    LockedNFTCannotClaim => "The locked nft cannot be claimed",
+    LockedNFTCannotTransfer => "The locked nft cannot be transferred",
+    LockedNFTCannotAddExtInfo => "The locked nft cannot append ext info",
+    LockedNFTCannotDestroy => "The locked nft cannot be destroyed",
+    LockedNFTCannotUpdateCharacteristic => "The characteristic of the locked nft cannot be updated",
+    GroupInputWitnessNoneError => "The witness of the first group input is none",
+    NFTCharacteristicNotZero => "The characteristic of the unrevealed nft is not zero",
+    NFTRevealSeedInvalid => "The reveal seed does not match the seed hash of the class",
+    NFTRevealCharacteristicError => "The revealed characteristic does not match the seed",
+    NFTCharacteristicImmutable => "The characteristic byte is immutable",
+    NFTCharacteristicNotIncreased => "The increase-only characteristic byte is decreased",
+    NFTCharacteristicNotDecreased => "The decrease-only characteristic byte is increased",
+    NFTCharacteristicOutOfRange => "The characteristic byte is out of the policy range",
+    NFTCharacteristicIssuerOnly => "The characteristic byte can only be updated by the issuer",
+    NFTExtInfoRecordInvalid => "The ext info records are truncated, unknown or malformed",
+    NFTExtInfoSignerMissing => "The signer of the annotation is not among the inputs",
+    LockPolicyViolated => "The lock of the cell is not permitted by the lock policy",
+    IssuerImmutableFieldsNotSame => "The version or the extension of the issuer is changed",
+    ActionDeclarationInvalid => "The declared action is unknown or not supported",
+    ActionNotMatched => "The declared action does not match the cells of the transaction",
+    NFTMigrationInvalid => "The nft migration changes fields other than the version and ext info",
+    DelegateQuotaInvalid => "The delegate quota cell is malformed or its class ids are wrong",
+    DelegateNotAllowed => "The lock of the delegate quota cell is not a delegate of the issuer",
+    IssuerTransferInvalid => "The pending issuer transfer is completed or cancelled wrongly",
+    SaleLockArgsInvalid => "The args of the nft sale lock are malformed",
+    SalePaymentNotEnough => "The output paid to the seller is less than the price of the nft",
+    AuctionLockArgsInvalid => "The args of the nft auction lock are malformed",
+    AuctionBidInvalid => "The bid is not higher or the auction cell of the bid is changed",
+    AuctionNotEnded => "The header deps do not prove the end time of the auction has passed",
+    AuctionSettlementInvalid => "The auctioned nft is not sent to the winner or the seller",
+    AuctionPaymentNotEnough => "The output paid for the bid or the refund is not enough",
+    ClaimLockArgsInvalid => "The args of the nft claim lock are malformed",
+    ClaimCodeInvalid => "The claim code in the witness does not match the code hash of the lock",
+    NFTClaimInvalid => "The nft is already claimed or not sent claimed to the recipient",
+    VaultArgsInvalid => "The args of the nft vault lock are malformed or commit to another nft",
+    VaultSharesInvalid => "The vault shares are minted or burned other than the full supply",
+    VaultReleaseInvalid => "The nft is released from the vault without burning the full supply",
+    ParentBoundLockArgsInvalid => "The parent-bound lock args are not a parent type and lock code",
+    ParentInputMissing => "The child nft is spent without its parent nft in the inputs",
+    ParentBoundChildOrphaned => "The child nft is still bound to a parent which is destroyed",
+    NFTRecipeNotSatisfied => "The crafted nft does not destroy the exact ingredients of the recipe",
+    RedemptionReceiptMissing => "The redeemable nft is burned without a redemption receipt output",
+    RedemptionReceiptInvalid => "The receipt is changed or created without burning its nft",
+    NFTProvenanceInvalid => "The provenance record of the nft is missing or updated wrongly",
+    NFTTransferCooldownNotPassed => "The nft is transferred before the since proves its cooldown",
+    ClaimCommitmentMissing => "No input matured long enough commits the recipient of the claim",
+    ParentBoundChildDetached => "The child nft is detached without its parent under the owner lock",
+    MarketNFTTypeInvalid => "The input under the market lock is not of the nft type code",
+    UnknownSysError => "The syscall failed with an error code unknown to ckb-std",
+}
+
+/// Look up the error returned by a contract with the exit code
+pub fn describe(code: i8) -> Option<&'static ErrorInfo> {
+    ERROR_TABLE.iter().find(|info| info.code == code)
+}
@@ -0,0 +1,146 @@
+use crate::helper::{
+    new_blake2b, parse_dyn_vec_len, parse_records, u32_from_slice, DYN_MIN_LEN, HASH_LEN,
+};
+use alloc::vec::Vec;
+use core::str;
+
+// The kinds of ext info records
+pub const URI_RECORD: u8 = 1;
+pub const CONTENT_HASH_RECORD: u8 = 2;
+pub const ATTRIBUTE_RECORD: u8 = 3;
+pub const SIGNED_ANNOTATION_RECORD: u8 = 4;
+pub const PROVENANCE_RECORD: u8 = 5;
+
+// transfer_count: u32 + owners_hash: [u8; 32]
+const PROVENANCE_LEN: usize = 36;
+// kind: u8 + size: u16 + PROVENANCE_LEN
+pub const PROVENANCE_RECORD_LEN: usize = 39;
+
+/// The ownership history of the nft of a class with provenance
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Provenance {
+    pub transfer_count: u32,
+    pub owners_hash:    [u8; 32],
+}
+
+impl Provenance {
+    fn from_content(content: &[u8]) -> Option<Self> {
+        if content.len() != PROVENANCE_LEN {
+            return None;
+        }
+        let mut owners_hash = [0u8; HASH_LEN];
+        owners_hash.copy_from_slice(&content[4..PROVENANCE_LEN]);
+        Some(Provenance {
+            transfer_count: u32_from_slice(&content[0..4]),
+            owners_hash,
+        })
+    }
+
+    /// Parse the provenance record which leads the ext info
+    pub fn from_ext_info(ext_info: &[u8]) -> Option<Self> {
+        if ext_info.len() < PROVENANCE_RECORD_LEN || ext_info[0] != PROVENANCE_RECORD {
+            return None;
+        }
+        if parse_dyn_vec_len(&ext_info[1..3]) != PROVENANCE_LEN + DYN_MIN_LEN {
+            return None;
+        }
+        Provenance::from_content(&ext_info[3..PROVENANCE_RECORD_LEN])
+    }
+
+    /// The provenance after a transfer: the transfer count is increased and the lock hash of the
+    /// previous owner is rolled into blake2b(owners_hash + previous_owner_lock_hash)
+    pub fn transferred(&self, previous_owner_lock_hash: &[u8; 32]) -> Option<Self> {
+        let mut blake2b = new_blake2b();
+        blake2b.update(&self.owners_hash);
+        blake2b.update(previous_owner_lock_hash);
+        let mut owners_hash = [0u8; HASH_LEN];
+        blake2b.finalize(&mut owners_hash);
+        Some(Provenance {
+            transfer_count: self.transfer_count.checked_add(1)?,
+            owners_hash,
+        })
+    }
+}
+
+/// The typed record of the ext info of version 1, and each record is encoded as
+/// <kind: u8> + <size: u16> + <content>
+/// 1) uri: <utf-8 text>, it cannot be empty
+/// 2) content hash: <hash: [u8; 32]>
+/// 3) attribute: <key: <size: u16> + <utf-8 text>> + <value>, the key cannot be empty
+/// 4) signed annotation: <signer: [u8; 32]> + <annotation>, the signer is the lock hash of a cell
+/// which must be consumed in the transaction appending the record
+/// 5) provenance: <transfer_count: u32> + <owners_hash: [u8; 32]>, it leads the ext info of the
+/// nft of a class with provenance and it is updated by the nft type instead of being appended
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExtInfoRecord<'a> {
+    Uri(&'a str),
+    ContentHash([u8; 32]),
+    Attribute {
+        key:   &'a str,
+        value: &'a [u8],
+    },
+    SignedAnnotation {
+        signer:     [u8; 32],
+        annotation: &'a [u8],
+    },
+    Provenance(Provenance),
+}
+
+impl<'a> ExtInfoRecord<'a> {
+    pub fn from_record(kind: u8, content: &'a [u8]) -> Option<Self> {
+        match kind {
+            URI_RECORD => {
+                let uri = str::from_utf8(content).ok()?;
+                if uri.is_empty() {
+                    return None;
+                }
+                Some(ExtInfoRecord::Uri(uri))
+            }
+            CONTENT_HASH_RECORD => {
+                if content.len() != HASH_LEN {
+                    return None;
+                }
+                let mut hash = [0u8; HASH_LEN];
+                hash.copy_from_slice(content);
+                Some(ExtInfoRecord::ContentHash(hash))
+            }
+            ATTRIBUTE_RECORD => {
+                if content.len() < DYN_MIN_LEN {
+                    return None;
+                }
+                let key_len = parse_dyn_vec_len(&content[..DYN_MIN_LEN]);
+                if key_len == DYN_MIN_LEN || content.len() < key_len {
+                    return None;
+                }
+                let key = str::from_utf8(&content[DYN_MIN_LEN..key_len]).ok()?;
+                Some(ExtInfoRecord::Attribute {
+                    key,
+                    value: &content[key_len..],
+                })
+            }
+            SIGNED_ANNOTATION_RECORD => {
+                if content.len() < HASH_LEN {
+                    return None;
+                }
+                let mut signer = [0u8; HASH_LEN];
+                signer.copy_from_slice(&content[..HASH_LEN]);
+                Some(ExtInfoRecord::SignedAnnotation {
+                    signer,
+                    annotation: &content[HASH_LEN..],
+                })
+            }
+            PROVENANCE_RECORD => Some(ExtInfoRecord::Provenance(Provenance::from_content(
+                content,
+            )?)),
+            _ => None,
+        }
+    }
+}
+
+/// Parse the ext info records, and return None if any record is truncated, unknown or malformed.
+pub fn parse_ext_info(data: &[u8]) -> Option<Vec<ExtInfoRecord>> {
+    parse_records(data)?
+        .into_iter()
+        .map(|(kind, content)| ExtInfoRecord::from_record(kind, content))
+        .collect()
+}
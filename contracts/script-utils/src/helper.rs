@@ -1,19 +1,34 @@
 use crate::class::CLASS_TYPE_ARGS_LEN;
 use crate::error::Error;
 use crate::issuer::ISSUER_TYPE_ARGS_LEN;
-use alloc::vec::Vec;
+use alloc::{vec, vec::Vec};
+use blake2b_rs::{Blake2b, Blake2bBuilder};
 use ckb_std::{
     ckb_constants::Source,
     ckb_types::{bytes::Bytes, packed::*, prelude::*},
+    error::SysError,
     high_level::{
         load_cell_data, load_cell_lock, load_cell_type, load_cell_type_hash, load_witness_args,
         QueryIter,
     },
+    syscalls,
 };
 use core::result::Result;
 
 const ID_LEN: usize = 4;
 pub const DYN_MIN_LEN: usize = 2; // the length of dynamic data size(u16)
+pub const HASH_LEN: usize = 32;
+// the length of record kind(u8) and record size(u16)
+const RECORD_HEADER_LEN: usize = 3;
+// the length of the chunk to compare the cell data
+const DATA_CHUNK_LEN: usize = 512;
+// the highest byte of the since is the flags of relative and metric, and the rest is the value
+const SINCE_FLAGS_SHIFT: u64 = 56;
+const SINCE_VALUE_MASK: u64 = 0x00ff_ffff_ffff_ffff;
+const SINCE_RELATIVE_BLOCK_NUMBER_FLAGS: u64 = 0x80;
+const SINCE_RELATIVE_EPOCH_FLAGS: u64 = 0xa0;
+// the epoch number is the lowest 24 bits of the epoch since value
+const SINCE_EPOCH_NUMBER_MASK: u64 = 0x00ff_ffff;
 
 const TYPE: u8 = 1;
 const CLASS_TYPE_CODE_HASH: [u8; 32] = [
@@ -21,10 +36,34 @@ const CLASS_TYPE_CODE_HASH: [u8; 32] = [
     148, 231, 175, 39, 187, 56, 149, 135, 144, 227, 252,
 ];
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Action {
     Create,
     Update,
     Destroy,
+    Reveal,
+    Migrate,
+}
+
+impl Action {
+    fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(Action::Create),
+            1 => Some(Action::Update),
+            2 => Some(Action::Destroy),
+            3 => Some(Action::Reveal),
+            4 => Some(Action::Migrate),
+            _ => None,
+        }
+    }
+
+    // The cells count can only tell create, update and destroy, and the others are updates
+    fn observed(&self) -> Self {
+        match self {
+            Action::Reveal | Action::Migrate => Action::Update,
+            _ => *self,
+        }
+    }
 }
 
 fn parse_type_args_id(type_script: Script, slice_start: usize) -> Option<u32> {
@@ -53,16 +92,114 @@ pub fn load_class_type(nft_args: &Bytes) -> Script {
         .build()
 }
 
+// The class cells of the issuer are pinned to the class type code
+pub fn check_class_type_of_issuer<'a>(issuer_id: &'a [u8]) -> impl Fn(&Script) -> bool + 'a {
+    move |type_: &Script| {
+        let type_args: Bytes = type_.args().unpack();
+        type_.code_hash().as_slice() == &CLASS_TYPE_CODE_HASH[..]
+            && type_.hash_type().as_slice() == &[TYPE]
+            && type_args.len() == CLASS_TYPE_ARGS_LEN
+            && &type_args[0..ISSUER_TYPE_ARGS_LEN] == issuer_id
+    }
+}
+
 pub fn count_cells_by_type(source: Source, predicate: &dyn Fn(&Script) -> bool) -> usize {
     QueryIter::new(load_cell_type, source)
         .filter(|type_opt| parse_type_opt(&type_opt, predicate))
         .count()
 }
 
-pub fn count_cells_by_type_hash(source: Source, predicate: &dyn Fn(&[u8]) -> bool) -> usize {
-    QueryIter::new(load_cell_type_hash, source)
-        .filter(|type_hash_opt| type_hash_opt.map_or(false, |type_hash| predicate(&type_hash)))
-        .count()
+/// The indexes of the cells whose type matches the predicate, so that the traces of the failed
+/// count checks can point to the offending cells
+pub fn positions_by_type(source: Source, predicate: &dyn Fn(&Script) -> bool) -> Vec<usize> {
+    QueryIter::new(load_cell_type, source)
+        .enumerate()
+        .filter(|(_, type_opt)| parse_type_opt(&type_opt, predicate))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+// Load the cell data from the offset into the buffer, and the buffer which is shorter than the
+// rest data is filled up. Return the length of the whole cell data.
+fn load_cell_data_into(
+    buf: &mut [u8],
+    offset: usize,
+    index: usize,
+    source: Source,
+) -> Result<usize, Error> {
+    match syscalls::load_cell_data(buf, offset, index, source) {
+        Ok(len) => Ok(offset + len),
+        Err(SysError::LengthNotEnough(len)) => Ok(offset + len),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Load the header of the cell data with the partial loading instead of copying the whole data,
+/// and return the header which may be shorter than header_len and the length of the whole data.
+pub fn load_cell_data_header(
+    index: usize,
+    source: Source,
+    header_len: usize,
+) -> Result<(Vec<u8>, usize), Error> {
+    let mut header = vec![0u8; header_len];
+    let data_len = load_cell_data_into(&mut header, 0, index, source)?;
+    header.truncate(data_len);
+    Ok((header, data_len))
+}
+
+/// Load the cell data from the offset to the end
+pub fn load_cell_data_from(index: usize, source: Source, offset: usize) -> Result<Vec<u8>, Error> {
+    let data_len = load_cell_data_into(&mut [], 0, index, source)?;
+    let mut data = vec![0u8; data_len.saturating_sub(offset)];
+    if !data.is_empty() {
+        load_cell_data_into(&mut data, offset, index, source)?;
+    }
+    Ok(data)
+}
+
+/// Load the cell data which skips the opaque ext info: the fixed fields and the dynamic fields
+/// which are encoded as <size: u16> + <content>.
+pub fn load_cell_data_fields(
+    index: usize,
+    source: Source,
+    fixed_len: usize,
+    dyn_fields_count: usize,
+) -> Result<Vec<u8>, Error> {
+    let (_, data_len) = load_cell_data_header(index, source, 0)?;
+    let mut fields_len = fixed_len;
+    for _ in 0..dyn_fields_count {
+        let mut size_buf = [0u8; DYN_MIN_LEN];
+        if data_len < fields_len + DYN_MIN_LEN {
+            break;
+        }
+        load_cell_data_into(&mut size_buf, fields_len, index, source)?;
+        fields_len += parse_dyn_vec_len(&size_buf);
+    }
+    let (fields, _) = load_cell_data_header(index, source, fields_len.min(data_len))?;
+    Ok(fields)
+}
+
+/// Compare the data of the two cells in the range of [start, end) chunk by chunk, so that the
+/// large data is never copied as a whole.
+pub fn cell_data_range_equal(
+    (index, source): (usize, Source),
+    (other_index, other_source): (usize, Source),
+    start: usize,
+    end: usize,
+) -> Result<bool, Error> {
+    let mut chunk = [0u8; DATA_CHUNK_LEN];
+    let mut other_chunk = [0u8; DATA_CHUNK_LEN];
+    let mut offset = start;
+    while offset < end {
+        let len = DATA_CHUNK_LEN.min(end - offset);
+        load_cell_data_into(&mut chunk[..len], offset, index, source)?;
+        load_cell_data_into(&mut other_chunk[..len], offset, other_index, other_source)?;
+        if chunk[..len] != other_chunk[..len] {
+            return Ok(false);
+        }
+        offset += len;
+    }
+    Ok(true)
 }
 
 pub fn load_output_index_by_type(type_script: &Script) -> Option<usize> {
@@ -71,32 +208,175 @@ pub fn load_output_index_by_type(type_script: &Script) -> Option<usize> {
     })
 }
 
-pub fn load_cell_data_by_type(
-    source: Source,
-    predicate: &dyn Fn(&Script) -> bool,
-) -> Option<Vec<u8>> {
-    QueryIter::new(load_cell_type, source)
-        .position(|type_opt| type_opt.map_or(false, |type_| predicate(&type_)))
-        .map(|index| load_cell_data(index, source).map_or_else(|_| Vec::new(), |data| data))
+/// The type scripts and type hashes of the inputs, outputs and cell deps are loaded once, then the
+/// counting, lookup and id-extraction queries are answered from memory instead of scanning the
+/// cells with syscalls or hashing the type scripts again and again.
+pub struct TxIndex {
+    inputs:          Vec<Option<Script>>,
+    outputs:         Vec<Option<Script>>,
+    cell_deps:       Vec<Option<Script>>,
+    input_hashes:    Vec<Option<[u8; 32]>>,
+    output_hashes:   Vec<Option<[u8; 32]>>,
+    cell_dep_hashes: Vec<Option<[u8; 32]>>,
 }
 
-pub fn load_cell_data_by_type_hash(
-    source: Source,
-    predicate: &dyn Fn(&[u8]) -> bool,
-) -> Option<Vec<u8>> {
-    QueryIter::new(load_cell_type_hash, source)
-        .position(|type_hash_opt| type_hash_opt.map_or(false, |type_hash| predicate(&type_hash)))
-        .map(|index| load_cell_data(index, source).map_or_else(|_| Vec::new(), |data| data))
-}
-
-pub fn load_output_type_args_ids(
-    slice_start: usize,
-    predicate: &dyn Fn(&Script) -> bool,
-) -> Vec<u32> {
-    QueryIter::new(load_cell_type, Source::Output)
-        .filter(|type_opt| parse_type_opt(&type_opt, predicate))
-        .filter_map(|type_opt| type_opt.and_then(|type_| parse_type_args_id(type_, slice_start)))
-        .collect()
+impl TxIndex {
+    pub fn load() -> Self {
+        TxIndex {
+            inputs:          QueryIter::new(load_cell_type, Source::Input).collect(),
+            outputs:         QueryIter::new(load_cell_type, Source::Output).collect(),
+            cell_deps:       QueryIter::new(load_cell_type, Source::CellDep).collect(),
+            input_hashes:    QueryIter::new(load_cell_type_hash, Source::Input).collect(),
+            output_hashes:   QueryIter::new(load_cell_type_hash, Source::Output).collect(),
+            cell_dep_hashes: QueryIter::new(load_cell_type_hash, Source::CellDep).collect(),
+        }
+    }
+
+    fn types(&self, source: Source) -> &[Option<Script>] {
+        match source {
+            Source::Input => &self.inputs,
+            Source::Output => &self.outputs,
+            Source::CellDep => &self.cell_deps,
+            _ => &[],
+        }
+    }
+
+    fn type_hashes(&self, source: Source) -> &[Option<[u8; 32]>] {
+        match source {
+            Source::Input => &self.input_hashes,
+            Source::Output => &self.output_hashes,
+            Source::CellDep => &self.cell_dep_hashes,
+            _ => &[],
+        }
+    }
+
+    pub fn count_cells_by_type(
+        &self,
+        source: Source,
+        predicate: &dyn Fn(&Script) -> bool,
+    ) -> usize {
+        self.types(source)
+            .iter()
+            .filter(|type_opt| parse_type_opt(type_opt, predicate))
+            .count()
+    }
+
+    pub fn count_cells_by_type_hash(
+        &self,
+        source: Source,
+        predicate: &dyn Fn(&[u8]) -> bool,
+    ) -> usize {
+        self.type_hashes(source)
+            .iter()
+            .filter(|hash_opt| hash_opt.map_or(false, |hash| predicate(&hash)))
+            .count()
+    }
+
+    pub fn position_by_type(
+        &self,
+        source: Source,
+        predicate: &dyn Fn(&Script) -> bool,
+    ) -> Option<usize> {
+        self.types(source)
+            .iter()
+            .position(|type_opt| parse_type_opt(type_opt, predicate))
+    }
+
+    pub fn position_by_type_hash(
+        &self,
+        source: Source,
+        predicate: &dyn Fn(&[u8]) -> bool,
+    ) -> Option<usize> {
+        self.type_hashes(source)
+            .iter()
+            .position(|hash_opt| hash_opt.map_or(false, |hash| predicate(&hash)))
+    }
+
+    pub fn positions_by_type_hash(
+        &self,
+        source: Source,
+        predicate: &dyn Fn(&[u8]) -> bool,
+    ) -> Vec<usize> {
+        self.type_hashes(source)
+            .iter()
+            .enumerate()
+            .filter(|(_, hash_opt)| hash_opt.map_or(false, |hash| predicate(&hash)))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    pub fn positions_by_type(
+        &self,
+        source: Source,
+        predicate: &dyn Fn(&Script) -> bool,
+    ) -> Vec<usize> {
+        self.types(source)
+            .iter()
+            .enumerate()
+            .filter(|(_, type_opt)| parse_type_opt(type_opt, predicate))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    pub fn load_types(&self, source: Source, predicate: &dyn Fn(&Script) -> bool) -> Vec<Script> {
+        self.types(source)
+            .iter()
+            .filter(|type_opt| parse_type_opt(type_opt, predicate))
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    pub fn load_type(&self, source: Source, index: usize) -> Option<Script> {
+        self.types(source).get(index).cloned().flatten()
+    }
+
+    /// Load the data of the first cell whose type matches the predicate, and return None if no
+    /// cell matches
+    pub fn load_cell_data_by_type(
+        &self,
+        source: Source,
+        predicate: &dyn Fn(&Script) -> bool,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.position_by_type(source, predicate)
+            .map(|index| load_cell_data(index, source).map_err(Error::from))
+            .transpose()
+    }
+
+    pub fn load_cell_data_by_type_hash(
+        &self,
+        source: Source,
+        predicate: &dyn Fn(&[u8]) -> bool,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.position_by_type_hash(source, predicate)
+            .map(|index| load_cell_data(index, source).map_err(Error::from))
+            .transpose()
+    }
+
+    pub fn load_output_type_args_ids(
+        &self,
+        slice_start: usize,
+        predicate: &dyn Fn(&Script) -> bool,
+    ) -> Vec<u32> {
+        self.load_type_args_ids(Source::Output, slice_start, predicate)
+    }
+
+    pub fn load_type_args_ids(
+        &self,
+        source: Source,
+        slice_start: usize,
+        predicate: &dyn Fn(&Script) -> bool,
+    ) -> Vec<u32> {
+        self.types(source)
+            .iter()
+            .filter(|type_opt| parse_type_opt(type_opt, predicate))
+            .filter_map(|type_opt| {
+                type_opt
+                    .clone()
+                    .and_then(|type_| parse_type_args_id(type_, slice_start))
+            })
+            .collect()
+    }
 }
 
 fn cell_deps_have_same_issuer_id(issuer_id: &[u8]) -> Result<bool, Error> {
@@ -133,7 +413,7 @@ pub fn check_group_input_witness_is_none_with_type(type_script: &Script) -> Resu
         .position(|type_opt| {
             type_opt.map_or(false, |type_| type_.as_slice() == type_script.as_slice())
         })
-        .map(|index| load_cell_lock(index, Source::Input).map_or(Err(Error::Encoding), Ok))
+        .map(|index| load_cell_lock(index, Source::Input).map_err(Error::from))
         .map_or_else(|| Err(Error::Encoding), |lock_| lock_)?;
 
     QueryIter::new(load_cell_lock, Source::Input)
@@ -147,6 +427,39 @@ pub fn check_group_input_witness_is_none_with_type(type_script: &Script) -> Resu
         .map_or_else(|| Err(Error::Encoding), |result_| result_)
 }
 
+/// Load the action declared in the input_type of the first group input witness, or in the
+/// output_type of the first group output witness for the creation, and the declaration is encoded
+/// as <action: u8> + <params>
+/// Return the observed action with empty params if nothing is declared.
+pub fn load_declared_action(observed: Action) -> Result<(Action, Bytes), Error> {
+    let source = match observed {
+        Action::Create => Source::GroupOutput,
+        _ => Source::GroupInput,
+    };
+    // The missing witness and the witness which isn't WitnessArgs declare nothing
+    let witness_args = match load_witness_args(0, source) {
+        Ok(witness_args) => Some(witness_args),
+        Err(SysError::IndexOutOfBound) | Err(SysError::Encoding) => None,
+        Err(err) => return Err(err.into()),
+    };
+    let declaration = witness_args.and_then(|witness_args| match observed {
+        Action::Create => witness_args.output_type().to_opt(),
+        _ => witness_args.input_type().to_opt(),
+    });
+    let declaration: Bytes = match declaration {
+        Some(declaration) => declaration.raw_data(),
+        None => return Ok((observed, Bytes::new())),
+    };
+    let action = declaration
+        .first()
+        .and_then(|code| Action::from_code(*code))
+        .ok_or(Error::ActionDeclarationInvalid)?;
+    if action.observed() != observed {
+        return Err(Error::ActionNotMatched);
+    }
+    Ok((action, declaration.slice(1..)))
+}
+
 pub fn parse_dyn_vec_len(data: &[u8]) -> usize {
     let mut size_buf = [0u8; 2];
     size_buf.copy_from_slice(&data[..]);
@@ -154,8 +467,84 @@ pub fn parse_dyn_vec_len(data: &[u8]) -> usize {
     size + DYN_MIN_LEN
 }
 
+/// Parse the records of an extension, and each record is encoded as
+/// <kind: u8> + <size: u16> + <content>
+/// Return None if the last record is truncated.
+pub fn parse_records(data: &[u8]) -> Option<Vec<(u8, &[u8])>> {
+    let mut records = Vec::new();
+    let mut index = 0;
+    while index < data.len() {
+        if data.len() < index + RECORD_HEADER_LEN {
+            return None;
+        }
+        let kind = data[index];
+        let content_len =
+            parse_dyn_vec_len(&data[(index + 1)..(index + RECORD_HEADER_LEN)]) - DYN_MIN_LEN;
+        let content_index = index + RECORD_HEADER_LEN;
+        if data.len() < content_index + content_len {
+            return None;
+        }
+        records.push((kind, &data[content_index..(content_index + content_len)]));
+        index = content_index + content_len;
+    }
+    Some(records)
+}
+
+pub fn new_blake2b() -> Blake2b {
+    Blake2bBuilder::new(HASH_LEN)
+        .personal(b"ckb-default-hash")
+        .build()
+}
+
+pub fn blake2b_256(data: &[u8]) -> [u8; HASH_LEN] {
+    let mut blake2b = new_blake2b();
+    blake2b.update(data);
+    let mut ret = [0u8; HASH_LEN];
+    blake2b.finalize(&mut ret);
+    ret
+}
+
 pub fn u32_from_slice(data: &[u8]) -> u32 {
     let mut buf = [0u8; 4];
     buf.copy_from_slice(data);
     u32::from_be_bytes(buf)
 }
+
+pub fn u64_from_slice(data: &[u8]) -> u64 {
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(data);
+    u64::from_be_bytes(buf)
+}
+
+/// Parse the since of the input as the absolute block number, and return None for the relative
+/// since or the since with the other metrics
+pub fn since_absolute_block_number(since: u64) -> Option<u64> {
+    if since >> SINCE_FLAGS_SHIFT != 0 {
+        return None;
+    }
+    Some(since & SINCE_VALUE_MASK)
+}
+
+/// Parse the since of the input as the relative block number, and return None for the absolute
+/// since or the since with the other metrics
+pub fn since_relative_block_number(since: u64) -> Option<u64> {
+    if since >> SINCE_FLAGS_SHIFT != SINCE_RELATIVE_BLOCK_NUMBER_FLAGS {
+        return None;
+    }
+    Some(since & SINCE_VALUE_MASK)
+}
+
+/// Parse the since of the input as the whole relative epochs, and the fraction of the epoch is
+/// ignored
+pub fn since_relative_epoch_number(since: u64) -> Option<u64> {
+    if since >> SINCE_FLAGS_SHIFT != SINCE_RELATIVE_EPOCH_FLAGS {
+        return None;
+    }
+    Some(since & SINCE_EPOCH_NUMBER_MASK)
+}
+
+pub fn u128_from_slice(data: &[u8]) -> u128 {
+    let mut buf = [0u8; 16];
+    buf.copy_from_slice(data);
+    u128::from_be_bytes(buf)
+}
@@ -1,11 +1,24 @@
 use crate::error::Error;
-use crate::helper::{parse_dyn_vec_len, u32_from_slice, DYN_MIN_LEN};
+use crate::helper::{
+    parse_dyn_vec_len, parse_records, u32_from_slice, u64_from_slice, DYN_MIN_LEN, HASH_LEN,
+};
+use crate::lock_policy::{LockPolicy, LOCK_POLICY_RECORD};
+use alloc::vec::Vec;
 use core::result::Result;
 
 const FIXED_LEN: usize = 9;
 // FIXED_LEN + DYN_MIN_LEN
 const ISSUER_DATA_MIN_LEN: usize = 11;
 pub const ISSUER_TYPE_ARGS_LEN: usize = 20;
+// ISSUER_TYPE_ARGS_LEN + the first class id(u32) of the quota
+pub const DELEGATE_QUOTA_ARGS_LEN: usize = 24;
+const DELEGATE_QUOTA_DATA_LEN: usize = 9;
+
+pub const DELEGATES_RECORD: u8 = 5;
+pub const PENDING_OWNER_RECORD: u8 = 6;
+pub const DESTROYED_CLASS_COUNT_RECORD: u8 = 7;
+// the lock hash and the expiry(u64) of the pending owner
+const PENDING_OWNER_LEN: usize = 40;
 
 /// Issuer cell data structure
 /// This structure contains the following information:
@@ -13,11 +26,38 @@ pub const ISSUER_TYPE_ARGS_LEN: usize = 20;
 /// 2) class_count: u32
 /// 3) set_count: u32
 /// 4) info: <size: u16> + <content>
+/// 5) extension: <size: u16> + <records>, only for version 1
+/// The field of 1) and the lock policy of 5) cannot be changed after they are set.
+///
+/// Each record of the extension is encoded as <kind: u8> + <size: u16> + <content> and every
+/// kind can appear at most once:
+/// 4) lock policy: <mode: u8> + <code_hash: [u8; 32]> * n, the allow list(mode 0) or the deny
+/// list(mode 1) of the lock code hashes of the issuer cell
+/// 5) delegates: <lock_hash: [u8; 32]> * n, the locks which can hold the delegate quota cells to
+/// create classes without spending the issuer cell
+/// 6) pending owner: <lock_hash: [u8; 32]> + <expiry: u64>, the lock which the issuer cell is
+/// being transferred to, and the absolute block number after which the transfer can be cancelled.
+/// The transfer is completed by a transaction signed by both the owner and the pending owner.
+/// 7) destroyed class count: u32, the count of the classes destroyed along with the issuer updates
+/// and of the class ids released by the destroyed delegate quota cells, so that the issuer can be
+/// destroyed once all its classes are gone. The version 0 issuer migrated to version 1 declares
+/// the count of the classes destroyed before the migration.
 #[derive(Debug, Clone)]
 pub struct Issuer {
-    pub version:     u8,
-    pub class_count: u32,
-    pub set_count:   u32,
+    pub version:               u8,
+    pub class_count:           u32,
+    pub set_count:             u32,
+    pub extension:             Vec<u8>,
+    pub lock_policy:           Option<LockPolicy>,
+    pub delegates:             Vec<[u8; 32]>,
+    pub pending_owner:         Option<PendingOwner>,
+    pub destroyed_class_count: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingOwner {
+    pub lock_hash: [u8; 32],
+    pub expiry:    u64,
 }
 
 impl Issuer {
@@ -27,7 +67,7 @@ impl Issuer {
         }
 
         let version: u8 = data[0];
-        if version != 0 {
+        if version > 1 {
             return Err(Error::VersionInvalid);
         }
 
@@ -39,10 +79,122 @@ impl Issuer {
             return Err(Error::IssuerDataInvalid);
         }
 
+        let mut extension = Vec::new();
+        if version == 1 {
+            let extension_index = FIXED_LEN + info_len;
+            // DYN_MIN_LEN: the min length of extension
+            if data.len() < extension_index + DYN_MIN_LEN {
+                return Err(Error::IssuerDataInvalid);
+            }
+            let extension_len =
+                parse_dyn_vec_len(&data[extension_index..(extension_index + DYN_MIN_LEN)]);
+            if data.len() < extension_index + extension_len {
+                return Err(Error::IssuerDataInvalid);
+            }
+            extension =
+                data[(extension_index + DYN_MIN_LEN)..(extension_index + extension_len)].to_vec();
+        }
+
+        let mut lock_policy = None;
+        let mut delegates = Vec::new();
+        let mut pending_owner = None;
+        let mut destroyed_class_count = None;
+        let records = parse_records(&extension).ok_or(Error::IssuerDataInvalid)?;
+        for (kind, content) in records {
+            match kind {
+                LOCK_POLICY_RECORD if lock_policy.is_none() => {
+                    lock_policy =
+                        Some(LockPolicy::from_record(content).ok_or(Error::IssuerDataInvalid)?);
+                }
+                DELEGATES_RECORD if delegates.is_empty() => {
+                    if content.is_empty() || content.len() % HASH_LEN != 0 {
+                        return Err(Error::IssuerDataInvalid);
+                    }
+                    delegates = content
+                        .chunks(HASH_LEN)
+                        .map(|chunk| {
+                            let mut lock_hash = [0u8; HASH_LEN];
+                            lock_hash.copy_from_slice(chunk);
+                            lock_hash
+                        })
+                        .collect();
+                }
+                PENDING_OWNER_RECORD if pending_owner.is_none() => {
+                    if content.len() != PENDING_OWNER_LEN {
+                        return Err(Error::IssuerDataInvalid);
+                    }
+                    let mut lock_hash = [0u8; HASH_LEN];
+                    lock_hash.copy_from_slice(&content[..HASH_LEN]);
+                    pending_owner = Some(PendingOwner {
+                        lock_hash,
+                        expiry: u64_from_slice(&content[HASH_LEN..]),
+                    });
+                }
+                DESTROYED_CLASS_COUNT_RECORD if destroyed_class_count.is_none() => {
+                    if content.len() != 4 {
+                        return Err(Error::IssuerDataInvalid);
+                    }
+                    destroyed_class_count = Some(u32_from_slice(content));
+                }
+                _ => return Err(Error::IssuerDataInvalid),
+            }
+        }
+
         Ok(Issuer {
             version,
             class_count,
             set_count,
+            extension,
+            lock_policy,
+            delegates,
+            pending_owner,
+            destroyed_class_count: destroyed_class_count.unwrap_or(0),
+        })
+    }
+
+    // The delegates, the pending owner and the destroyed class count can be changed, and the lock
+    // policy is immutable
+    pub fn immutable_equal(&self, other: &Issuer) -> bool {
+        self.version == other.version && self.lock_policy == other.lock_policy
+    }
+
+    pub fn is_delegate(&self, lock_hash: &[u8; 32]) -> bool {
+        self.delegates.iter().any(|delegate| delegate == lock_hash)
+    }
+}
+
+/// Delegate quota cell data structure
+/// This structure contains the following information:
+/// 1) version: u8
+/// 2) next_class_id: u32
+/// 3) end_class_id: u32
+/// The quota reserves the class ids from the first class id of its type args to the end class id
+/// (exclusive) of the issuer, and the delegate creates the classes from the next class id.
+#[derive(Debug, Clone)]
+pub struct DelegateQuota {
+    pub version:       u8,
+    pub next_class_id: u32,
+    pub end_class_id:  u32,
+}
+
+impl DelegateQuota {
+    pub fn from_data(data: &[u8]) -> Result<Self, Error> {
+        if data.len() != DELEGATE_QUOTA_DATA_LEN {
+            return Err(Error::DelegateQuotaInvalid);
+        }
+        let version: u8 = data[0];
+        if version != 0 {
+            return Err(Error::VersionInvalid);
+        }
+        let next_class_id = u32_from_slice(&data[1..5]);
+        let end_class_id = u32_from_slice(&data[5..9]);
+        if next_class_id > end_class_id {
+            return Err(Error::DelegateQuotaInvalid);
+        }
+        Ok(DelegateQuota {
+            version,
+            next_class_id,
+            end_class_id,
         })
     }
 }
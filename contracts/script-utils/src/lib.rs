@@ -3,6 +3,16 @@ extern crate alloc;
 
 pub mod class;
 pub mod error;
+#[cfg(feature = "error-table")]
+pub mod error_table;
+pub mod ext_info;
 pub mod helper;
 pub mod issuer;
+pub mod lock_policy;
+pub mod market;
+pub mod media;
+pub mod nesting;
 pub mod nft;
+pub mod redemption;
+mod trace;
+pub mod vault;
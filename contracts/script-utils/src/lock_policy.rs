@@ -0,0 +1,89 @@
+use crate::error::Error;
+use crate::helper::{check_group_input_witness_is_none_with_type, HASH_LEN};
+use crate::trace;
+use alloc::vec::Vec;
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{packed::*, prelude::*},
+    high_level::{load_cell_lock, QueryIter},
+};
+use core::result::Result;
+
+// The lock policy record kind shared by the class extension and the issuer extension
+pub const LOCK_POLICY_RECORD: u8 = 4;
+
+const ALLOW_LIST_MODE: u8 = 0;
+const DENY_LIST_MODE: u8 = 1;
+
+/// The lock code hashes which the class, nft or issuer cells may or may not sit under
+#[derive(Debug, Clone, PartialEq)]
+pub enum LockPolicy {
+    AllowList(Vec<[u8; 32]>),
+    DenyList(Vec<[u8; 32]>),
+}
+
+impl LockPolicy {
+    /// Lock policy record structure: <mode: u8> + <code_hash: [u8; 32]> * n
+    /// mode 0 means the allow list and mode 1 means the deny list, and n cannot be zero
+    pub fn from_record(content: &[u8]) -> Option<Self> {
+        if content.len() <= 1 || (content.len() - 1) % HASH_LEN != 0 {
+            return None;
+        }
+        let code_hashes = content[1..]
+            .chunks(HASH_LEN)
+            .map(|chunk| {
+                let mut code_hash = [0u8; HASH_LEN];
+                code_hash.copy_from_slice(chunk);
+                code_hash
+            })
+            .collect();
+        match content[0] {
+            ALLOW_LIST_MODE => Some(LockPolicy::AllowList(code_hashes)),
+            DENY_LIST_MODE => Some(LockPolicy::DenyList(code_hashes)),
+            _ => None,
+        }
+    }
+
+    pub fn permits(&self, lock: &Script) -> bool {
+        let code_hash = lock.code_hash();
+        let listed = |code_hashes: &Vec<[u8; 32]>| {
+            code_hashes
+                .iter()
+                .any(|hash| &hash[..] == code_hash.as_slice())
+        };
+        match self {
+            LockPolicy::AllowList(code_hashes) => listed(code_hashes),
+            LockPolicy::DenyList(code_hashes) => !listed(code_hashes),
+        }
+    }
+}
+
+pub fn check_lock_policy(policy: &LockPolicy, source: Source) -> Result<(), Error> {
+    match QueryIter::new(load_cell_lock, source).position(|lock| !policy.permits(&lock)) {
+        None => Ok(()),
+        Some(index) => {
+            trace!("lock policy violated by cell {} of {:?}", index, source);
+            Err(Error::LockPolicyViolated)
+        }
+    }
+}
+
+/// Check the locks of the group cells against the lock policy, and the cells without lock policy
+/// still disable the anyone-can-pay lock with the witness of the owner's first input.
+pub fn check_group_locks(
+    type_script: &Script,
+    policy: &Option<LockPolicy>,
+    sources: &[Source],
+) -> Result<(), Error> {
+    match policy {
+        Some(policy) => sources
+            .iter()
+            .try_for_each(|source| check_lock_policy(policy, *source)),
+        None => {
+            if check_group_input_witness_is_none_with_type(type_script)? {
+                return Err(Error::GroupInputWitnessNoneError);
+            }
+            Ok(())
+        }
+    }
+}
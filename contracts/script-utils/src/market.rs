@@ -0,0 +1,148 @@
+use crate::error::Error;
+use crate::helper::{load_cell_data_header, HASH_LEN};
+use crate::nft::{Nft, NFT_DATA_MIN_LEN};
+use crate::trace;
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{packed::*, prelude::*},
+    error::SysError,
+    high_level::{
+        load_cell_capacity, load_cell_data, load_cell_lock_hash, load_cell_type,
+        load_cell_type_hash, load_header, QueryIter,
+    },
+};
+use core::result::Result;
+
+const SUDT_AMOUNT_LEN: usize = 16;
+// code_hash + hash_type: u8
+pub const NFT_CODE_LEN: usize = 33;
+
+/// The code of the nft type in the market lock args: <code_hash: [u8; 32]> + <hash_type: u8>, so
+/// that the cells of other types are not taken as the nfts
+#[derive(Debug, Clone, PartialEq)]
+pub struct NftCode {
+    pub code_hash: [u8; 32],
+    pub hash_type: u8,
+}
+
+impl NftCode {
+    pub fn from_slice(content: &[u8]) -> Option<Self> {
+        if content.len() != NFT_CODE_LEN {
+            return None;
+        }
+        let mut code_hash = [0u8; HASH_LEN];
+        code_hash.copy_from_slice(&content[0..HASH_LEN]);
+        Some(NftCode {
+            code_hash,
+            hash_type: content[HASH_LEN],
+        })
+    }
+
+    pub fn is_nft_type(&self, type_: &Script) -> bool {
+        type_.code_hash().as_slice() == &self.code_hash[..]
+            && type_.hash_type().as_slice()[0] == self.hash_type
+    }
+}
+
+/// The currency of the prices in the market locks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Currency {
+    Ckb,
+    Sudt([u8; 32]),
+}
+
+/// The amount paid to the receiver by the output: the capacity in shannons for CKB, or the
+/// little-endian u128 amount of the sUDT cell
+pub fn load_paid_amount(
+    index: usize,
+    currency: &Currency,
+    receiver_lock_hash: &[u8; 32],
+) -> Result<u128, Error> {
+    match load_cell_lock_hash(index, Source::Output) {
+        Ok(lock_hash) if &lock_hash == receiver_lock_hash => (),
+        Ok(_) | Err(SysError::IndexOutOfBound) => return Ok(0),
+        Err(err) => return Err(err.into()),
+    }
+    match currency {
+        Currency::Ckb => Ok(load_cell_capacity(index, Source::Output)? as u128),
+        Currency::Sudt(type_hash) => {
+            if load_cell_type_hash(index, Source::Output)?.as_ref() != Some(type_hash) {
+                return Ok(0);
+            }
+            let data = load_cell_data(index, Source::Output)?;
+            Ok(parse_sudt_amount(&data).unwrap_or(0))
+        }
+    }
+}
+
+/// The sUDT amount is the little-endian u128 at the start of the cell data
+pub fn parse_sudt_amount(data: &[u8]) -> Option<u128> {
+    if data.len() < SUDT_AMOUNT_LEN {
+        return None;
+    }
+    let mut amount = [0u8; SUDT_AMOUNT_LEN];
+    amount.copy_from_slice(&data[..SUDT_AMOUNT_LEN]);
+    Some(u128::from_le_bytes(amount))
+}
+
+pub fn inputs_have_lock_hash(lock_hash: &[u8; 32]) -> bool {
+    QueryIter::new(load_cell_lock_hash, Source::Input)
+        .any(|input_lock_hash| &input_lock_hash == lock_hash)
+}
+
+/// The latest timestamp in milliseconds of the header deps, which proves the time has passed
+pub fn load_header_deps_timestamp() -> Option<u64> {
+    QueryIter::new(load_header, Source::HeaderDep)
+        .map(|header| header.raw().timestamp().unpack())
+        .max()
+}
+
+/// The timestamp in milliseconds of the block which commits the input, and the block hash must be
+/// in the header deps
+pub fn load_input_committed_timestamp(index: usize) -> Option<u64> {
+    load_header(index, Source::Input)
+        .ok()
+        .map(|header| header.raw().timestamp().unpack())
+}
+
+/// Load the nft of the input under the market lock, which must be of the nft type code, and only
+/// the nft header is loaded
+pub fn load_nft_of_input(index: usize, nft_code: &NftCode) -> Result<Nft, Error> {
+    match load_cell_type(index, Source::Input)? {
+        Some(type_) if nft_code.is_nft_type(&type_) => (),
+        _ => {
+            trace!("input {} under the market lock is not an nft", index);
+            return Err(Error::MarketNFTTypeInvalid);
+        }
+    }
+    let (header, _) = load_cell_data_header(index, Source::Input, NFT_DATA_MIN_LEN)
+        .map_err(|_| Error::NFTDataInvalid)?;
+    Nft::from_data(&header)
+}
+
+/// The nft leaving the market lock must be transferable, which is the same as the transfer rules
+/// of the nft type
+pub fn check_nft_transferable(nft: &Nft) -> Result<(), Error> {
+    if nft.is_locked() {
+        return Err(Error::LockedNFTCannotTransfer);
+    }
+    if !nft.is_claimed() && !nft.allow_transfer_before_claim() {
+        return Err(Error::NFTCannotTransferBeforeClaim);
+    }
+    if nft.is_claimed() && !nft.allow_transfer_after_claim() {
+        return Err(Error::NFTCannotTransferAfterClaim);
+    }
+    Ok(())
+}
+
+/// The unclaimed nft must be claimable to flip its claim state, which is the same as the claim
+/// rules of the nft type
+pub fn check_nft_claimable(nft: &Nft) -> Result<(), Error> {
+    if nft.is_locked() {
+        return Err(Error::LockedNFTCannotClaim);
+    }
+    if !nft.allow_claim() {
+        return Err(Error::NFTDisallowClaimed);
+    }
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use crate::class::ContentHash;
+use crate::helper::{blake2b_256, new_blake2b, HASH_LEN};
+use alloc::vec::Vec;
+
+type Hash = [u8; HASH_LEN];
+
+/// The helpers in this module don't use any syscall, so that the issuers and buyers can verify the
+/// media of a token against the content hash of its class off-chain.
+pub fn media_hash(media: &[u8]) -> Hash {
+    blake2b_256(media)
+}
+
+/// The merkle leaf of a token: blake2b(token_id(u32 big endian) + media_hash)
+pub fn token_leaf_hash(token_id: u32, media_hash: &Hash) -> Hash {
+    let mut blake2b = new_blake2b();
+    blake2b.update(&token_id.to_be_bytes());
+    blake2b.update(media_hash);
+    let mut ret = [0u8; HASH_LEN];
+    blake2b.finalize(&mut ret);
+    ret
+}
+
+// The two children are sorted before hashing, so that the proof doesn't need the positions
+fn merge_nodes(left: &Hash, right: &Hash) -> Hash {
+    let (first, second) = if left <= right {
+        (left, right)
+    } else {
+        (right, left)
+    };
+    let mut blake2b = new_blake2b();
+    blake2b.update(first);
+    blake2b.update(second);
+    let mut ret = [0u8; HASH_LEN];
+    blake2b.finalize(&mut ret);
+    ret
+}
+
+// The last node of an odd level is promoted to the next level as it is
+fn next_level(nodes: &[Hash]) -> Vec<Hash> {
+    nodes
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => merge_nodes(left, right),
+            _ => pair[0],
+        })
+        .collect()
+}
+
+/// Calculate the merkle root of the token leaves ordered by token id
+pub fn merkle_root(leaves: &[Hash]) -> Hash {
+    if leaves.is_empty() {
+        return [0u8; HASH_LEN];
+    }
+    let mut nodes = leaves.to_vec();
+    while nodes.len() > 1 {
+        nodes = next_level(&nodes);
+    }
+    nodes[0]
+}
+
+/// Generate the merkle proof of the leaf at the index
+pub fn merkle_proof(leaves: &[Hash], index: usize) -> Vec<Hash> {
+    let mut proof = Vec::new();
+    let mut nodes = leaves.to_vec();
+    let mut index = index;
+    while nodes.len() > 1 {
+        let sibling = index ^ 1;
+        if sibling < nodes.len() {
+            proof.push(nodes[sibling]);
+        }
+        nodes = next_level(&nodes);
+        index /= 2;
+    }
+    proof
+}
+
+/// Verify the media of a token against the content hash of its class, and the proof is only
+/// required by the merkle root.
+pub fn verify_token_media(
+    content_hash: &ContentHash,
+    token_id: u32,
+    media: &[u8],
+    proof: &[Hash],
+) -> bool {
+    match content_hash {
+        ContentHash::Media(hash) => &media_hash(media) == hash,
+        ContentHash::MerkleRoot(root) => {
+            let leaf = token_leaf_hash(token_id, &media_hash(media));
+            let computed_root = proof
+                .iter()
+                .fold(leaf, |node, sibling| merge_nodes(&node, sibling));
+            &computed_root == root
+        }
+    }
+}
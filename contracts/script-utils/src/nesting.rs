@@ -0,0 +1,72 @@
+use crate::error::Error;
+use crate::helper::HASH_LEN;
+use alloc::vec::Vec;
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{packed::*, prelude::*},
+    high_level::{load_cell_lock, load_cell_type_hash, QueryIter},
+};
+use core::result::Result;
+
+// parent_type_hash + owner_lock_code_hash + owner_lock_hash_type: u8
+pub const PARENT_BOUND_LOCK_ARGS_LEN: usize = 65;
+
+/// Parent-bound lock args structure:
+/// 1) parent_type_hash: [u8; 32], the child nft is bound to the cell of the type hash
+/// 2) owner_lock_code_hash: [u8; 32] + owner_lock_hash_type: u8, the code of the lock which is
+/// signed by the owner of the parent, and the children are detached only when the parent is spent
+/// under a lock of the code
+#[derive(Debug, Clone)]
+pub struct ParentBound {
+    pub parent_type_hash:     [u8; 32],
+    pub owner_lock_code_hash: [u8; 32],
+    pub owner_lock_hash_type: u8,
+}
+
+impl ParentBound {
+    pub fn from_args(lock_args: &[u8]) -> Result<Self, Error> {
+        if lock_args.len() != PARENT_BOUND_LOCK_ARGS_LEN {
+            return Err(Error::ParentBoundLockArgsInvalid);
+        }
+        let mut parent_type_hash = [0u8; HASH_LEN];
+        parent_type_hash.copy_from_slice(&lock_args[0..HASH_LEN]);
+        let mut owner_lock_code_hash = [0u8; HASH_LEN];
+        owner_lock_code_hash.copy_from_slice(&lock_args[HASH_LEN..HASH_LEN * 2]);
+        Ok(ParentBound {
+            parent_type_hash,
+            owner_lock_code_hash,
+            owner_lock_hash_type: lock_args[HASH_LEN * 2],
+        })
+    }
+
+    /// The market locks can be spent by anyone who pays for the parent, so only the lock of the
+    /// owner code proves that the owner of the parent signed the transaction
+    pub fn is_owner_lock(&self, lock: &Script) -> bool {
+        lock.code_hash().as_slice() == &self.owner_lock_code_hash[..]
+            && lock.hash_type().as_slice()[0] == self.owner_lock_hash_type
+    }
+}
+
+pub fn position_of_parent(parent_type_hash: &[u8; 32], source: Source) -> Option<usize> {
+    QueryIter::new(load_cell_type_hash, source)
+        .position(|type_hash| type_hash.as_ref() == Some(parent_type_hash))
+}
+
+/// The indexes of the children bound to the parent by the parent-bound lock of the same code with
+/// any owner lock code, and the children may be the parents of their own children
+pub fn load_children_of_parent(
+    parent_bound_lock: &Script,
+    parent_type_hash: &[u8; 32],
+    source: Source,
+) -> Vec<usize> {
+    QueryIter::new(load_cell_lock, source)
+        .enumerate()
+        .filter(|(_, lock)| {
+            lock.code_hash().as_slice() == parent_bound_lock.code_hash().as_slice()
+                && lock.hash_type().as_slice() == parent_bound_lock.hash_type().as_slice()
+                && lock.args().raw_data().len() == PARENT_BOUND_LOCK_ARGS_LEN
+                && lock.args().raw_data()[0..HASH_LEN] == parent_type_hash[..]
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
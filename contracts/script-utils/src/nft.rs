@@ -1,8 +1,10 @@
 use crate::error::Error;
+use crate::helper::new_blake2b;
 use core::result::Result;
 
 pub const NFT_DATA_MIN_LEN: usize = 11;
 pub const NFT_TYPE_ARGS_LEN: usize = 28;
+const CHARACTERISTIC_LEN: usize = 8;
 
 /// NFT cell data structure
 /// This structure contains the following information:
@@ -10,8 +12,9 @@ pub const NFT_TYPE_ARGS_LEN: usize = 28;
 /// 2) characteristic: [u8; 8]
 /// 3) configure: u8
 /// 4) state: u8
-/// 5) extinfo_data: <size: u16> + <vartext>
-/// The filed of 5) can be changed and it also can be missing and it will not be validated.
+/// 5) extinfo_data: <size: u16> + <vartext> for version 0, or the typed records for version 1
+/// The filed of 5) can be changed and it also can be missing. The version 0 ext info will not be
+/// validated, and the version 1 ext info must be the records defined in the ext_info module.
 #[derive(Debug, Clone)]
 pub struct Nft {
     pub version:        u8,
@@ -27,7 +30,7 @@ impl Nft {
         }
 
         let version: u8 = data[0];
-        if version != 0 {
+        if version > 1 {
             return Err(Error::VersionInvalid);
         }
 
@@ -85,3 +88,16 @@ impl Nft {
         self.state & 0b0000_0010 == 0b0000_0010
     }
 }
+
+/// The characteristic revealed by the seed committed in the class:
+/// the first 8 bytes of blake2b(seed + token_id(u32 big endian))
+pub fn reveal_characteristic(seed: &[u8], token_id: u32) -> [u8; CHARACTERISTIC_LEN] {
+    let mut blake2b = new_blake2b();
+    blake2b.update(seed);
+    blake2b.update(&token_id.to_be_bytes());
+    let mut ret = [0u8; 32];
+    blake2b.finalize(&mut ret);
+    let mut characteristic = [0u8; CHARACTERISTIC_LEN];
+    characteristic.copy_from_slice(&ret[0..CHARACTERISTIC_LEN]);
+    characteristic
+}
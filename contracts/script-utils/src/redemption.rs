@@ -0,0 +1,115 @@
+use crate::class::CLASS_TYPE_ARGS_LEN;
+use crate::error::Error;
+use crate::helper::HASH_LEN;
+use alloc::vec::Vec;
+use ckb_std::ckb_types::{packed::*, prelude::*};
+use core::result::Result;
+
+// The redemption record kind of the class extension
+pub const REDEMPTION_RECORD: u8 = 6;
+
+// code_hash + hash_type: u8
+const RECEIPT_CODE_LEN: usize = 33;
+// class_type_args + nft_code_hash + nft_hash_type: u8
+pub const RECEIPT_TYPE_ARGS_LEN: usize = CLASS_TYPE_ARGS_LEN + HASH_LEN + 1;
+// nft_type_hash + redeemer_lock_hash
+pub const RECEIPT_DATA_LEN: usize = 64;
+
+/// The type script code of the redemption receipts of a class
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceiptCode {
+    pub code_hash: [u8; 32],
+    pub hash_type: u8,
+}
+
+impl ReceiptCode {
+    /// Redemption record structure: <code_hash: [u8; 32]> + <hash_type: u8>
+    pub fn from_record(content: &[u8]) -> Option<Self> {
+        if content.len() != RECEIPT_CODE_LEN {
+            return None;
+        }
+        let mut code_hash = [0u8; HASH_LEN];
+        code_hash.copy_from_slice(&content[0..HASH_LEN]);
+        Some(ReceiptCode {
+            code_hash,
+            hash_type: content[HASH_LEN],
+        })
+    }
+
+    /// The receipts of a class are indexed by the receipt type with the class type args and the
+    /// code of the nft type
+    pub fn receipt_type(&self, class_args: &[u8], nft_type: &Script) -> Script {
+        let mut args = class_args.to_vec();
+        args.extend_from_slice(nft_type.code_hash().as_slice());
+        args.extend_from_slice(nft_type.hash_type().as_slice());
+        Script::new_builder()
+            .code_hash(self.code_hash.pack())
+            .hash_type(Byte::new(self.hash_type))
+            .args(args.pack())
+            .build()
+    }
+}
+
+/// Redemption receipt type args structure:
+/// 1) class_type_args: [u8; 24], the class of the burned nfts
+/// 2) nft_code_hash: [u8; 32] + nft_hash_type: u8, the code of the nft type of the burned nfts,
+/// so that a cell of another type cannot be burned for a receipt
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceiptArgs {
+    pub class_args:    [u8; CLASS_TYPE_ARGS_LEN],
+    pub nft_code_hash: [u8; 32],
+    pub nft_hash_type: u8,
+}
+
+impl ReceiptArgs {
+    pub fn from_args(args: &[u8]) -> Result<Self, Error> {
+        if args.len() != RECEIPT_TYPE_ARGS_LEN {
+            return Err(Error::TypeArgsInvalid);
+        }
+        let mut class_args = [0u8; CLASS_TYPE_ARGS_LEN];
+        class_args.copy_from_slice(&args[0..CLASS_TYPE_ARGS_LEN]);
+        let mut nft_code_hash = [0u8; HASH_LEN];
+        nft_code_hash.copy_from_slice(&args[CLASS_TYPE_ARGS_LEN..RECEIPT_TYPE_ARGS_LEN - 1]);
+        Ok(ReceiptArgs {
+            class_args,
+            nft_code_hash,
+            nft_hash_type: args[RECEIPT_TYPE_ARGS_LEN - 1],
+        })
+    }
+
+    pub fn is_nft_type(&self, type_: &Script) -> bool {
+        type_.code_hash().as_slice() == &self.nft_code_hash[..]
+            && type_.hash_type().as_slice()[0] == self.nft_hash_type
+    }
+}
+
+/// Redemption receipt cell data structure:
+/// 1) nft_type_hash: [u8; 32], the type hash of the burned nft
+/// 2) redeemer_lock_hash: [u8; 32], the lock hash of the burned nft
+#[derive(Debug, Clone, PartialEq)]
+pub struct Receipt {
+    pub nft_type_hash:      [u8; 32],
+    pub redeemer_lock_hash: [u8; 32],
+}
+
+impl Receipt {
+    pub fn from_data(data: &[u8]) -> Result<Self, Error> {
+        if data.len() != RECEIPT_DATA_LEN {
+            return Err(Error::RedemptionReceiptInvalid);
+        }
+        let mut nft_type_hash = [0u8; HASH_LEN];
+        nft_type_hash.copy_from_slice(&data[0..HASH_LEN]);
+        let mut redeemer_lock_hash = [0u8; HASH_LEN];
+        redeemer_lock_hash.copy_from_slice(&data[HASH_LEN..RECEIPT_DATA_LEN]);
+        Ok(Receipt {
+            nft_type_hash,
+            redeemer_lock_hash,
+        })
+    }
+
+    pub fn to_data(&self) -> Vec<u8> {
+        let mut data = self.nft_type_hash.to_vec();
+        data.extend_from_slice(&self.redeemer_lock_hash);
+        data
+    }
+}
@@ -0,0 +1,12 @@
+/// Emit a debug line when the calling contract is built with the `debug-trace` feature.
+///
+/// The line is printed by `ckb_std::debug!`, which is compiled out without debug assertions, so
+/// release builds carry no traces even with the feature on.
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        if cfg!(feature = "debug-trace") {
+            ckb_std::debug!($($arg)*);
+        }
+    };
+}
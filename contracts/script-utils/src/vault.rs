@@ -0,0 +1,52 @@
+use crate::error::Error;
+use crate::helper::{u128_from_slice, HASH_LEN};
+use crate::market::NftCode;
+use ckb_std::ckb_types::{packed::*, prelude::*};
+use core::result::Result;
+
+// nft_type_hash
+pub const SHARES_TYPE_ARGS_LEN: usize = 32;
+// nft_type_hash + supply(u128) + nft_code_hash + nft_hash_type(u8)
+pub const VAULT_LOCK_ARGS_LEN: usize = 81;
+
+/// Vault lock args structure:
+/// 1) nft_type_hash: [u8; 32]
+/// 2) supply: u128, the fixed supply of the shares minted when the nft is locked into the vault
+/// 3) nft_code_hash: [u8; 32] + nft_hash_type: u8, the code of the nft type, so that only the
+/// cell of the nft type is vaulted, and the nft type requires the shares to be minted with it
+#[derive(Debug, Clone)]
+pub struct Vault {
+    pub nft_type_hash: [u8; 32],
+    pub supply:        u128,
+    pub nft_code:      NftCode,
+}
+
+impl Vault {
+    pub fn from_args(args: &[u8]) -> Result<Self, Error> {
+        if args.len() != VAULT_LOCK_ARGS_LEN {
+            return Err(Error::VaultArgsInvalid);
+        }
+        let mut nft_type_hash = [0u8; HASH_LEN];
+        nft_type_hash.copy_from_slice(&args[0..HASH_LEN]);
+        let supply = u128_from_slice(&args[HASH_LEN..48]);
+        if supply == 0 {
+            return Err(Error::VaultArgsInvalid);
+        }
+        let nft_code = NftCode::from_slice(&args[48..]).ok_or(Error::VaultArgsInvalid)?;
+        Ok(Vault {
+            nft_type_hash,
+            supply,
+            nft_code,
+        })
+    }
+}
+
+/// The shares type is the vault code with the nft type hash as args, so that the shares of every
+/// vault commit to its nft
+pub fn shares_type(vault_lock: &Script, nft_type_hash: &[u8]) -> Script {
+    Script::new_builder()
+        .code_hash(vault_lock.code_hash())
+        .hash_type(vault_lock.hash_type())
+        .args(nft_type_hash.pack())
+        .build()
+}
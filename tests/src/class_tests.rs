@@ -11,6 +11,7 @@ const MAX_CYCLES: u64 = 10_000_000;
 
 // error numbers
 const ENCODING: i8 = 4;
+const ISSUER_CELLS_COUNT_ERROR: i8 = 6;
 const TYPE_ARGS_INVALID: i8 = 7;
 const CLASS_DATA_INVALID: i8 = 12;
 const CLASS_TOTAL_SMALLER_THAN_ISSUED: i8 = 13;
@@ -20,11 +21,24 @@ const CLASS_IMMUTABLE_FIELDS_NOT_SAME: i8 = 16;
 const CLASS_CELL_CANNOT_DESTROYED: i8 = 17;
 const CLASS_ID_INCREASE_ERROR: i8 = 18;
 const GROUP_INPUT_WITNESS_NONE_ERROR: i8 = 40;
+const LOCK_POLICY_VIOLATED: i8 = 51;
+const ACTION_DECLARATION_INVALID: i8 = 53;
+const DELEGATE_QUOTA_INVALID: i8 = 56;
+const DELEGATE_NOT_ALLOWED: i8 = 57;
+
+// declared actions
+const REVEAL_ACTION: u8 = 3;
+
+const CONTENT_HASH: &str = "df518d6081b2c1ebc8b989738e3e8e833e0080d26f0f6024cf7d6da93d2f9af7";
+const ANOTHER_CONTENT_HASH: &str =
+    "e3c7017d5fba6f96434da7ca9dcbde82c67713057496ba551d49dc56b1e2c07d";
 
 #[derive(PartialEq, Eq, Clone, Copy)]
 enum UpdateCase {
     Default,
     Batch,
+    ContentHash,
+    LockPolicy,
 }
 
 #[derive(PartialEq)]
@@ -49,9 +63,16 @@ enum ClassError {
     ClassTypeArgsInvalid,
     TypeArgsClassIdNotSame,
     GroupInputWitnessNoneError,
+    ClassContentHashInvalid,
+    ClassContentHashNotSame,
+    ClassExtInfoInvalid,
+    LockPolicyViolated,
+    ActionDeclarationInvalid,
+    IssuerNotSpent,
 }
 
 fn create_test_context(action: Action, class_error: ClassError) -> (Context, TransactionView) {
+    let issuer_not_spent = matches!(class_error, ClassError::IssuerNotSpent);
     // deploy contract
     let mut context = Context::default();
 
@@ -102,7 +123,29 @@ fn create_test_context(action: Action, class_error: ClassError) -> (Context, Tra
         .previous_output(issuer_input_out_point.clone())
         .build();
 
+    // version 1 class data with the lock policy record: <mode: u8> + <code_hash: [u8; 32]>
+    let lock_policy_mode = match class_error {
+        ClassError::LockPolicyViolated => "01",
+        _ => "00",
+    };
+    let lock_policy_class_data = Bytes::from(
+        hex::decode(format!(
+            "010000000f00000005000001550002666600038989890024040021{}{}",
+            lock_policy_mode,
+            hex::encode(lock_script.code_hash().as_slice())
+        ))
+        .unwrap(),
+    );
+
     let class_input_data = match action {
+        Action::Update(UpdateCase::LockPolicy) => lock_policy_class_data.clone(),
+        Action::Update(UpdateCase::ContentHash) => Bytes::from(
+            hex::decode(format!(
+                "010000000f0000000500000155000266660003898989002401002100{}",
+                CONTENT_HASH
+            ))
+            .unwrap(),
+        ),
         Action::Update(_) => {
             Bytes::from(hex::decode("000000000f0000000500000155000266660003898989").unwrap())
         }
@@ -169,7 +212,9 @@ fn create_test_context(action: Action, class_error: ClassError) -> (Context, Tra
         Action::Create => vec![issuer_input],
         Action::Destroy => vec![class_input.clone(), class_input],
         Action::Update(case) => match case {
-            UpdateCase::Default => vec![class_input],
+            UpdateCase::Default | UpdateCase::ContentHash | UpdateCase::LockPolicy => {
+                vec![class_input]
+            }
             UpdateCase::Batch => vec![class_input, another_class_input],
         },
     };
@@ -207,11 +252,13 @@ fn create_test_context(action: Action, class_error: ClassError) -> (Context, Tra
             .type_(Some(issuer_type_script.clone()).pack())
             .build()],
         Action::Update(case) => match case {
-            UpdateCase::Default => vec![CellOutput::new_builder()
-                .capacity(500u64.pack())
-                .lock(lock_script.clone())
-                .type_(Some(class_type_script.clone()).pack())
-                .build()],
+            UpdateCase::Default | UpdateCase::ContentHash | UpdateCase::LockPolicy => {
+                vec![CellOutput::new_builder()
+                    .capacity(500u64.pack())
+                    .lock(lock_script.clone())
+                    .type_(Some(class_type_script.clone()).pack())
+                    .build()]
+            }
             UpdateCase::Batch => vec![
                 CellOutput::new_builder()
                     .capacity(500u64.pack())
@@ -269,6 +316,18 @@ fn create_test_context(action: Action, class_error: ClassError) -> (Context, Tra
                 Bytes::from(hex::decode("000000000f0000000000000155000266660003898989").unwrap()),
                 Bytes::from(hex::decode("000000000f0000000000000155000266660003898989").unwrap()),
             ],
+            ClassError::ClassContentHashInvalid => vec![
+                Bytes::from(hex::decode("000000000b000000000000").unwrap()),
+                Bytes::from(
+                    hex::decode(format!(
+                        "010000000f0000000000000155000266660003898989002401002102{}",
+                        CONTENT_HASH
+                    ))
+                    .unwrap(),
+                ),
+                Bytes::from(hex::decode("000000000f0000000000000155000266660003898989").unwrap()),
+                Bytes::from(hex::decode("000000000f0000000000000155000266660003898989").unwrap()),
+            ],
             _ => vec![
                 Bytes::from(hex::decode("000000000b000000000000").unwrap()),
                 Bytes::from(hex::decode("000000000f0000000000000155000266660003898989").unwrap()),
@@ -298,10 +357,32 @@ fn create_test_context(action: Action, class_error: ClassError) -> (Context, Tra
             ClassError::ClassDescriptionNotSame => vec![Bytes::from(
                 hex::decode("000000000f0000000500000155000299990003898989").unwrap(),
             )],
+            ClassError::ClassExtInfoInvalid => vec![Bytes::from(
+                hex::decode(format!(
+                    "010000000f000000050000015500026666000489898949002401002100{}00050300080005",
+                    CONTENT_HASH
+                ))
+                .unwrap(),
+            )],
+            ClassError::ClassContentHashNotSame => vec![Bytes::from(
+                hex::decode(format!(
+                    "010000000f000000050000015500026666000489898949002401002100{}",
+                    ANOTHER_CONTENT_HASH
+                ))
+                .unwrap(),
+            )],
             _ => match case {
+                UpdateCase::LockPolicy => vec![lock_policy_class_data.clone()],
                 UpdateCase::Default => vec![Bytes::from(
                     hex::decode("000000000f000000050000015500026666000489898949").unwrap(),
                 )],
+                UpdateCase::ContentHash => vec![Bytes::from(
+                    hex::decode(format!(
+                        "010000000f000000050000015500026666000489898949002401002100{}000b010008697066733a2f2f78",
+                        CONTENT_HASH
+                    ))
+                    .unwrap(),
+                )],
                 UpdateCase::Batch => vec![
                     Bytes::from(
                         hex::decode("000000000f000000050000015500026666000489898949").unwrap(),
@@ -316,10 +397,20 @@ fn create_test_context(action: Action, class_error: ClassError) -> (Context, Tra
     };
 
     let mut witnesses = vec![];
-    match class_error {
-        ClassError::GroupInputWitnessNoneError => {
+    match (class_error, action) {
+        // The lock policy replaces the anyone-can-pay check with the witness
+        (ClassError::GroupInputWitnessNoneError, _)
+        | (ClassError::NoError, Action::Update(UpdateCase::LockPolicy)) => {
             witnesses.push(Bytes::from("0x"))
         }
+        // The declaration in the input_type: <action: u8> + <params>
+        (ClassError::ActionDeclarationInvalid, _) => witnesses.push(
+            WitnessArgs::new_builder()
+                .lock(Some(Bytes::from(vec![0u8; 65])).pack())
+                .input_type(Some(Bytes::from(vec![REVEAL_ACTION])).pack())
+                .build()
+                .as_bytes(),
+        ),
         _ => {
             witnesses.push(Bytes::from(hex::decode("5500000010000000550000005500000041000000b69c542c0ee6c4b6d8350514d876ea7d8ef563e406253e959289457204447d2c4eb4e4a993073f5e76d244d2f93f7c108652e3295a9c8d72c12477e095026b9500").unwrap()))
         }
@@ -328,14 +419,34 @@ fn create_test_context(action: Action, class_error: ClassError) -> (Context, Tra
         witnesses.push(Bytes::from("0x"))
     }
 
+    let mut cell_deps = vec![
+        lock_script_dep,
+        issuer_type_script_dep,
+        class_type_script_dep,
+    ];
+    // The version 1 issuer counts the destroyed classes, so it cannot be only a cell dep
+    if issuer_not_spent {
+        let issuer_cell_dep_out_point = context.create_cell(
+            CellOutput::new_builder()
+                .capacity(2000u64.pack())
+                .lock(lock_script.clone())
+                .type_(Some(issuer_type_script.clone()).pack())
+                .build(),
+            Bytes::from(hex::decode("01000000080000000000000000").unwrap()),
+        );
+        cell_deps.push(
+            CellDep::new_builder()
+                .out_point(issuer_cell_dep_out_point)
+                .build(),
+        );
+    }
+
     // build transaction
     let tx = TransactionBuilder::default()
         .inputs(inputs)
         .outputs(outputs)
         .outputs_data(outputs_data.pack())
-        .cell_dep(lock_script_dep)
-        .cell_dep(issuer_type_script_dep)
-        .cell_dep(class_type_script_dep)
+        .cell_deps(cell_deps)
         .witnesses(witnesses.pack())
         .build();
     (context, tx)
@@ -391,6 +502,19 @@ fn test_destroy_class_cell_success() {
     println!("consume cycles: {}", cycles);
 }
 
+#[test]
+fn test_update_class_cell_with_content_hash_success() {
+    let (mut context, tx) =
+        create_test_context(Action::Update(UpdateCase::ContentHash), ClassError::NoError);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
 #[test]
 fn test_update_class_data_len_error() {
     let (mut context, tx) = create_test_context(
@@ -539,6 +663,16 @@ fn test_class_cell_cannot_destroyed_error() {
     assert_script_error(err, CLASS_CELL_CANNOT_DESTROYED);
 }
 
+#[test]
+fn test_destroy_class_of_version_1_issuer_without_issuer_input_error() {
+    let (mut context, tx) = create_test_context(Action::Destroy, ClassError::IssuerNotSpent);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ISSUER_CELLS_COUNT_ERROR);
+}
+
 #[test]
 fn test_create_class_cells_increase_error() {
     let (mut context, tx) = create_test_context(Action::Create, ClassError::ClassIdIncreaseError);
@@ -572,3 +706,368 @@ fn test_destroy_class_with_witness_none_error() {
     let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
     assert_script_error(err, GROUP_INPUT_WITNESS_NONE_ERROR);
 }
+
+#[test]
+fn test_create_class_content_hash_invalid_error() {
+    let (mut context, tx) =
+        create_test_context(Action::Create, ClassError::ClassContentHashInvalid);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, CLASS_DATA_INVALID);
+}
+
+#[test]
+fn test_update_class_immutable_content_hash_not_same_error() {
+    let (mut context, tx) = create_test_context(
+        Action::Update(UpdateCase::ContentHash),
+        ClassError::ClassContentHashNotSame,
+    );
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, CLASS_IMMUTABLE_FIELDS_NOT_SAME);
+}
+
+#[test]
+fn test_update_class_ext_info_record_invalid_error() {
+    let (mut context, tx) = create_test_context(
+        Action::Update(UpdateCase::ContentHash),
+        ClassError::ClassExtInfoInvalid,
+    );
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, CLASS_DATA_INVALID);
+}
+
+#[test]
+fn test_update_class_with_lock_policy_and_witness_none_success() {
+    let (mut context, tx) =
+        create_test_context(Action::Update(UpdateCase::LockPolicy), ClassError::NoError);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_update_class_lock_policy_violated_error() {
+    let (mut context, tx) = create_test_context(
+        Action::Update(UpdateCase::LockPolicy),
+        ClassError::LockPolicyViolated,
+    );
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, LOCK_POLICY_VIOLATED);
+}
+
+#[test]
+fn test_update_class_with_declared_reveal_error() {
+    let (mut context, tx) = create_test_context(
+        Action::Update(UpdateCase::Default),
+        ClassError::ActionDeclarationInvalid,
+    );
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ACTION_DECLARATION_INVALID);
+}
+
+// The benchmark of the class-type script which queries the transaction index many times
+const BENCHMARK_CLASS_COUNT: u32 = 100;
+// The max cycles of a block
+const BENCHMARK_MAX_CYCLES: u64 = 3_500_000_000;
+
+fn create_batch_creation_context(count: u32) -> (Context, TransactionView) {
+    create_multi_issuer_creation_context(vec![(0, count, (0..count).collect())])
+}
+
+// The classes created under every issuer: (input class count, output class count, class ids)
+type IssuerClasses = (u32, u32, Vec<u32>);
+
+fn create_multi_issuer_creation_context(issuers: Vec<IssuerClasses>) -> (Context, TransactionView) {
+    let mut context = Context::default();
+
+    let class_bin: Bytes = Loader::default().load_binary("class-type");
+    let class_out_point = context.deploy_cell(class_bin);
+    let issuer_bin: Bytes = Loader::default().load_binary("issuer-type");
+    let issuer_out_point = context.deploy_cell(issuer_bin);
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+
+    let lock_script = context
+        .build_script(&always_success_out_point, Default::default())
+        .expect("script");
+
+    let mut inputs = vec![];
+    let mut outputs = vec![];
+    let mut outputs_data = vec![];
+    for (index, (input_class_count, output_class_count, class_ids)) in issuers.iter().enumerate() {
+        let issuer_type_script = context
+            .build_script(
+                &issuer_out_point,
+                Bytes::from(
+                    hex::decode(format!(
+                        "157a3633c3477d84b604a25e5fca5ca681762c{:02x}",
+                        0x10 + index
+                    ))
+                    .unwrap(),
+                ),
+            )
+            .expect("script");
+        let issuer_type_hash: [u8; 32] = issuer_type_script.calc_script_hash().unpack();
+
+        let issuer_input_out_point = context.create_cell(
+            CellOutput::new_builder()
+                .capacity(2000u64.pack())
+                .lock(lock_script.clone())
+                .type_(Some(issuer_type_script.clone()).pack())
+                .build(),
+            Bytes::from(hex::decode(format!("00{:08x}000000000000", input_class_count)).unwrap()),
+        );
+        inputs.push(
+            CellInput::new_builder()
+                .previous_output(issuer_input_out_point)
+                .build(),
+        );
+
+        outputs.push(
+            CellOutput::new_builder()
+                .capacity(2000u64.pack())
+                .lock(lock_script.clone())
+                .type_(Some(issuer_type_script).pack())
+                .build(),
+        );
+        outputs_data.push(Bytes::from(
+            hex::decode(format!("00{:08x}000000000000", output_class_count)).unwrap(),
+        ));
+        for class_id in class_ids {
+            let mut class_type_args = issuer_type_hash[0..20].to_vec();
+            class_type_args.extend_from_slice(&class_id.to_be_bytes());
+            let class_type_script = context
+                .build_script(&class_out_point, Bytes::from(class_type_args))
+                .expect("script");
+            outputs.push(
+                CellOutput::new_builder()
+                    .capacity(500u64.pack())
+                    .lock(lock_script.clone())
+                    .type_(Some(class_type_script).pack())
+                    .build(),
+            );
+            outputs_data.push(Bytes::from(
+                hex::decode("000000000f0000000000000155000266660003898989").unwrap(),
+            ));
+        }
+    }
+
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_dep(CellDep::new_builder().out_point(always_success_out_point).build())
+        .cell_dep(CellDep::new_builder().out_point(issuer_out_point).build())
+        .cell_dep(CellDep::new_builder().out_point(class_out_point).build())
+        .witness(Bytes::from(hex::decode("5500000010000000550000005500000041000000b69c542c0ee6c4b6d8350514d876ea7d8ef563e406253e959289457204447d2c4eb4e4a993073f5e76d244d2f93f7c108652e3295a9c8d72c12477e095026b9500").unwrap()).pack())
+        .build();
+    (context, tx)
+}
+
+#[test]
+fn test_batch_create_class_cells_cycles() {
+    let (mut context, tx) = create_batch_creation_context(BENCHMARK_CLASS_COUNT);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, BENCHMARK_MAX_CYCLES)
+        .expect("pass verification");
+    println!(
+        "batch create {} classes consume cycles: {}, {} per class",
+        BENCHMARK_CLASS_COUNT,
+        cycles,
+        cycles / BENCHMARK_CLASS_COUNT as u64
+    );
+}
+
+#[test]
+fn test_create_classes_of_multiple_issuers_success() {
+    let (mut context, tx) =
+        create_multi_issuer_creation_context(vec![(0, 2, vec![0, 1]), (3, 4, vec![3])]);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_create_classes_of_multiple_issuers_id_increase_error() {
+    let (mut context, tx) =
+        create_multi_issuer_creation_context(vec![(0, 2, vec![0, 1]), (3, 5, vec![3, 5])]);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, CLASS_ID_INCREASE_ERROR);
+}
+
+#[test]
+fn test_create_classes_of_multiple_issuers_count_error() {
+    let (mut context, tx) =
+        create_multi_issuer_creation_context(vec![(0, 2, vec![0, 1]), (3, 5, vec![3])]);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, CLASS_CELLS_COUNT_ERROR);
+}
+
+// The delegate creates the classes with its quota cell of the class ids from 5 to 10, and the
+// version 1 issuer cell with the delegates record is referenced by the cell deps
+fn create_delegate_creation_context(
+    delegated: bool,
+    next_class_id: u32,
+    class_ids: Vec<u32>,
+) -> (Context, TransactionView) {
+    let mut context = Context::default();
+
+    let class_bin: Bytes = Loader::default().load_binary("class-type");
+    let class_out_point = context.deploy_cell(class_bin);
+    let issuer_bin: Bytes = Loader::default().load_binary("issuer-type");
+    let issuer_out_point = context.deploy_cell(issuer_bin);
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+
+    let lock_script = context
+        .build_script(&always_success_out_point, Default::default())
+        .expect("script");
+    let issuer_type_script = context
+        .build_script(
+            &issuer_out_point,
+            Bytes::from(hex::decode("157a3633c3477d84b604a25e5fca5ca681762c10").unwrap()),
+        )
+        .expect("script");
+    let issuer_type_hash: [u8; 32] = issuer_type_script.calc_script_hash().unpack();
+
+    // delegates record: <lock_hash: [u8; 32]> * n
+    let delegate = if delegated {
+        hex::encode(lock_script.calc_script_hash().as_slice())
+    } else {
+        "ff".repeat(32)
+    };
+    let issuer_cell_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(2000u64.pack())
+            .lock(lock_script.clone())
+            .type_(Some(issuer_type_script).pack())
+            .build(),
+        Bytes::from(hex::decode(format!("010000000a0000000000000023050020{}", delegate)).unwrap()),
+    );
+
+    let mut quota_type_args = issuer_type_hash[0..20].to_vec();
+    quota_type_args.extend_from_slice(&5u32.to_be_bytes());
+    let quota_type_script = context
+        .build_script(&issuer_out_point, Bytes::from(quota_type_args))
+        .expect("script");
+    let quota_input_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(500u64.pack())
+            .lock(lock_script.clone())
+            .type_(Some(quota_type_script.clone()).pack())
+            .build(),
+        Bytes::from(hex::decode("00000000050000000a").unwrap()),
+    );
+    let quota_input = CellInput::new_builder()
+        .previous_output(quota_input_out_point)
+        .build();
+
+    let mut outputs = vec![CellOutput::new_builder()
+        .capacity(500u64.pack())
+        .lock(lock_script.clone())
+        .type_(Some(quota_type_script).pack())
+        .build()];
+    let mut outputs_data = vec![Bytes::from(
+        hex::decode(format!("00{:08x}0000000a", next_class_id)).unwrap(),
+    )];
+    for class_id in class_ids {
+        let mut class_type_args = issuer_type_hash[0..20].to_vec();
+        class_type_args.extend_from_slice(&class_id.to_be_bytes());
+        let class_type_script = context
+            .build_script(&class_out_point, Bytes::from(class_type_args))
+            .expect("script");
+        outputs.push(
+            CellOutput::new_builder()
+                .capacity(500u64.pack())
+                .lock(lock_script.clone())
+                .type_(Some(class_type_script).pack())
+                .build(),
+        );
+        outputs_data.push(Bytes::from(
+            hex::decode("000000000f0000000000000155000266660003898989").unwrap(),
+        ));
+    }
+
+    let tx = TransactionBuilder::default()
+        .input(quota_input)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_dep(CellDep::new_builder().out_point(always_success_out_point).build())
+        .cell_dep(CellDep::new_builder().out_point(issuer_out_point).build())
+        .cell_dep(CellDep::new_builder().out_point(class_out_point).build())
+        .cell_dep(CellDep::new_builder().out_point(issuer_cell_out_point).build())
+        .witness(Bytes::from(hex::decode("5500000010000000550000005500000041000000b69c542c0ee6c4b6d8350514d876ea7d8ef563e406253e959289457204447d2c4eb4e4a993073f5e76d244d2f93f7c108652e3295a9c8d72c12477e095026b9500").unwrap()).pack())
+        .build();
+    (context, tx)
+}
+
+#[test]
+fn test_create_classes_with_delegate_quota_success() {
+    let (mut context, tx) = create_delegate_creation_context(true, 7, vec![5, 6]);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_create_classes_with_delegate_quota_not_delegate_error() {
+    let (mut context, tx) = create_delegate_creation_context(false, 7, vec![5, 6]);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DELEGATE_NOT_ALLOWED);
+}
+
+#[test]
+fn test_create_classes_with_delegate_quota_id_increase_error() {
+    let (mut context, tx) = create_delegate_creation_context(true, 7, vec![5, 7]);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, CLASS_ID_INCREASE_ERROR);
+}
+
+#[test]
+fn test_create_classes_with_delegate_quota_exceeded_error() {
+    let (mut context, tx) = create_delegate_creation_context(true, 11, (5..11).collect());
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DELEGATE_QUOTA_INVALID);
+}
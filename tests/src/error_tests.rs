@@ -0,0 +1,48 @@
+use script_utils::error::Error;
+use script_utils::error_table::{describe, ERROR_TABLE};
+use std::collections::HashSet;
+
+// The codes from 120 to 127 are reserved for the syscall errors without a dedicated code
+const RESERVED_SYS_ERROR_CODES: std::ops::RangeInclusive<i8> = 120..=127;
+
+#[test]
+fn test_error_codes_are_unique() {
+    let mut codes = HashSet::new();
+    for info in ERROR_TABLE {
+        assert!(
+            codes.insert(info.code),
+            "duplicate error code {}",
+            info.code
+        );
+    }
+}
+
+#[test]
+fn test_error_codes_are_positive() {
+    for info in ERROR_TABLE {
+        assert!(info.code > 0, "error {} has no positive code", info.name);
+    }
+}
+
+#[test]
+fn test_reserved_error_codes() {
+    let reserved: Vec<_> = ERROR_TABLE
+        .iter()
+        .filter(|info| RESERVED_SYS_ERROR_CODES.contains(&info.code))
+        .map(|info| info.name)
+        .collect();
+    assert_eq!(reserved, vec!["UnknownSysError"]);
+}
+
+#[test]
+fn test_error_table_matches_variants() {
+    for info in ERROR_TABLE {
+        assert_eq!(describe(info.code), Some(info));
+    }
+    assert_eq!(Error::LockPolicyViolated.info().code, 51);
+    assert_eq!(
+        describe(Error::UnknownSysError as i8).unwrap().name,
+        "UnknownSysError"
+    );
+    assert_eq!(describe(0), None);
+}
@@ -1,4 +1,4 @@
-use crate::{assert_script_error, Loader};
+use crate::{assert_script_error, Loader, CLASS_TYPE_CODE_HASH};
 use blake2b_rs::Blake2bBuilder;
 use ckb_testtool::ckb_types::{
     bytes::Bytes,
@@ -9,6 +9,7 @@ use ckb_testtool::ckb_types::{
 use ckb_testtool::{builtin::ALWAYS_SUCCESS, context::Context};
 
 const MAX_CYCLES: u64 = 10_000_000;
+const TYPE: u8 = 1;
 
 // error numbers
 const ISSUER_DATA_INVALID: i8 = 5;
@@ -19,10 +20,22 @@ const ISSUER_SET_COUNT_ERROR: i8 = 9;
 const ISSUER_CELL_CANNOT_DESTROYED: i8 = 10;
 const VERSION_INVALID: i8 = 11;
 const GROUP_INPUT_WITNESS_NONE_ERROR: i8 = 40;
+const LOCK_POLICY_VIOLATED: i8 = 51;
+const ISSUER_IMMUTABLE_FIELDS_NOT_SAME: i8 = 52;
+const ACTION_NOT_MATCHED: i8 = 54;
+const DELEGATE_QUOTA_INVALID: i8 = 56;
+const DELEGATE_NOT_ALLOWED: i8 = 57;
+const ISSUER_TRANSFER_INVALID: i8 = 58;
+const SALE_PAYMENT_NOT_ENOUGH: i8 = 60;
+
+// declared actions
+const DESTROY_ACTION: u8 = 2;
+const MIGRATE_ACTION: u8 = 4;
 
 #[derive(PartialEq)]
 enum Action {
     Create,
+    BatchCreate(u8),
     Update(u8),
     Destroy,
 }
@@ -39,6 +52,15 @@ enum IssuerError {
     IssuerCellCannotDestroyed,
     GroupInputWitnessNoneError,
     BatchDestroyError,
+    LockPolicyAllowList,
+    LockPolicyViolated,
+    IssuerImmutableFieldsNotSame,
+    ActionNotMatched,
+    BatchCreateArgsCollision,
+    BatchCreateArgsMismatch,
+    DestroyedClassesCounted,
+    LiveClassesRemain,
+    DestroyedClassCountInvalid,
 }
 
 fn create_test_context(action: Action, issuer_error: IssuerError) -> (Context, TransactionView) {
@@ -70,13 +92,18 @@ fn create_test_context(action: Action, issuer_error: IssuerError) -> (Context, T
         .previous_output(normal_input_out_point.clone())
         .build();
 
-    let mut blake2b = Blake2bBuilder::new(32)
-        .personal(b"ckb-default-hash")
-        .build();
-    blake2b.update(normal_input.as_slice());
-    blake2b.update(&(0u64).to_le_bytes());
-    let mut ret = [0; 32];
-    blake2b.finalize(&mut ret);
+    // The issuer type args are derived from the first input and the index of the issuer output
+    let type_id_args = |output_index: u64| {
+        let mut blake2b = Blake2bBuilder::new(32)
+            .personal(b"ckb-default-hash")
+            .build();
+        blake2b.update(normal_input.as_slice());
+        blake2b.update(&output_index.to_le_bytes());
+        let mut ret = [0; 32];
+        blake2b.finalize(&mut ret);
+        ret
+    };
+    let ret = type_id_args(0);
     let issuer_type_args = match issuer_error {
         IssuerError::TypeArgsInvalid => Bytes::copy_from_slice(&ret[0..10]),
         _ => Bytes::copy_from_slice(&ret[0..20]),
@@ -89,10 +116,45 @@ fn create_test_context(action: Action, issuer_error: IssuerError) -> (Context, T
         .out_point(issuer_out_point.clone())
         .build();
 
+    // version 1 issuer data with the lock policy record: <mode: u8> + <code_hash: [u8; 32]>
+    let lock_code_hash = hex::encode(lock_script.code_hash().as_slice());
+    let allow_list_issuer_data = Bytes::from(
+        hex::decode(format!(
+            "0100000000000000000000002404002100{}",
+            lock_code_hash
+        ))
+        .unwrap(),
+    );
+    let deny_list_issuer_data = Bytes::from(
+        hex::decode(format!(
+            "0100000000000000000000002404002101{}",
+            lock_code_hash
+        ))
+        .unwrap(),
+    );
+
+    // version 1 issuer data of 2 classes with the destroyed class count record: u32
+    let destroyed_count_issuer_data = |destroyed_class_count: u32| {
+        Bytes::from(
+            hex::decode(format!(
+                "01000000020000000000000007070004{:08x}",
+                destroyed_class_count
+            ))
+            .unwrap(),
+        )
+    };
+
     let issuer_input_data = match issuer_error {
         IssuerError::IssuerCellCannotDestroyed => {
             Bytes::from(hex::decode("0000000000000000080000").unwrap())
         }
+        IssuerError::LockPolicyAllowList | IssuerError::IssuerImmutableFieldsNotSame => {
+            allow_list_issuer_data.clone()
+        }
+        IssuerError::LockPolicyViolated => deny_list_issuer_data.clone(),
+        IssuerError::DestroyedClassesCounted => destroyed_count_issuer_data(2),
+        IssuerError::LiveClassesRemain => destroyed_count_issuer_data(1),
+        IssuerError::DestroyedClassCountInvalid => destroyed_count_issuer_data(0),
         _ => Bytes::from(hex::decode("0000000000000000000000").unwrap()),
     };
 
@@ -130,7 +192,7 @@ fn create_test_context(action: Action, issuer_error: IssuerError) -> (Context, T
                 );
             }
         }
-        Action::Create => (),
+        Action::Create | Action::BatchCreate(_) => (),
     }
 
     let mut outputs = match action {
@@ -139,6 +201,26 @@ fn create_test_context(action: Action, issuer_error: IssuerError) -> (Context, T
             .lock(lock_script.clone())
             .type_(Some(issuer_type_script.clone()).pack())
             .build()],
+        Action::BatchCreate(count) => (0..count as u64)
+            .map(|index| {
+                let args_index = match issuer_error {
+                    IssuerError::BatchCreateArgsCollision => 0,
+                    IssuerError::BatchCreateArgsMismatch => count as u64 - 1 - index,
+                    _ => index,
+                };
+                let type_script = context
+                    .build_script(
+                        &issuer_out_point,
+                        Bytes::copy_from_slice(&type_id_args(args_index)[0..20]),
+                    )
+                    .expect("script");
+                CellOutput::new_builder()
+                    .capacity(500u64.pack())
+                    .lock(lock_script.clone())
+                    .type_(Some(type_script).pack())
+                    .build()
+            })
+            .collect(),
         Action::Update(_) => vec![CellOutput::new_builder()
             .capacity(500u64.pack())
             .lock(lock_script.clone())
@@ -178,15 +260,20 @@ fn create_test_context(action: Action, issuer_error: IssuerError) -> (Context, T
                 Bytes::from(hex::decode("0000000000000000080000").unwrap())
             }
             IssuerError::VersionInvalid => {
-                Bytes::from(hex::decode("0100000000000000000000").unwrap())
+                Bytes::from(hex::decode("0200000000000000000000").unwrap())
             }
+            IssuerError::LockPolicyAllowList => allow_list_issuer_data.clone(),
+            IssuerError::LockPolicyViolated => deny_list_issuer_data.clone(),
+            IssuerError::IssuerImmutableFieldsNotSame => deny_list_issuer_data.clone(),
+            IssuerError::DestroyedClassCountInvalid => destroyed_count_issuer_data(1),
             _ => Bytes::from(hex::decode("0000000000000000000000").unwrap()),
         })
         .collect();
 
     let mut witnesses = vec![];
     match issuer_error {
-        IssuerError::GroupInputWitnessNoneError => {
+        // The lock policy replaces the anyone-can-pay check with the witness
+        IssuerError::GroupInputWitnessNoneError | IssuerError::LockPolicyAllowList => {
             witnesses.push(Bytes::from("0x"))
         }
         _ => {
@@ -194,7 +281,16 @@ fn create_test_context(action: Action, issuer_error: IssuerError) -> (Context, T
         }
     }
     for _ in 1..inputs.len() {
-        witnesses.push(Bytes::from("0x"))
+        match issuer_error {
+            // The declaration in the input_type: <action: u8> + <params>
+            IssuerError::ActionNotMatched => witnesses.push(
+                WitnessArgs::new_builder()
+                    .input_type(Some(Bytes::from(vec![DESTROY_ACTION])).pack())
+                    .build()
+                    .as_bytes(),
+            ),
+            _ => witnesses.push(Bytes::from("0x")),
+        }
     }
 
     // build transaction
@@ -348,6 +444,40 @@ fn test_destroy_issuer_with_witness_none_error() {
     assert_script_error(err, GROUP_INPUT_WITNESS_NONE_ERROR);
 }
 
+#[test]
+fn test_destroy_issuer_with_destroyed_classes_success() {
+    let (mut context, tx) =
+        create_test_context(Action::Destroy, IssuerError::DestroyedClassesCounted);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_destroy_issuer_with_live_classes_error() {
+    let (mut context, tx) = create_test_context(Action::Destroy, IssuerError::LiveClassesRemain);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ISSUER_CELL_CANNOT_DESTROYED);
+}
+
+#[test]
+fn test_update_issuer_destroyed_class_count_error() {
+    let (mut context, tx) =
+        create_test_context(Action::Update(1), IssuerError::DestroyedClassCountInvalid);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ISSUER_CLASS_COUNT_ERROR);
+}
+
 #[test]
 fn test_batch_destroy_issuer_error() {
     let (mut context, tx) = create_test_context(Action::Destroy, IssuerError::BatchDestroyError);
@@ -357,3 +487,911 @@ fn test_batch_destroy_issuer_error() {
     let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
     assert_script_error(err, ISSUER_CELLS_COUNT_ERROR);
 }
+
+#[test]
+fn test_update_issuer_with_lock_policy_and_witness_none_success() {
+    let (mut context, tx) =
+        create_test_context(Action::Update(1), IssuerError::LockPolicyAllowList);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_update_issuer_lock_policy_violated_error() {
+    let (mut context, tx) = create_test_context(Action::Update(1), IssuerError::LockPolicyViolated);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, LOCK_POLICY_VIOLATED);
+}
+
+#[test]
+fn test_update_issuer_lock_policy_not_same_error() {
+    let (mut context, tx) =
+        create_test_context(Action::Update(1), IssuerError::IssuerImmutableFieldsNotSame);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ISSUER_IMMUTABLE_FIELDS_NOT_SAME);
+}
+
+#[test]
+fn test_create_issuer_lock_policy_violated_error() {
+    let (mut context, tx) = create_test_context(Action::Create, IssuerError::LockPolicyViolated);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, LOCK_POLICY_VIOLATED);
+}
+
+#[test]
+fn test_update_issuer_with_declared_destroy_error() {
+    let (mut context, tx) = create_test_context(Action::Update(1), IssuerError::ActionNotMatched);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ACTION_NOT_MATCHED);
+}
+
+#[test]
+fn test_batch_create_issuers_success() {
+    let (mut context, tx) = create_test_context(Action::BatchCreate(3), IssuerError::NoError);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_batch_create_issuers_args_collision_error() {
+    let (mut context, tx) = create_test_context(
+        Action::BatchCreate(2),
+        IssuerError::BatchCreateArgsCollision,
+    );
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ISSUER_CELLS_COUNT_ERROR);
+}
+
+#[test]
+fn test_batch_create_issuers_args_mismatch_error() {
+    let (mut context, tx) =
+        create_test_context(Action::BatchCreate(2), IssuerError::BatchCreateArgsMismatch);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, TYPE_ARGS_INVALID);
+}
+
+#[derive(PartialEq, Copy, Clone)]
+enum QuotaCase {
+    Delegated,
+    NotDelegated,
+    RangeMismatch,
+    ClassesCreated,
+}
+
+// The issuer owner advances the class count from 5 to 10 and carves the class ids out into the
+// quota cell held by the delegate
+fn create_quota_creation_context(case: QuotaCase) -> (Context, TransactionView) {
+    let mut context = Context::default();
+    let issuer_bin: Bytes = Loader::default().load_binary("issuer-type");
+    let issuer_out_point = context.deploy_cell(issuer_bin);
+    let class_bin: Bytes = Loader::default().load_binary("class-type");
+    let class_out_point = context.deploy_cell(class_bin);
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+
+    let lock_script = context
+        .build_script(&always_success_out_point, Default::default())
+        .expect("script");
+    let issuer_type_script = context
+        .build_script(
+            &issuer_out_point,
+            Bytes::from(hex::decode("157a3633c3477d84b604a25e5fca5ca681762c10").unwrap()),
+        )
+        .expect("script");
+    let issuer_type_hash: [u8; 32] = issuer_type_script.calc_script_hash().unpack();
+
+    // delegates record: <lock_hash: [u8; 32]> * n
+    let delegate = if case == QuotaCase::NotDelegated {
+        "ff".repeat(32)
+    } else {
+        hex::encode(lock_script.calc_script_hash().as_slice())
+    };
+    let issuer_data = |class_count: u32| {
+        Bytes::from(
+            hex::decode(format!(
+                "01{:08x}0000000000000023050020{}",
+                class_count, delegate
+            ))
+            .unwrap(),
+        )
+    };
+    let issuer_input_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(500u64.pack())
+            .lock(lock_script.clone())
+            .type_(Some(issuer_type_script.clone()).pack())
+            .build(),
+        issuer_data(5),
+    );
+    let issuer_input = CellInput::new_builder()
+        .previous_output(issuer_input_out_point)
+        .build();
+
+    let mut quota_type_args = issuer_type_hash[0..20].to_vec();
+    quota_type_args.extend_from_slice(&5u32.to_be_bytes());
+    let quota_type_script = context
+        .build_script(&issuer_out_point, Bytes::from(quota_type_args))
+        .expect("script");
+
+    let mut outputs = vec![
+        CellOutput::new_builder()
+            .capacity(500u64.pack())
+            .lock(lock_script.clone())
+            .type_(Some(issuer_type_script).pack())
+            .build(),
+        CellOutput::new_builder()
+            .capacity(500u64.pack())
+            .lock(lock_script.clone())
+            .type_(Some(quota_type_script).pack())
+            .build(),
+    ];
+    let end_class_id = if case == QuotaCase::RangeMismatch {
+        9
+    } else {
+        10
+    };
+    // delegate quota data: <version: u8> + <next_class_id: u32> + <end_class_id: u32>
+    let mut outputs_data = vec![
+        issuer_data(10),
+        Bytes::from(hex::decode(format!("0000000005{:08x}", end_class_id)).unwrap()),
+    ];
+
+    // The issuer owner creates the classes with the class ids reserved for the quota
+    if case == QuotaCase::ClassesCreated {
+        for class_id in 5u32..10 {
+            let mut class_type_args = issuer_type_hash[0..20].to_vec();
+            class_type_args.extend_from_slice(&class_id.to_be_bytes());
+            let class_type_script = Script::new_builder()
+                .code_hash(CLASS_TYPE_CODE_HASH.pack())
+                .args(Bytes::from(class_type_args).pack())
+                .hash_type(Byte::new(TYPE))
+                .build();
+            outputs.push(
+                CellOutput::new_builder()
+                    .capacity(500u64.pack())
+                    .lock(lock_script.clone())
+                    .type_(Some(class_type_script).pack())
+                    .build(),
+            );
+            outputs_data.push(Bytes::from(
+                hex::decode("000000000f0000000000000155000266660003898989").unwrap(),
+            ));
+        }
+    }
+
+    let tx = TransactionBuilder::default()
+        .input(issuer_input)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_dep(CellDep::new_builder().out_point(always_success_out_point).build())
+        .cell_dep(CellDep::new_builder().out_point(issuer_out_point).build())
+        .cell_dep(CellDep::new_builder().out_point(class_out_point).build())
+        .witness(Bytes::from(hex::decode("5500000010000000550000005500000041000000b69c542c0ee6c4b6d8350514d876ea7d8ef563e406253e959289457204447d2c4eb4e4a993073f5e76d244d2f93f7c108652e3295a9c8d72c12477e095026b9500").unwrap()).pack())
+        .build();
+    (context, tx)
+}
+
+#[test]
+fn test_create_delegate_quota_success() {
+    let (mut context, tx) = create_quota_creation_context(QuotaCase::Delegated);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_create_delegate_quota_not_delegate_error() {
+    let (mut context, tx) = create_quota_creation_context(QuotaCase::NotDelegated);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DELEGATE_NOT_ALLOWED);
+}
+
+#[test]
+fn test_create_delegate_quota_range_error() {
+    let (mut context, tx) = create_quota_creation_context(QuotaCase::RangeMismatch);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DELEGATE_QUOTA_INVALID);
+}
+
+// The class outputs run the class type pinned to its deployed type id, see CLASS_TYPE_CODE_HASH
+#[test]
+#[ignore]
+fn test_create_delegate_quota_with_issuer_classes_error() {
+    let (mut context, tx) = create_quota_creation_context(QuotaCase::ClassesCreated);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, DELEGATE_QUOTA_INVALID);
+}
+
+#[derive(PartialEq, Copy, Clone)]
+enum CountCase {
+    Migrate,
+    MigrateWithoutDeclaration,
+    MigrateOverCounted,
+    ReleaseQuota,
+    ReleaseQuotaMiscounted,
+    DestroyWithQuota,
+    DestroyQuotaWithoutIssuer,
+}
+
+// The issuer reserved the class ids 5..10 for the quota cell, whose delegate has created the
+// classes 5 and 6, and the classes 0..5 are destroyed before
+fn create_destroyed_count_context(case: CountCase) -> (Context, TransactionView) {
+    let mut context = Context::default();
+    let issuer_bin: Bytes = Loader::default().load_binary("issuer-type");
+    let issuer_out_point = context.deploy_cell(issuer_bin);
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+
+    let lock_script = context
+        .build_script(&always_success_out_point, Default::default())
+        .expect("script");
+    let issuer_type_script = context
+        .build_script(
+            &issuer_out_point,
+            Bytes::from(hex::decode("157a3633c3477d84b604a25e5fca5ca681762c10").unwrap()),
+        )
+        .expect("script");
+    let issuer_type_hash: [u8; 32] = issuer_type_script.calc_script_hash().unpack();
+    let mut quota_type_args = issuer_type_hash[0..20].to_vec();
+    quota_type_args.extend_from_slice(&5u32.to_be_bytes());
+    let quota_type_script = context
+        .build_script(&issuer_out_point, Bytes::from(quota_type_args))
+        .expect("script");
+
+    // version 1 issuer data with the destroyed class count record: u32
+    let issuer_v1_data = |class_count: u32, destroyed_class_count: u32| {
+        Bytes::from(
+            hex::decode(format!(
+                "01{:08x}0000000000000007070004{:08x}",
+                class_count, destroyed_class_count
+            ))
+            .unwrap(),
+        )
+    };
+    let issuer_cell = CellOutput::new_builder()
+        .capacity(500u64.pack())
+        .lock(lock_script.clone())
+        .type_(Some(issuer_type_script).pack())
+        .build();
+    let quota_cell = CellOutput::new_builder()
+        .capacity(500u64.pack())
+        .lock(lock_script.clone())
+        .type_(Some(quota_type_script).pack())
+        .build();
+    // delegate quota data: <version: u8> + <next_class_id: u32> + <end_class_id: u32>
+    let quota_data = Bytes::from(hex::decode("00000000070000000a").unwrap());
+
+    let (input_cells, outputs, outputs_data) = match case {
+        CountCase::Migrate | CountCase::MigrateWithoutDeclaration => (
+            vec![(
+                issuer_cell.clone(),
+                Bytes::from(hex::decode("000000000a000000000000").unwrap()),
+            )],
+            vec![issuer_cell],
+            vec![issuer_v1_data(10, 5)],
+        ),
+        CountCase::MigrateOverCounted => (
+            vec![(
+                issuer_cell.clone(),
+                Bytes::from(hex::decode("000000000a000000000000").unwrap()),
+            )],
+            vec![issuer_cell],
+            vec![issuer_v1_data(10, 11)],
+        ),
+        // The class ids 7..10 left in the destroyed quota are released
+        CountCase::ReleaseQuota => (
+            vec![
+                (issuer_cell.clone(), issuer_v1_data(10, 5)),
+                (quota_cell, quota_data),
+            ],
+            vec![issuer_cell],
+            vec![issuer_v1_data(10, 8)],
+        ),
+        CountCase::ReleaseQuotaMiscounted => (
+            vec![
+                (issuer_cell.clone(), issuer_v1_data(10, 5)),
+                (quota_cell, quota_data),
+            ],
+            vec![issuer_cell],
+            vec![issuer_v1_data(10, 7)],
+        ),
+        // The classes 5 and 6 created with the quota are destroyed before
+        CountCase::DestroyWithQuota => (
+            vec![
+                (issuer_cell, issuer_v1_data(10, 7)),
+                (quota_cell, quota_data),
+            ],
+            vec![],
+            vec![],
+        ),
+        CountCase::DestroyQuotaWithoutIssuer => (vec![(quota_cell, quota_data)], vec![], vec![]),
+    };
+    let inputs: Vec<CellInput> = input_cells
+        .into_iter()
+        .map(|(output, data)| {
+            let out_point = context.create_cell(output, data);
+            CellInput::new_builder().previous_output(out_point).build()
+        })
+        .collect();
+
+    // The declaration in the input_type: <action: u8> + <params>
+    let declaration = match case {
+        CountCase::Migrate | CountCase::MigrateOverCounted => {
+            Some(Bytes::from(vec![MIGRATE_ACTION]))
+        }
+        _ => None,
+    };
+    let witnesses: Vec<Bytes> = inputs
+        .iter()
+        .enumerate()
+        .map(|(index, _)| {
+            let input_type = if index == 0 {
+                declaration.clone()
+            } else {
+                None
+            };
+            WitnessArgs::new_builder()
+                .lock(Some(Bytes::from(vec![0u8; 65])).pack())
+                .input_type(input_type.pack())
+                .build()
+                .as_bytes()
+        })
+        .collect();
+
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(always_success_out_point)
+                .build(),
+        )
+        .cell_dep(CellDep::new_builder().out_point(issuer_out_point).build())
+        .witnesses(witnesses.pack())
+        .build();
+    (context, tx)
+}
+
+#[test]
+fn test_migrate_issuer_with_destroyed_class_count_success() {
+    let (mut context, tx) = create_destroyed_count_context(CountCase::Migrate);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_migrate_issuer_without_declaration_error() {
+    let (mut context, tx) = create_destroyed_count_context(CountCase::MigrateWithoutDeclaration);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ISSUER_IMMUTABLE_FIELDS_NOT_SAME);
+}
+
+#[test]
+fn test_migrate_issuer_with_destroyed_class_count_over_error() {
+    let (mut context, tx) = create_destroyed_count_context(CountCase::MigrateOverCounted);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ISSUER_CLASS_COUNT_ERROR);
+}
+
+#[test]
+fn test_release_delegate_quota_class_ids_success() {
+    let (mut context, tx) = create_destroyed_count_context(CountCase::ReleaseQuota);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_release_delegate_quota_class_ids_miscounted_error() {
+    let (mut context, tx) = create_destroyed_count_context(CountCase::ReleaseQuotaMiscounted);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ISSUER_CLASS_COUNT_ERROR);
+}
+
+#[test]
+fn test_destroy_issuer_with_delegate_quota_success() {
+    let (mut context, tx) = create_destroyed_count_context(CountCase::DestroyWithQuota);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_destroy_delegate_quota_without_issuer_error() {
+    let (mut context, tx) = create_destroyed_count_context(CountCase::DestroyQuotaWithoutIssuer);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ISSUER_CELLS_COUNT_ERROR);
+}
+
+const TRANSFER_EXPIRY: u64 = 100;
+
+#[derive(PartialEq)]
+enum TransferCase {
+    Start,
+    Complete,
+    CompleteWithoutPendingOwner,
+    MoveWhilePending,
+    Decline,
+    CancelAfterExpiry,
+    CancelBeforeExpiry,
+    CompleteWithOwnerKey,
+    CompleteWithoutOwnerKey,
+}
+
+fn is_owner_key_locked(case: &TransferCase) -> bool {
+    *case == TransferCase::CompleteWithOwnerKey || *case == TransferCase::CompleteWithoutOwnerKey
+}
+
+// The issuer cell is transferred from the owner lock to the pending owner lock, and the pending
+// owner record is <lock_hash: [u8; 32]> + <expiry: u64>
+fn create_transfer_context(case: TransferCase) -> (Context, TransactionView) {
+    let mut context = Context::default();
+    let issuer_bin: Bytes = Loader::default().load_binary("issuer-type");
+    let issuer_out_point = context.deploy_cell(issuer_bin);
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+
+    let owner_key_lock = context
+        .build_script(&always_success_out_point, Bytes::from(vec![2]))
+        .expect("script");
+    let pending_owner_lock = context
+        .build_script(&always_success_out_point, Bytes::from(vec![1]))
+        .expect("script");
+    let issuer_type_script = context
+        .build_script(
+            &issuer_out_point,
+            Bytes::from(hex::decode("157a3633c3477d84b604a25e5fca5ca681762c10").unwrap()),
+        )
+        .expect("script");
+
+    // The non-trivial owner lock is unlocked only along with an input of the owner key lock, which
+    // stands for the signature of the owner: the sale lock with an unpayable price
+    let (owner_lock, lock_out_point) = if is_owner_key_locked(&case) {
+        let sale_bin: Bytes = Loader::default().load_binary("nft-sale-lock");
+        let sale_out_point = context.deploy_cell(sale_bin);
+        let mut sale_args = owner_key_lock.calc_script_hash().as_slice().to_vec();
+        sale_args.extend_from_slice(&u128::max_value().to_be_bytes());
+        sale_args.extend_from_slice(&[0u8; 33]);
+        let sale_lock = context
+            .build_script(&sale_out_point, Bytes::from(sale_args))
+            .expect("script");
+        (sale_lock, Some(sale_out_point))
+    } else {
+        let owner_lock = context
+            .build_script(&always_success_out_point, Default::default())
+            .expect("script");
+        (owner_lock, None)
+    };
+
+    let issuer_data = Bytes::from(hex::decode("01000000000000000000000000").unwrap());
+    let pending_issuer_data = Bytes::from(
+        hex::decode(format!(
+            "0100000000000000000000002b060028{}{:016x}",
+            hex::encode(pending_owner_lock.calc_script_hash().as_slice()),
+            TRANSFER_EXPIRY
+        ))
+        .unwrap(),
+    );
+
+    let input_data = match case {
+        TransferCase::Start => issuer_data.clone(),
+        _ => pending_issuer_data.clone(),
+    };
+    let output_data = match case {
+        TransferCase::Start | TransferCase::MoveWhilePending => pending_issuer_data.clone(),
+        _ => issuer_data.clone(),
+    };
+    let output_lock = match case {
+        TransferCase::Complete
+        | TransferCase::CompleteWithoutPendingOwner
+        | TransferCase::MoveWhilePending
+        | TransferCase::CompleteWithOwnerKey
+        | TransferCase::CompleteWithoutOwnerKey => pending_owner_lock.clone(),
+        _ => owner_lock.clone(),
+    };
+    // the absolute block number since of the issuer input
+    let since = match case {
+        TransferCase::CancelAfterExpiry => TRANSFER_EXPIRY,
+        _ => 0,
+    };
+
+    let issuer_input_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(500u64.pack())
+            .lock(owner_lock.clone())
+            .type_(Some(issuer_type_script.clone()).pack())
+            .build(),
+        input_data,
+    );
+    let mut inputs = vec![CellInput::new_builder()
+        .previous_output(issuer_input_out_point)
+        .since(since.pack())
+        .build()];
+    let mut witnesses = vec![Bytes::from(hex::decode("5500000010000000550000005500000041000000b69c542c0ee6c4b6d8350514d876ea7d8ef563e406253e959289457204447d2c4eb4e4a993073f5e76d244d2f93f7c108652e3295a9c8d72c12477e095026b9500").unwrap())];
+    match case {
+        TransferCase::Complete
+        | TransferCase::MoveWhilePending
+        | TransferCase::Decline
+        | TransferCase::CompleteWithOwnerKey
+        | TransferCase::CompleteWithoutOwnerKey => {
+            let pending_owner_input_out_point = context.create_cell(
+                CellOutput::new_builder()
+                    .capacity(500u64.pack())
+                    .lock(pending_owner_lock.clone())
+                    .build(),
+                Bytes::new(),
+            );
+            inputs.push(
+                CellInput::new_builder()
+                    .previous_output(pending_owner_input_out_point)
+                    .build(),
+            );
+            witnesses.push(Bytes::new());
+        }
+        _ => (),
+    }
+    // The owner signs the completion along with the pending owner
+    if case == TransferCase::CompleteWithOwnerKey {
+        let owner_key_input_out_point = context.create_cell(
+            CellOutput::new_builder()
+                .capacity(500u64.pack())
+                .lock(owner_key_lock.clone())
+                .build(),
+            Bytes::new(),
+        );
+        inputs.push(
+            CellInput::new_builder()
+                .previous_output(owner_key_input_out_point)
+                .build(),
+        );
+        witnesses.push(Bytes::new());
+    }
+
+    let mut cell_deps = vec![
+        CellDep::new_builder()
+            .out_point(always_success_out_point)
+            .build(),
+        CellDep::new_builder().out_point(issuer_out_point).build(),
+    ];
+    if let Some(lock_out_point) = lock_out_point {
+        cell_deps.push(CellDep::new_builder().out_point(lock_out_point).build());
+    }
+
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .output(
+            CellOutput::new_builder()
+                .capacity(500u64.pack())
+                .lock(output_lock)
+                .type_(Some(issuer_type_script).pack())
+                .build(),
+        )
+        .output_data(output_data.pack())
+        .cell_deps(cell_deps)
+        .witnesses(witnesses.pack())
+        .build();
+    (context, tx)
+}
+
+#[test]
+fn test_start_issuer_transfer_success() {
+    let (mut context, tx) = create_transfer_context(TransferCase::Start);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_complete_issuer_transfer_success() {
+    let (mut context, tx) = create_transfer_context(TransferCase::Complete);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_decline_issuer_transfer_success() {
+    let (mut context, tx) = create_transfer_context(TransferCase::Decline);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_cancel_issuer_transfer_after_expiry_success() {
+    let (mut context, tx) = create_transfer_context(TransferCase::CancelAfterExpiry);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_complete_issuer_transfer_without_pending_owner_error() {
+    let (mut context, tx) = create_transfer_context(TransferCase::CompleteWithoutPendingOwner);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ISSUER_TRANSFER_INVALID);
+}
+
+#[test]
+fn test_move_issuer_while_transfer_pending_error() {
+    let (mut context, tx) = create_transfer_context(TransferCase::MoveWhilePending);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ISSUER_TRANSFER_INVALID);
+}
+
+#[test]
+fn test_cancel_issuer_transfer_before_expiry_error() {
+    let (mut context, tx) = create_transfer_context(TransferCase::CancelBeforeExpiry);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ISSUER_TRANSFER_INVALID);
+}
+
+#[test]
+fn test_complete_issuer_transfer_with_owner_key_success() {
+    let (mut context, tx) = create_transfer_context(TransferCase::CompleteWithOwnerKey);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+// The pending owner cannot complete the transfer alone, because the owner lock of the issuer
+// input must be unlocked as well
+#[test]
+fn test_complete_issuer_transfer_without_owner_key_error() {
+    let (mut context, tx) = create_transfer_context(TransferCase::CompleteWithoutOwnerKey);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, SALE_PAYMENT_NOT_ENOUGH);
+}
+
+// The issuer lifecycle: the issuer is created, creates two classes, counts the classes destroyed
+// along with its update, and is destroyed at last. The classes are created and destroyed under
+// the pinned class type, which only runs on a chain with the deployed class type.
+#[test]
+#[ignore]
+fn test_issuer_lifecycle_with_destroyed_classes_success() {
+    let mut context = Context::default();
+    let issuer_bin: Bytes = Loader::default().load_binary("issuer-type");
+    let issuer_out_point = context.deploy_cell(issuer_bin);
+    let class_bin: Bytes = Loader::default().load_binary("class-type");
+    let class_out_point = context.deploy_cell(class_bin);
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+
+    let lock_script = context
+        .build_script(&always_success_out_point, Default::default())
+        .expect("script");
+    let cell_deps = vec![
+        CellDep::new_builder()
+            .out_point(always_success_out_point)
+            .build(),
+        CellDep::new_builder()
+            .out_point(issuer_out_point.clone())
+            .build(),
+        CellDep::new_builder().out_point(class_out_point).build(),
+    ];
+    let witness = Bytes::from(hex::decode("5500000010000000550000005500000041000000b69c542c0ee6c4b6d8350514d876ea7d8ef563e406253e959289457204447d2c4eb4e4a993073f5e76d244d2f93f7c108652e3295a9c8d72c12477e095026b9500").unwrap());
+
+    // version 1 issuer data with the destroyed class count record: u32
+    let issuer_data = |class_count: u32, destroyed_class_count: Option<u32>| {
+        let extension = match destroyed_class_count {
+            Some(count) => format!("0007070004{:08x}", count),
+            None => "0000".to_string(),
+        };
+        Bytes::from(hex::decode(format!("01{:08x}000000000000{}", class_count, extension)).unwrap())
+    };
+    let class_data =
+        Bytes::from(hex::decode("000000000f0000000000000155000266660003898989").unwrap());
+
+    // create the issuer
+    let normal_input = CellInput::new_builder()
+        .previous_output(
+            context.create_cell(
+                CellOutput::new_builder()
+                    .capacity(500u64.pack())
+                    .lock(lock_script.clone())
+                    .build(),
+                Bytes::new(),
+            ),
+        )
+        .build();
+    let mut blake2b = Blake2bBuilder::new(32)
+        .personal(b"ckb-default-hash")
+        .build();
+    blake2b.update(normal_input.as_slice());
+    blake2b.update(&0u64.to_le_bytes());
+    let mut issuer_type_args = [0; 32];
+    blake2b.finalize(&mut issuer_type_args);
+    let issuer_type_script = context
+        .build_script(
+            &issuer_out_point,
+            Bytes::copy_from_slice(&issuer_type_args[0..20]),
+        )
+        .expect("script");
+    let issuer_output = CellOutput::new_builder()
+        .capacity(500u64.pack())
+        .lock(lock_script.clone())
+        .type_(Some(issuer_type_script.clone()).pack())
+        .build();
+
+    let tx = TransactionBuilder::default()
+        .input(normal_input)
+        .output(issuer_output.clone())
+        .output_data(issuer_data(0, None).pack())
+        .cell_deps(cell_deps.clone())
+        .witness(witness.pack())
+        .build();
+    context.verify_tx(&tx, MAX_CYCLES).expect("create issuer");
+
+    // create the classes 0 and 1 of the issuer
+    let issuer_type_hash: [u8; 32] = issuer_type_script.calc_script_hash().unpack();
+    let class_outputs: Vec<CellOutput> = (0u32..2)
+        .map(|class_id| {
+            let mut class_type_args = issuer_type_hash[0..20].to_vec();
+            class_type_args.extend_from_slice(&class_id.to_be_bytes());
+            CellOutput::new_builder()
+                .capacity(500u64.pack())
+                .lock(lock_script.clone())
+                .type_(
+                    Some(
+                        Script::new_builder()
+                            .code_hash(CLASS_TYPE_CODE_HASH.pack())
+                            .args(Bytes::from(class_type_args).pack())
+                            .hash_type(Byte::new(TYPE))
+                            .build(),
+                    )
+                    .pack(),
+                )
+                .build()
+        })
+        .collect();
+
+    let issuer_input = CellInput::new_builder()
+        .previous_output(context.create_cell(issuer_output.clone(), issuer_data(0, None)))
+        .build();
+    let tx = TransactionBuilder::default()
+        .input(issuer_input)
+        .output(issuer_output.clone())
+        .output_data(issuer_data(2, None).pack())
+        .outputs(class_outputs.clone())
+        .outputs_data(vec![class_data.clone(), class_data.clone()].pack())
+        .cell_deps(cell_deps.clone())
+        .witness(witness.pack())
+        .build();
+    context.verify_tx(&tx, MAX_CYCLES).expect("create classes");
+
+    // destroy the classes along with the issuer update
+    let mut inputs = vec![CellInput::new_builder()
+        .previous_output(context.create_cell(issuer_output.clone(), issuer_data(2, None)))
+        .build()];
+    for class_output in class_outputs {
+        inputs.push(
+            CellInput::new_builder()
+                .previous_output(context.create_cell(class_output, class_data.clone()))
+                .build(),
+        );
+    }
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .output(issuer_output.clone())
+        .output_data(issuer_data(2, Some(2)).pack())
+        .cell_deps(cell_deps.clone())
+        .witnesses(vec![witness.clone(), Bytes::new(), Bytes::new()].pack())
+        .build();
+    context.verify_tx(&tx, MAX_CYCLES).expect("destroy classes");
+
+    // destroy the issuer without any live class
+    let issuer_input = CellInput::new_builder()
+        .previous_output(context.create_cell(issuer_output, issuer_data(2, Some(2))))
+        .build();
+    let tx = TransactionBuilder::default()
+        .input(issuer_input)
+        .output(
+            CellOutput::new_builder()
+                .capacity(500u64.pack())
+                .lock(lock_script)
+                .build(),
+        )
+        .output_data(Bytes::new().pack())
+        .cell_deps(cell_deps)
+        .witness(witness.pack())
+        .build();
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
@@ -14,6 +14,30 @@ mod class_tests;
 #[cfg(test)]
 mod nft_tests;
 
+#[cfg(test)]
+mod error_tests;
+
+#[cfg(test)]
+mod media_tests;
+
+#[cfg(test)]
+mod nft_sale_lock_tests;
+
+#[cfg(test)]
+mod nft_auction_lock_tests;
+
+#[cfg(test)]
+mod nft_claim_lock_tests;
+
+#[cfg(test)]
+mod nft_vault_tests;
+
+#[cfg(test)]
+mod parent_bound_lock_tests;
+
+#[cfg(test)]
+mod redemption_receipt_tests;
+
 const TEST_ENV_VAR: &str = "CAPSULE_TEST_ENV";
 
 pub enum TestEnv {
@@ -45,6 +69,14 @@ impl Default for Loader {
     }
 }
 
+/// The class type code hash pinned by the contracts. The class type is deployed with a type id
+/// which the test context cannot reproduce, so the class cells under this code hash can be cell
+/// deps of the tests but cannot be spent or created by them.
+pub const CLASS_TYPE_CODE_HASH: [u8; 32] = [
+    9, 91, 140, 11, 78, 81, 164, 95, 149, 58, 205, 31, 205, 30, 57, 72, 159, 38, 117, 180, 188,
+    148, 231, 175, 39, 187, 56, 149, 135, 144, 227, 252,
+];
+
 impl Loader {
     fn with_test_env(env: TestEnv) -> Self {
         let load_prefix = match env {
@@ -0,0 +1,162 @@
+use blake2b_rs::Blake2bBuilder;
+use script_utils::class::ContentHash;
+use script_utils::media::{
+    media_hash, merkle_proof, merkle_root, token_leaf_hash, verify_token_media,
+};
+
+type Hash = [u8; 32];
+
+// The ckb blake2b hash of the empty data
+const EMPTY_MEDIA_HASH: &str = "44f4c69744d5f8c55d642062949dcae49bc4e7ef43d388c5a12f42b5633d163e";
+
+fn blake2b_256(data: &[u8]) -> Hash {
+    let mut blake2b = Blake2bBuilder::new(32)
+        .personal(b"ckb-default-hash")
+        .build();
+    blake2b.update(data);
+    let mut ret = [0; 32];
+    blake2b.finalize(&mut ret);
+    ret
+}
+
+// The sorted pair hash, calculated independently of the media module
+fn merge(left: &Hash, right: &Hash) -> Hash {
+    let mut data = Vec::new();
+    if left <= right {
+        data.extend_from_slice(left);
+        data.extend_from_slice(right);
+    } else {
+        data.extend_from_slice(right);
+        data.extend_from_slice(left);
+    }
+    blake2b_256(&data)
+}
+
+fn token_media(token_id: u32) -> Vec<u8> {
+    format!("ipfs://token-{}", token_id).into_bytes()
+}
+
+fn token_leaves(count: u32) -> Vec<Hash> {
+    (0..count)
+        .map(|token_id| token_leaf_hash(token_id, &media_hash(&token_media(token_id))))
+        .collect()
+}
+
+#[test]
+fn test_media_hash_of_empty_media() {
+    assert_eq!(hex::encode(media_hash(&[])), EMPTY_MEDIA_HASH);
+}
+
+#[test]
+fn test_token_leaf_hash() {
+    let hash = media_hash(b"media");
+    let mut data = 7u32.to_be_bytes().to_vec();
+    data.extend_from_slice(&hash);
+    assert_eq!(token_leaf_hash(7, &hash), blake2b_256(&data));
+}
+
+#[test]
+fn test_merkle_root_of_known_leaves() {
+    let leaves = token_leaves(4);
+    let root = merge(
+        &merge(&leaves[0], &leaves[1]),
+        &merge(&leaves[2], &leaves[3]),
+    );
+    assert_eq!(merkle_root(&leaves), root);
+}
+
+#[test]
+fn test_merkle_root_of_odd_leaves() {
+    let leaves = token_leaves(3);
+    // The last leaf of the odd level is promoted as it is
+    let root = merge(&merge(&leaves[0], &leaves[1]), &leaves[2]);
+    assert_eq!(merkle_root(&leaves), root);
+
+    let leaves = token_leaves(1);
+    assert_eq!(merkle_root(&leaves), leaves[0]);
+    assert!(merkle_proof(&leaves, 0).is_empty());
+    assert_eq!(merkle_root(&[]), [0u8; 32]);
+}
+
+#[test]
+fn test_merkle_proof_round_trip() {
+    for count in 1..=9 {
+        let leaves = token_leaves(count);
+        let content_hash = ContentHash::MerkleRoot(merkle_root(&leaves));
+        for token_id in 0..count {
+            let proof = merkle_proof(&leaves, token_id as usize);
+            assert!(
+                verify_token_media(&content_hash, token_id, &token_media(token_id), &proof),
+                "token {} of {} leaves",
+                token_id,
+                count
+            );
+        }
+    }
+}
+
+#[test]
+fn test_verify_tampered_media_and_proof() {
+    let leaves = token_leaves(5);
+    let content_hash = ContentHash::MerkleRoot(merkle_root(&leaves));
+    let proof = merkle_proof(&leaves, 2);
+    assert!(verify_token_media(
+        &content_hash,
+        2,
+        &token_media(2),
+        &proof
+    ));
+
+    // The media of another token, the wrong token id or a tampered leaf
+    assert!(!verify_token_media(
+        &content_hash,
+        2,
+        &token_media(3),
+        &proof
+    ));
+    assert!(!verify_token_media(
+        &content_hash,
+        3,
+        &token_media(2),
+        &proof
+    ));
+    assert!(!verify_token_media(&content_hash, 2, b"tampered", &proof));
+
+    // The tampered, truncated or extended proof
+    let mut tampered_proof = proof.clone();
+    tampered_proof[0][0] ^= 1;
+    assert!(!verify_token_media(
+        &content_hash,
+        2,
+        &token_media(2),
+        &tampered_proof
+    ));
+    assert!(!verify_token_media(
+        &content_hash,
+        2,
+        &token_media(2),
+        &proof[1..]
+    ));
+    let mut extended_proof = proof;
+    extended_proof.push(leaves[0]);
+    assert!(!verify_token_media(
+        &content_hash,
+        2,
+        &token_media(2),
+        &extended_proof
+    ));
+}
+
+#[test]
+fn test_verify_shared_media() {
+    let media = b"ipfs://shared-media";
+    let content_hash = ContentHash::Media(media_hash(media));
+    assert!(verify_token_media(&content_hash, 0, media, &[]));
+    assert!(verify_token_media(&content_hash, 9, media, &[]));
+    assert!(!verify_token_media(
+        &content_hash,
+        0,
+        b"ipfs://other-media",
+        &[]
+    ));
+}
@@ -0,0 +1,728 @@
+use crate::{assert_script_error, Loader};
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{HeaderBuilder, TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::{builtin::ALWAYS_SUCCESS, context::Context};
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+// error numbers
+const LOCKED_NFT_CANNOT_TRANSFER: i8 = 36;
+const AUCTION_LOCK_ARGS_INVALID: i8 = 61;
+const AUCTION_BID_INVALID: i8 = 62;
+const AUCTION_NOT_ENDED: i8 = 63;
+const AUCTION_SETTLEMENT_INVALID: i8 = 64;
+const AUCTION_PAYMENT_NOT_ENOUGH: i8 = 65;
+
+const NFT_CAPACITY: u64 = 500;
+const RESERVE_PRICE: u128 = 10_000;
+const END_TIME: u64 = 1_000_000;
+const BID: u128 = 12_000;
+const NEXT_BID: u128 = 15_000;
+const START_PRICE: u128 = 20_000;
+const START_TIME: u64 = 0;
+
+#[derive(PartialEq, Clone, Copy)]
+enum AuctionCase {
+    Cancel,
+    CancelWithBid,
+    FirstBid,
+    LateBid,
+    LateBidWithOldHeader,
+    Outbid,
+    OutbidWithoutRefund,
+    BidBelowReserve,
+    BidNotHigher,
+    Settle,
+    SettleBeforeEnd,
+    SettleToOtherLock,
+    SettleLateBid,
+    SettleLateBidToSeller,
+    DutchBuyAtStart,
+    DutchBuyWithHeader,
+    DutchPaymentNotEnough,
+    LockedNft,
+    ArgsInvalid,
+}
+
+impl AuctionCase {
+    fn is_dutch(self) -> bool {
+        matches!(
+            self,
+            AuctionCase::DutchBuyAtStart
+                | AuctionCase::DutchBuyWithHeader
+                | AuctionCase::DutchPaymentNotEnough
+        )
+    }
+
+    fn has_bid(self) -> bool {
+        matches!(
+            self,
+            AuctionCase::CancelWithBid
+                | AuctionCase::Outbid
+                | AuctionCase::OutbidWithoutRefund
+                | AuctionCase::BidNotHigher
+                | AuctionCase::Settle
+                | AuctionCase::SettleBeforeEnd
+                | AuctionCase::SettleToOtherLock
+                | AuctionCase::SettleLateBid
+                | AuctionCase::SettleLateBidToSeller
+        )
+    }
+}
+
+// auction lock args: <seller_lock_hash: [u8; 32]> + <reserve_price: u128> + <end_time: u64> +
+// <auction_type: u8> + <nft_code_hash: [u8; 32]> + <nft_hash_type: u8>, and <bidder_lock_hash:
+// [u8; 32]> + <bid: u128> for the English auction, or <start_price: u128> + <start_time: u64> for
+// the Dutch auction
+fn auction_args(
+    seller_lock: &Script,
+    nft_code: &Script,
+    dutch: bool,
+    bid: Option<(&Script, u128)>,
+) -> Bytes {
+    let mut args = seller_lock.calc_script_hash().as_slice().to_vec();
+    args.extend_from_slice(&RESERVE_PRICE.to_be_bytes());
+    args.extend_from_slice(&END_TIME.to_be_bytes());
+    args.push(if dutch { 1 } else { 0 });
+    args.extend_from_slice(nft_code.code_hash().as_slice());
+    args.extend_from_slice(nft_code.hash_type().as_slice());
+    if dutch {
+        args.extend_from_slice(&START_PRICE.to_be_bytes());
+        args.extend_from_slice(&START_TIME.to_be_bytes());
+    } else if let Some((bidder_lock, amount)) = bid {
+        args.extend_from_slice(bidder_lock.calc_script_hash().as_slice());
+        args.extend_from_slice(&amount.to_be_bytes());
+    }
+    Bytes::from(args)
+}
+
+fn create_test_context(case: AuctionCase) -> (Context, TransactionView) {
+    // deploy contracts
+    let mut context = Context::default();
+    let auction_lock_bin: Bytes = Loader::default().load_binary("nft-auction-lock");
+    let auction_lock_out_point = context.deploy_cell(auction_lock_bin);
+    let nft_bin: Bytes = Loader::default().load_binary("nft-type");
+    let nft_out_point = context.deploy_cell(nft_bin);
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+
+    // prepare scripts
+    let seller_lock = context
+        .build_script(&always_success_out_point, Bytes::from("seller"))
+        .expect("script");
+    let bidder_lock = context
+        .build_script(&always_success_out_point, Bytes::from("bidder"))
+        .expect("script");
+    let next_bidder_lock = context
+        .build_script(&always_success_out_point, Bytes::from("next-bidder"))
+        .expect("script");
+
+    let nft_code = context
+        .build_script(&nft_out_point, Bytes::new())
+        .expect("script");
+    let mut args = auction_args(
+        &seller_lock,
+        &nft_code,
+        case.is_dutch(),
+        Some((&bidder_lock, BID)).filter(|_| case.has_bid()),
+    );
+    if case == AuctionCase::ArgsInvalid {
+        args.truncate(60);
+    }
+    let auction_lock = context
+        .build_script(&auction_lock_out_point, args)
+        .expect("script");
+    let next_bid = match case {
+        AuctionCase::BidBelowReserve => RESERVE_PRICE - 1,
+        AuctionCase::BidNotHigher => BID,
+        _ => NEXT_BID,
+    };
+    let next_auction_lock = context
+        .build_script(
+            &auction_lock_out_point,
+            auction_args(
+                &seller_lock,
+                &nft_code,
+                false,
+                Some((&next_bidder_lock, next_bid)),
+            ),
+        )
+        .expect("script");
+    let nft_type = context
+        .build_script(
+            &nft_out_point,
+            Bytes::from(
+                hex::decode("f90f9c38b0ea0815156bbc340c910d0a21af57c20000000000000011").unwrap(),
+            ),
+        )
+        .expect("script");
+
+    // prepare cells
+    let nft_data = match case {
+        AuctionCase::LockedNft => Bytes::from(hex::decode("0000000000000000000002").unwrap()),
+        _ => Bytes::from(hex::decode("0000000000000000000000").unwrap()),
+    };
+    // The highest bid is escrowed in the capacity of the auction cell
+    let escrowed = if case.has_bid() { BID as u64 } else { 0 };
+    let nft_input_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity((NFT_CAPACITY + escrowed).pack())
+            .lock(auction_lock)
+            .type_(Some(nft_type.clone()).pack())
+            .build(),
+        nft_data.clone(),
+    );
+    let another_lock = match case {
+        AuctionCase::Cancel | AuctionCase::CancelWithBid => seller_lock.clone(),
+        _ => next_bidder_lock.clone(),
+    };
+    let another_input_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(100_000u64.pack())
+            .lock(another_lock)
+            .build(),
+        Bytes::new(),
+    );
+    let inputs = vec![
+        CellInput::new_builder()
+            .previous_output(nft_input_out_point.clone())
+            .build(),
+        CellInput::new_builder()
+            .previous_output(another_input_out_point)
+            .build(),
+    ];
+
+    let nft_output = |lock: &Script, capacity: u64| {
+        CellOutput::new_builder()
+            .capacity(capacity.pack())
+            .lock(lock.clone())
+            .type_(Some(nft_type.clone()).pack())
+            .build()
+    };
+    let payment_output = |lock: &Script, capacity: u128| {
+        CellOutput::new_builder()
+            .capacity((capacity as u64).pack())
+            .lock(lock.clone())
+            .build()
+    };
+    let outputs = match case {
+        AuctionCase::Cancel | AuctionCase::CancelWithBid => {
+            vec![nft_output(&seller_lock, NFT_CAPACITY)]
+        }
+        AuctionCase::FirstBid
+        | AuctionCase::LateBid
+        | AuctionCase::LateBidWithOldHeader
+        | AuctionCase::BidBelowReserve
+        | AuctionCase::LockedNft => {
+            vec![nft_output(
+                &next_auction_lock,
+                NFT_CAPACITY + next_bid as u64,
+            )]
+        }
+        // The payments are at the same index as the auction input
+        AuctionCase::Outbid | AuctionCase::BidNotHigher => vec![
+            payment_output(&bidder_lock, BID),
+            nft_output(&next_auction_lock, NFT_CAPACITY + next_bid as u64),
+        ],
+        AuctionCase::OutbidWithoutRefund => vec![
+            payment_output(&bidder_lock, BID - 1),
+            nft_output(&next_auction_lock, NFT_CAPACITY + next_bid as u64),
+        ],
+        AuctionCase::Settle | AuctionCase::SettleBeforeEnd | AuctionCase::SettleLateBid => vec![
+            payment_output(&seller_lock, BID),
+            nft_output(&bidder_lock, NFT_CAPACITY),
+        ],
+        AuctionCase::SettleToOtherLock => vec![
+            payment_output(&seller_lock, BID),
+            nft_output(&next_bidder_lock, NFT_CAPACITY),
+        ],
+        // The late bid is refunded and the nft goes back to the seller
+        AuctionCase::SettleLateBidToSeller => vec![
+            payment_output(&bidder_lock, BID),
+            nft_output(&seller_lock, NFT_CAPACITY),
+        ],
+        AuctionCase::DutchBuyAtStart => vec![
+            payment_output(&seller_lock, START_PRICE),
+            nft_output(&next_bidder_lock, NFT_CAPACITY),
+        ],
+        // The price is 15_000 at the half of the Dutch auction
+        AuctionCase::DutchBuyWithHeader | AuctionCase::DutchPaymentNotEnough => vec![
+            payment_output(&seller_lock, 15_000),
+            nft_output(&next_bidder_lock, NFT_CAPACITY),
+        ],
+        AuctionCase::ArgsInvalid => vec![nft_output(&seller_lock, NFT_CAPACITY)],
+    };
+    let outputs_data: Vec<Bytes> = outputs
+        .iter()
+        .map(|output| {
+            if output.type_().is_some() {
+                nft_data.clone()
+            } else {
+                Bytes::new()
+            }
+        })
+        .collect();
+
+    // The header deps prove the time has passed
+    let header_timestamp = match case {
+        AuctionCase::Settle | AuctionCase::SettleToOtherLock | AuctionCase::LateBid => {
+            Some(END_TIME)
+        }
+        AuctionCase::SettleBeforeEnd => Some(END_TIME - 1),
+        AuctionCase::LateBidWithOldHeader => Some(END_TIME / 2),
+        AuctionCase::DutchBuyWithHeader => Some(END_TIME / 2),
+        _ => None,
+    };
+
+    // The nft type checks the witness lock of the first input
+    let witnesses = vec![
+        WitnessArgs::new_builder()
+            .lock(Some(Bytes::new()).pack())
+            .build()
+            .as_bytes(),
+        Bytes::new(),
+    ];
+
+    // build transaction
+    let mut tx_builder = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(always_success_out_point)
+                .build(),
+        )
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(auction_lock_out_point)
+                .build(),
+        )
+        .cell_dep(CellDep::new_builder().out_point(nft_out_point).build())
+        .witnesses(witnesses.pack());
+    if let Some(timestamp) = header_timestamp {
+        let header = HeaderBuilder::default().timestamp(timestamp.pack()).build();
+        tx_builder = tx_builder.header_dep(header.hash());
+        context.insert_header(header);
+    }
+    // The bids and the settlements carry the header of the block committing the auction input,
+    // which is after the end time for the late bids
+    let committed_timestamp = match case {
+        AuctionCase::LateBidWithOldHeader
+        | AuctionCase::SettleLateBid
+        | AuctionCase::SettleLateBidToSeller => Some(END_TIME),
+        AuctionCase::Cancel | AuctionCase::CancelWithBid | AuctionCase::ArgsInvalid => None,
+        _ if case.is_dutch() => None,
+        _ => Some(END_TIME / 2),
+    };
+    if let Some(timestamp) = committed_timestamp {
+        let header = HeaderBuilder::default()
+            .timestamp(timestamp.pack())
+            .number(1u64.pack())
+            .build();
+        context.link_cell_with_block(nft_input_out_point, header.hash(), 0);
+        tx_builder = tx_builder.header_dep(header.hash());
+        context.insert_header(header);
+    }
+    (context, tx_builder.build())
+}
+
+#[test]
+fn test_cancel_nft_auction_success() {
+    let (mut context, tx) = create_test_context(AuctionCase::Cancel);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_cancel_nft_auction_with_bid_error() {
+    let (mut context, tx) = create_test_context(AuctionCase::CancelWithBid);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, AUCTION_NOT_ENDED);
+}
+
+#[test]
+fn test_first_bid_of_nft_auction_success() {
+    let (mut context, tx) = create_test_context(AuctionCase::FirstBid);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_bid_after_end_time_error() {
+    let (mut context, tx) = create_test_context(AuctionCase::LateBid);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, AUCTION_BID_INVALID);
+}
+
+#[test]
+fn test_bid_on_auction_committed_after_end_time_with_old_header_error() {
+    let (mut context, tx) = create_test_context(AuctionCase::LateBidWithOldHeader);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, AUCTION_BID_INVALID);
+}
+
+#[test]
+fn test_outbid_with_refund_success() {
+    let (mut context, tx) = create_test_context(AuctionCase::Outbid);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_outbid_without_refund_error() {
+    let (mut context, tx) = create_test_context(AuctionCase::OutbidWithoutRefund);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, AUCTION_PAYMENT_NOT_ENOUGH);
+}
+
+#[test]
+fn test_bid_below_reserve_price_error() {
+    let (mut context, tx) = create_test_context(AuctionCase::BidBelowReserve);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, AUCTION_BID_INVALID);
+}
+
+#[test]
+fn test_bid_not_higher_error() {
+    let (mut context, tx) = create_test_context(AuctionCase::BidNotHigher);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, AUCTION_BID_INVALID);
+}
+
+#[test]
+fn test_settle_nft_auction_success() {
+    let (mut context, tx) = create_test_context(AuctionCase::Settle);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_settle_nft_auction_before_end_error() {
+    let (mut context, tx) = create_test_context(AuctionCase::SettleBeforeEnd);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, AUCTION_NOT_ENDED);
+}
+
+#[test]
+fn test_settle_late_bid_to_bidder_error() {
+    let (mut context, tx) = create_test_context(AuctionCase::SettleLateBid);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, AUCTION_SETTLEMENT_INVALID);
+}
+
+#[test]
+fn test_settle_late_bid_with_refund_success() {
+    let (mut context, tx) = create_test_context(AuctionCase::SettleLateBidToSeller);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_settle_nft_auction_to_other_lock_error() {
+    let (mut context, tx) = create_test_context(AuctionCase::SettleToOtherLock);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, AUCTION_SETTLEMENT_INVALID);
+}
+
+#[test]
+fn test_dutch_auction_buy_at_start_price_success() {
+    let (mut context, tx) = create_test_context(AuctionCase::DutchBuyAtStart);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dutch_auction_buy_with_header_success() {
+    let (mut context, tx) = create_test_context(AuctionCase::DutchBuyWithHeader);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_dutch_auction_payment_not_enough_error() {
+    let (mut context, tx) = create_test_context(AuctionCase::DutchPaymentNotEnough);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, AUCTION_PAYMENT_NOT_ENOUGH);
+}
+
+#[test]
+fn test_bid_locked_nft_error() {
+    let (mut context, tx) = create_test_context(AuctionCase::LockedNft);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, LOCKED_NFT_CANNOT_TRANSFER);
+}
+
+#[test]
+fn test_nft_auction_lock_args_invalid_error() {
+    let (mut context, tx) = create_test_context(AuctionCase::ArgsInvalid);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, AUCTION_LOCK_ARGS_INVALID);
+}
+
+const SALE_PRICE: u128 = 11_000;
+
+// The auction settlement of the input 0 and the sale of the input 1 are paid to the same seller.
+// With the overlapping payment, one output of the seller at the index 1 covers both listings.
+fn create_cross_lock_context(overlapping: bool) -> (Context, TransactionView) {
+    // deploy contracts
+    let mut context = Context::default();
+    let auction_lock_bin: Bytes = Loader::default().load_binary("nft-auction-lock");
+    let auction_lock_out_point = context.deploy_cell(auction_lock_bin);
+    let sale_lock_bin: Bytes = Loader::default().load_binary("nft-sale-lock");
+    let sale_lock_out_point = context.deploy_cell(sale_lock_bin);
+    let nft_bin: Bytes = Loader::default().load_binary("nft-type");
+    let nft_out_point = context.deploy_cell(nft_bin);
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+
+    // prepare scripts
+    let seller_lock = context
+        .build_script(&always_success_out_point, Bytes::from("seller"))
+        .expect("script");
+    let bidder_lock = context
+        .build_script(&always_success_out_point, Bytes::from("bidder"))
+        .expect("script");
+    let buyer_lock = context
+        .build_script(&always_success_out_point, Bytes::from("buyer"))
+        .expect("script");
+    let nft_code = context
+        .build_script(&nft_out_point, Bytes::new())
+        .expect("script");
+    let auction_lock = context
+        .build_script(
+            &auction_lock_out_point,
+            auction_args(&seller_lock, &nft_code, false, Some((&bidder_lock, BID))),
+        )
+        .expect("script");
+    // sale lock args: <seller_lock_hash: [u8; 32]> + <price: u128> + <nft_code_hash: [u8; 32]> +
+    // <nft_hash_type: u8>
+    let mut sale_args = seller_lock.calc_script_hash().as_slice().to_vec();
+    sale_args.extend_from_slice(&SALE_PRICE.to_be_bytes());
+    sale_args.extend_from_slice(nft_code.code_hash().as_slice());
+    sale_args.extend_from_slice(nft_code.hash_type().as_slice());
+    let sale_lock = context
+        .build_script(&sale_lock_out_point, Bytes::from(sale_args))
+        .expect("script");
+    let nft_type_args = |token_id: u32| {
+        let mut args = hex::decode("f90f9c38b0ea0815156bbc340c910d0a21af57c200000000").unwrap();
+        args.extend_from_slice(&token_id.to_be_bytes());
+        Bytes::from(args)
+    };
+    let auction_nft_type = context
+        .build_script(&nft_out_point, nft_type_args(0x11))
+        .expect("script");
+    let sale_nft_type = context
+        .build_script(&nft_out_point, nft_type_args(0x12))
+        .expect("script");
+
+    // prepare cells
+    let nft_data = Bytes::from(hex::decode("0000000000000000000000").unwrap());
+    let nft_output = |lock: &Script, type_: &Script, capacity: u64| {
+        CellOutput::new_builder()
+            .capacity(capacity.pack())
+            .lock(lock.clone())
+            .type_(Some(type_.clone()).pack())
+            .build()
+    };
+    let payment_output = |capacity: u128| {
+        CellOutput::new_builder()
+            .capacity((capacity as u64).pack())
+            .lock(seller_lock.clone())
+            .build()
+    };
+    let auction_nft_out_point = context.create_cell(
+        nft_output(&auction_lock, &auction_nft_type, NFT_CAPACITY + BID as u64),
+        nft_data.clone(),
+    );
+    let sale_nft_out_point = context.create_cell(
+        nft_output(&sale_lock, &sale_nft_type, NFT_CAPACITY),
+        nft_data.clone(),
+    );
+    let buyer_input_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(100_000u64.pack())
+            .lock(buyer_lock.clone())
+            .build(),
+        Bytes::new(),
+    );
+    let inputs = vec![
+        CellInput::new_builder()
+            .previous_output(auction_nft_out_point.clone())
+            .build(),
+        CellInput::new_builder()
+            .previous_output(sale_nft_out_point)
+            .build(),
+        CellInput::new_builder()
+            .previous_output(buyer_input_out_point)
+            .build(),
+    ];
+
+    let outputs = if overlapping {
+        vec![
+            nft_output(&bidder_lock, &auction_nft_type, NFT_CAPACITY),
+            payment_output(BID.max(SALE_PRICE)),
+            nft_output(&buyer_lock, &sale_nft_type, NFT_CAPACITY),
+        ]
+    } else {
+        vec![
+            payment_output(BID),
+            payment_output(SALE_PRICE),
+            nft_output(&bidder_lock, &auction_nft_type, NFT_CAPACITY),
+            nft_output(&buyer_lock, &sale_nft_type, NFT_CAPACITY),
+        ]
+    };
+    let outputs_data: Vec<Bytes> = outputs
+        .iter()
+        .map(|output| {
+            if output.type_().is_some() {
+                nft_data.clone()
+            } else {
+                Bytes::new()
+            }
+        })
+        .collect();
+
+    // The nft type checks the witness lock of the first input
+    let witnesses = vec![
+        WitnessArgs::new_builder()
+            .lock(Some(Bytes::new()).pack())
+            .build()
+            .as_bytes(),
+        Bytes::new(),
+        Bytes::new(),
+    ];
+
+    // The header deps prove the auction has ended, along with the header of the block committing
+    // the auction input before the end time
+    let header = HeaderBuilder::default().timestamp(END_TIME.pack()).build();
+    context.insert_header(header.clone());
+    let committed_header = HeaderBuilder::default()
+        .timestamp((END_TIME / 2).pack())
+        .number(1u64.pack())
+        .build();
+    context.link_cell_with_block(auction_nft_out_point, committed_header.hash(), 0);
+    context.insert_header(committed_header.clone());
+
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(always_success_out_point)
+                .build(),
+        )
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(auction_lock_out_point)
+                .build(),
+        )
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(sale_lock_out_point)
+                .build(),
+        )
+        .cell_dep(CellDep::new_builder().out_point(nft_out_point).build())
+        .header_dep(header.hash())
+        .header_dep(committed_header.hash())
+        .witnesses(witnesses.pack())
+        .build();
+    (context, tx)
+}
+
+#[test]
+fn test_settle_nft_auction_along_with_sale_success() {
+    let (mut context, tx) = create_cross_lock_context(false);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_settle_nft_auction_with_sale_payment_error() {
+    let (mut context, tx) = create_cross_lock_context(true);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, AUCTION_PAYMENT_NOT_ENOUGH);
+}
@@ -0,0 +1,312 @@
+use crate::{assert_script_error, Loader};
+use blake2b_rs::Blake2bBuilder;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::{builtin::ALWAYS_SUCCESS, context::Context};
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+// error numbers
+const NFT_DISALLOW_CLAIMED: i8 = 27;
+const CLAIM_LOCK_ARGS_INVALID: i8 = 66;
+const CLAIM_CODE_INVALID: i8 = 67;
+const NFT_CLAIM_INVALID: i8 = 68;
+const CLAIM_COMMITMENT_MISSING: i8 = 80;
+
+const CLAIM_CODE: &str = "airdrop-claim-code";
+const CLAIM_COMMITMENT_MATURITY: u64 = 10;
+// The flags of the relative since in block numbers
+const SINCE_RELATIVE_BLOCK_NUMBER: u64 = 0x8000_0000_0000_0000;
+
+#[derive(PartialEq)]
+enum ClaimCase {
+    Claim,
+    TakeBack,
+    CodeInvalid,
+    RecipientMismatch,
+    OutputNotClaimed,
+    DisallowClaim,
+    ArgsInvalid,
+    CommitmentMissing,
+    CommitmentNotMatured,
+    RecipientSubstituted,
+}
+
+fn blake2b_256(data: &[u8]) -> [u8; 32] {
+    let mut blake2b = Blake2bBuilder::new(32)
+        .personal(b"ckb-default-hash")
+        .build();
+    blake2b.update(data);
+    let mut ret = [0u8; 32];
+    blake2b.finalize(&mut ret);
+    ret
+}
+
+fn create_test_context(case: ClaimCase) -> (Context, TransactionView) {
+    // deploy contracts
+    let mut context = Context::default();
+    let claim_lock_bin: Bytes = Loader::default().load_binary("nft-claim-lock");
+    let claim_lock_out_point = context.deploy_cell(claim_lock_bin);
+    let nft_bin: Bytes = Loader::default().load_binary("nft-type");
+    let nft_out_point = context.deploy_cell(nft_bin);
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+
+    // prepare scripts
+    let owner_lock = context
+        .build_script(&always_success_out_point, Bytes::from("owner"))
+        .expect("script");
+    let recipient_lock = context
+        .build_script(&always_success_out_point, Bytes::from("recipient"))
+        .expect("script");
+    let other_lock = context
+        .build_script(&always_success_out_point, Bytes::from("other"))
+        .expect("script");
+
+    // claim lock args: <code_hash: [u8; 32]> + <owner_lock_hash: [u8; 32]> + <nft_code_hash:
+    // [u8; 32]> + <nft_hash_type: u8>
+    let nft_code = context
+        .build_script(&nft_out_point, Bytes::new())
+        .expect("script");
+    let mut claim_args = blake2b_256(CLAIM_CODE.as_bytes()).to_vec();
+    claim_args.extend_from_slice(owner_lock.calc_script_hash().as_slice());
+    claim_args.extend_from_slice(nft_code.code_hash().as_slice());
+    claim_args.extend_from_slice(nft_code.hash_type().as_slice());
+    if case == ClaimCase::ArgsInvalid {
+        claim_args.truncate(32);
+    }
+    let claim_lock = context
+        .build_script(&claim_lock_out_point, Bytes::from(claim_args))
+        .expect("script");
+    let nft_type = context
+        .build_script(
+            &nft_out_point,
+            Bytes::from(
+                hex::decode("f90f9c38b0ea0815156bbc340c910d0a21af57c20000000000000011").unwrap(),
+            ),
+        )
+        .expect("script");
+
+    // prepare cells
+    let nft_input_data = match case {
+        ClaimCase::DisallowClaim => Bytes::from(hex::decode("0000000000000000000100").unwrap()),
+        _ => Bytes::from(hex::decode("0000000000000000000000").unwrap()),
+    };
+    let nft_input_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(500u64.pack())
+            .lock(claim_lock)
+            .type_(Some(nft_type.clone()).pack())
+            .build(),
+        nft_input_data,
+    );
+    let another_lock = match case {
+        ClaimCase::TakeBack => owner_lock.clone(),
+        _ => recipient_lock.clone(),
+    };
+    let another_input_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(another_lock)
+            .build(),
+        Bytes::new(),
+    );
+    let mut inputs = vec![
+        CellInput::new_builder()
+            .previous_output(nft_input_out_point)
+            .build(),
+        CellInput::new_builder()
+            .previous_output(another_input_out_point)
+            .build(),
+    ];
+
+    // The recipient commits blake2b(<claim_code> + <recipient_lock_hash>) before the claim, and
+    // the front-runner substitutes its own lock for the committed recipient
+    let mut commitment = CLAIM_CODE.as_bytes().to_vec();
+    commitment.extend_from_slice(recipient_lock.calc_script_hash().as_slice());
+    let commitment_blocks = match case {
+        ClaimCase::CommitmentNotMatured => CLAIM_COMMITMENT_MATURITY - 1,
+        _ => CLAIM_COMMITMENT_MATURITY,
+    };
+    if case != ClaimCase::TakeBack && case != ClaimCase::CommitmentMissing {
+        let commitment_out_point = context.create_cell(
+            CellOutput::new_builder()
+                .capacity(100u64.pack())
+                .lock(recipient_lock.clone())
+                .build(),
+            Bytes::copy_from_slice(&blake2b_256(&commitment)),
+        );
+        inputs.push(
+            CellInput::new_builder()
+                .previous_output(commitment_out_point)
+                .since((SINCE_RELATIVE_BLOCK_NUMBER | commitment_blocks).pack())
+                .build(),
+        );
+    }
+
+    let output_lock = match case {
+        ClaimCase::TakeBack => owner_lock,
+        ClaimCase::RecipientMismatch | ClaimCase::RecipientSubstituted => other_lock.clone(),
+        _ => recipient_lock.clone(),
+    };
+    let outputs = vec![CellOutput::new_builder()
+        .capacity(500u64.pack())
+        .lock(output_lock)
+        .type_(Some(nft_type).pack())
+        .build()];
+    let nft_output_data = match case {
+        ClaimCase::TakeBack | ClaimCase::OutputNotClaimed => "0000000000000000000000",
+        ClaimCase::DisallowClaim => "0000000000000000000101",
+        _ => "0000000000000000000001",
+    };
+    let outputs_data = vec![Bytes::from(hex::decode(nft_output_data).unwrap())];
+
+    // witness lock: <recipient_lock_hash: [u8; 32]> + <claim_code>
+    let witness_recipient_lock = match case {
+        ClaimCase::RecipientSubstituted => other_lock,
+        _ => recipient_lock,
+    };
+    let mut witness_lock = witness_recipient_lock
+        .calc_script_hash()
+        .as_slice()
+        .to_vec();
+    match case {
+        ClaimCase::CodeInvalid => witness_lock.extend_from_slice(b"wrong-claim-code"),
+        _ => witness_lock.extend_from_slice(CLAIM_CODE.as_bytes()),
+    }
+    let witnesses = vec![
+        WitnessArgs::new_builder()
+            .lock(Some(Bytes::from(witness_lock)).pack())
+            .build()
+            .as_bytes(),
+        Bytes::new(),
+    ];
+
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(always_success_out_point)
+                .build(),
+        )
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(claim_lock_out_point)
+                .build(),
+        )
+        .cell_dep(CellDep::new_builder().out_point(nft_out_point).build())
+        .witnesses(witnesses.pack())
+        .build();
+    (context, tx)
+}
+
+#[test]
+fn test_claim_nft_with_claim_code_success() {
+    let (mut context, tx) = create_test_context(ClaimCase::Claim);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_take_back_unclaimed_nft_success() {
+    let (mut context, tx) = create_test_context(ClaimCase::TakeBack);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_claim_nft_with_wrong_code_error() {
+    let (mut context, tx) = create_test_context(ClaimCase::CodeInvalid);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, CLAIM_CODE_INVALID);
+}
+
+#[test]
+fn test_claim_nft_to_other_lock_error() {
+    let (mut context, tx) = create_test_context(ClaimCase::RecipientMismatch);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_CLAIM_INVALID);
+}
+
+#[test]
+fn test_claim_nft_output_not_claimed_error() {
+    let (mut context, tx) = create_test_context(ClaimCase::OutputNotClaimed);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_CLAIM_INVALID);
+}
+
+#[test]
+fn test_claim_nft_disallow_claim_error() {
+    let (mut context, tx) = create_test_context(ClaimCase::DisallowClaim);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_DISALLOW_CLAIMED);
+}
+
+#[test]
+fn test_nft_claim_lock_args_invalid_error() {
+    let (mut context, tx) = create_test_context(ClaimCase::ArgsInvalid);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, CLAIM_LOCK_ARGS_INVALID);
+}
+
+#[test]
+fn test_claim_nft_without_commitment_error() {
+    let (mut context, tx) = create_test_context(ClaimCase::CommitmentMissing);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, CLAIM_COMMITMENT_MISSING);
+}
+
+#[test]
+fn test_claim_nft_with_immature_commitment_error() {
+    let (mut context, tx) = create_test_context(ClaimCase::CommitmentNotMatured);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, CLAIM_COMMITMENT_MISSING);
+}
+
+// The front-runner copies the revealed claim code with its own lock as the recipient
+#[test]
+fn test_claim_nft_with_substituted_recipient_error() {
+    let (mut context, tx) = create_test_context(ClaimCase::RecipientSubstituted);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, CLAIM_COMMITMENT_MISSING);
+}
@@ -0,0 +1,311 @@
+use crate::{assert_script_error, Loader};
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::{builtin::ALWAYS_SUCCESS, context::Context};
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+// error numbers
+const NFT_CANNOT_TRANSFER_AFTER_CLAIM: i8 = 30;
+const LOCKED_NFT_CANNOT_TRANSFER: i8 = 36;
+const SALE_LOCK_ARGS_INVALID: i8 = 59;
+const SALE_PAYMENT_NOT_ENOUGH: i8 = 60;
+const MARKET_NFT_TYPE_INVALID: i8 = 82;
+
+const PRICE: u128 = 10_000;
+
+#[derive(PartialEq)]
+enum SaleCase {
+    Cancel,
+    BuyWithCkb,
+    BuyWithSudt,
+    PaymentNotEnough,
+    PaymentToOtherIndex,
+    SudtTypeMismatch,
+    LockedNft,
+    ClaimedNftNotTransferable,
+    FakeNft,
+    ArgsInvalid,
+}
+
+fn create_test_context(case: SaleCase) -> (Context, TransactionView) {
+    // deploy contracts
+    let mut context = Context::default();
+    let sale_lock_bin: Bytes = Loader::default().load_binary("nft-sale-lock");
+    let sale_lock_out_point = context.deploy_cell(sale_lock_bin);
+    let nft_bin: Bytes = Loader::default().load_binary("nft-type");
+    let nft_out_point = context.deploy_cell(nft_bin);
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+
+    // prepare scripts
+    let seller_lock = context
+        .build_script(&always_success_out_point, Bytes::from("seller"))
+        .expect("script");
+    let buyer_lock = context
+        .build_script(&always_success_out_point, Bytes::from("buyer"))
+        .expect("script");
+    let sudt_type = context
+        .build_script(&always_success_out_point, Bytes::from("sudt"))
+        .expect("script");
+    let another_sudt_type = context
+        .build_script(&always_success_out_point, Bytes::from("another-sudt"))
+        .expect("script");
+
+    let nft_type = context
+        .build_script(
+            &nft_out_point,
+            Bytes::from(
+                hex::decode("f90f9c38b0ea0815156bbc340c910d0a21af57c20000000000000011").unwrap(),
+            ),
+        )
+        .expect("script");
+    // The fake nft has the args of the nft but another type code
+    let nft_type = match case {
+        SaleCase::FakeNft => context
+            .build_script(&always_success_out_point, nft_type.args().raw_data())
+            .expect("script"),
+        _ => nft_type,
+    };
+
+    // sale lock args: <seller_lock_hash: [u8; 32]> + <price: u128> + <nft_code_hash: [u8; 32]> +
+    // <nft_hash_type: u8> + <sudt_type_hash: [u8; 32]>
+    let nft_code = context
+        .build_script(&nft_out_point, Bytes::new())
+        .expect("script");
+    let mut sale_args = seller_lock.calc_script_hash().as_slice().to_vec();
+    sale_args.extend_from_slice(&PRICE.to_be_bytes());
+    sale_args.extend_from_slice(nft_code.code_hash().as_slice());
+    sale_args.extend_from_slice(nft_code.hash_type().as_slice());
+    match case {
+        SaleCase::BuyWithSudt | SaleCase::SudtTypeMismatch => {
+            sale_args.extend_from_slice(sudt_type.calc_script_hash().as_slice())
+        }
+        SaleCase::ArgsInvalid => sale_args.truncate(40),
+        _ => (),
+    }
+    let sale_lock = context
+        .build_script(&sale_lock_out_point, Bytes::from(sale_args))
+        .expect("script");
+
+    // prepare cells
+    let nft_data = match case {
+        SaleCase::LockedNft => Bytes::from(hex::decode("0000000000000000000002").unwrap()),
+        SaleCase::ClaimedNftNotTransferable => {
+            Bytes::from(hex::decode("0000000000000000002001").unwrap())
+        }
+        _ => Bytes::from(hex::decode("0000000000000000000000").unwrap()),
+    };
+    let nft_input_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(500u64.pack())
+            .lock(sale_lock.clone())
+            .type_(Some(nft_type.clone()).pack())
+            .build(),
+        nft_data.clone(),
+    );
+    let another_lock = match case {
+        SaleCase::Cancel => seller_lock.clone(),
+        _ => buyer_lock.clone(),
+    };
+    let another_input_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(100_000u64.pack())
+            .lock(another_lock)
+            .build(),
+        Bytes::new(),
+    );
+    let inputs = vec![
+        CellInput::new_builder()
+            .previous_output(nft_input_out_point)
+            .build(),
+        CellInput::new_builder()
+            .previous_output(another_input_out_point)
+            .build(),
+    ];
+
+    let nft_output = CellOutput::new_builder()
+        .capacity(500u64.pack())
+        .lock(match case {
+            SaleCase::Cancel => seller_lock.clone(),
+            _ => buyer_lock.clone(),
+        })
+        .type_(Some(nft_type).pack())
+        .build();
+    let payment_capacity = match case {
+        SaleCase::PaymentNotEnough => PRICE as u64 - 1,
+        _ => PRICE as u64,
+    };
+    let payment_output = match case {
+        SaleCase::BuyWithSudt => CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(seller_lock.clone())
+            .type_(Some(sudt_type).pack())
+            .build(),
+        SaleCase::SudtTypeMismatch => CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(seller_lock.clone())
+            .type_(Some(another_sudt_type).pack())
+            .build(),
+        _ => CellOutput::new_builder()
+            .capacity(payment_capacity.pack())
+            .lock(seller_lock.clone())
+            .build(),
+    };
+    // sUDT data: <amount: u128 little endian>
+    let payment_data = match case {
+        SaleCase::BuyWithSudt | SaleCase::SudtTypeMismatch => {
+            Bytes::from(PRICE.to_le_bytes().to_vec())
+        }
+        _ => Bytes::new(),
+    };
+    // The payment must be at the same index as the listed nft
+    let (outputs, outputs_data) = match case {
+        SaleCase::PaymentToOtherIndex => (vec![nft_output, payment_output], vec![
+            nft_data,
+            payment_data,
+        ]),
+        _ => (vec![payment_output, nft_output], vec![
+            payment_data,
+            nft_data,
+        ]),
+    };
+
+    // The nft type checks the witness lock of the first input
+    let witnesses = vec![
+        WitnessArgs::new_builder()
+            .lock(Some(Bytes::new()).pack())
+            .build()
+            .as_bytes(),
+        Bytes::new(),
+    ];
+
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(always_success_out_point)
+                .build(),
+        )
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(sale_lock_out_point)
+                .build(),
+        )
+        .cell_dep(CellDep::new_builder().out_point(nft_out_point).build())
+        .witnesses(witnesses.pack())
+        .build();
+    (context, tx)
+}
+
+#[test]
+fn test_cancel_nft_sale_success() {
+    let (mut context, tx) = create_test_context(SaleCase::Cancel);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_buy_nft_with_ckb_success() {
+    let (mut context, tx) = create_test_context(SaleCase::BuyWithCkb);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_buy_nft_with_sudt_success() {
+    let (mut context, tx) = create_test_context(SaleCase::BuyWithSudt);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_buy_nft_payment_not_enough_error() {
+    let (mut context, tx) = create_test_context(SaleCase::PaymentNotEnough);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, SALE_PAYMENT_NOT_ENOUGH);
+}
+
+#[test]
+fn test_buy_nft_payment_to_other_index_error() {
+    let (mut context, tx) = create_test_context(SaleCase::PaymentToOtherIndex);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, SALE_PAYMENT_NOT_ENOUGH);
+}
+
+#[test]
+fn test_buy_nft_sudt_type_mismatch_error() {
+    let (mut context, tx) = create_test_context(SaleCase::SudtTypeMismatch);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, SALE_PAYMENT_NOT_ENOUGH);
+}
+
+#[test]
+fn test_buy_locked_nft_error() {
+    let (mut context, tx) = create_test_context(SaleCase::LockedNft);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, LOCKED_NFT_CANNOT_TRANSFER);
+}
+
+#[test]
+fn test_buy_claimed_nft_not_transferable_error() {
+    let (mut context, tx) = create_test_context(SaleCase::ClaimedNftNotTransferable);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_CANNOT_TRANSFER_AFTER_CLAIM);
+}
+
+#[test]
+fn test_buy_fake_nft_error() {
+    let (mut context, tx) = create_test_context(SaleCase::FakeNft);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, MARKET_NFT_TYPE_INVALID);
+}
+
+#[test]
+fn test_nft_sale_lock_args_invalid_error() {
+    let (mut context, tx) = create_test_context(SaleCase::ArgsInvalid);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, SALE_LOCK_ARGS_INVALID);
+}
@@ -1,4 +1,5 @@
-use crate::{assert_script_error, Loader};
+use crate::{assert_script_error, Loader, CLASS_TYPE_CODE_HASH};
+use blake2b_rs::Blake2bBuilder;
 use ckb_testtool::ckb_types::{
     bytes::Bytes,
     core::{TransactionBuilder, TransactionView},
@@ -10,10 +11,6 @@ use ckb_testtool::{builtin::ALWAYS_SUCCESS, context::Context};
 const MAX_CYCLES: u64 = 70_000_000;
 
 const TYPE: u8 = 1;
-const CLASS_TYPE_CODE_HASH: [u8; 32] = [
-    9, 91, 140, 11, 78, 81, 164, 95, 149, 58, 205, 31, 205, 30, 57, 72, 159, 38, 117, 180, 188,
-    148, 231, 175, 39, 187, 56, 149, 135, 144, 227, 252,
-];
 
 // error numbers
 const TYPE_ARGS_INVALID: i8 = 7;
@@ -39,6 +36,35 @@ const LOCKED_NFT_CANNOT_ADD_EXT_INFO: i8 = 37;
 const LOCKED_NFT_CANNOT_DESTROY: i8 = 38;
 const LOCKED_NFT_CANNOT_UPDATE_CHARACTERISTIC: i8 = 39;
 const GROUP_INPUT_WITNESS_NONE_ERROR: i8 = 40;
+const NFT_CHARACTERISTIC_NOT_ZERO: i8 = 41;
+const NFT_REVEAL_SEED_INVALID: i8 = 42;
+const NFT_REVEAL_CHARACTERISTIC_ERROR: i8 = 43;
+const NFT_CHARACTERISTIC_IMMUTABLE: i8 = 44;
+const NFT_CHARACTERISTIC_NOT_INCREASED: i8 = 45;
+const NFT_CHARACTERISTIC_NOT_DECREASED: i8 = 46;
+const NFT_CHARACTERISTIC_OUT_OF_RANGE: i8 = 47;
+const NFT_CHARACTERISTIC_ISSUER_ONLY: i8 = 48;
+const NFT_EXT_INFO_RECORD_INVALID: i8 = 49;
+const NFT_EXT_INFO_SIGNER_MISSING: i8 = 50;
+const LOCK_POLICY_VIOLATED: i8 = 51;
+const CLASS_CELLS_COUNT_ERROR: i8 = 14;
+const ACTION_DECLARATION_INVALID: i8 = 53;
+const ACTION_NOT_MATCHED: i8 = 54;
+const NFT_MIGRATION_INVALID: i8 = 55;
+const NFT_RECIPE_NOT_SATISFIED: i8 = 75;
+const NFT_PROVENANCE_INVALID: i8 = 78;
+const NFT_TRANSFER_COOLDOWN_NOT_PASSED: i8 = 79;
+
+const REVEAL_SEED: &[u8] = b"nft-reveal-seed";
+
+// declared actions
+const DESTROY_ACTION: u8 = 2;
+const REVEAL_ACTION: u8 = 3;
+const MIGRATE_ACTION: u8 = 4;
+
+// ext info records: uri "ipfs://x" and attribute "level" = 0x03
+const URI_RECORD: &str = "010008697066733a2f2f78";
+const ATTRIBUTE_RECORD: &str = "03000800056c6576656c03";
 
 #[derive(PartialEq, Eq, Clone, Copy)]
 enum DestroyCase {
@@ -57,6 +83,13 @@ enum UpdateCase {
     UpdateCharacteristic,
     UpdateStateWithIssuer,
     UpdateStateWithClass,
+    Reveal,
+    CharacteristicPolicy,
+    PruneExtInfoWithIssuer,
+    RewriteExtInfoWithClass,
+    AddExtInfoRecords,
+    LockPolicy,
+    Migrate,
 }
 
 #[derive(PartialEq, Eq)]
@@ -98,6 +131,44 @@ enum NftError {
     UpdateStateWithoutClass,
     UpdateStateWithOtherClass,
     GroupInputWitnessNoneError,
+    NFTCharacteristicNotZero,
+    NFTRevealSeedInvalid,
+    NFTRevealCharacteristicError,
+    NFTCharacteristicImmutable,
+    NFTCharacteristicNotIncreased,
+    NFTCharacteristicNotDecreased,
+    NFTCharacteristicOutOfRange,
+    NFTCharacteristicIssuerOnly,
+    NFTExtInfoRecordInvalid,
+    NFTExtInfoSignerMissing,
+    LockPolicyViolated,
+    ActionDeclarationInvalid,
+    ActionNotMatched,
+    NFTMigrationInvalid,
+}
+
+fn blake2b_256(data: &[u8]) -> [u8; 32] {
+    let mut blake2b = Blake2bBuilder::new(32)
+        .personal(b"ckb-default-hash")
+        .build();
+    blake2b.update(data);
+    let mut ret = [0; 32];
+    blake2b.finalize(&mut ret);
+    ret
+}
+
+fn declared_witness(declaration: Vec<u8>) -> Bytes {
+    WitnessArgs::new_builder()
+        .lock(Some(Bytes::from(vec![0u8; 65])).pack())
+        .input_type(Some(Bytes::from(declaration)).pack())
+        .build()
+        .as_bytes()
+}
+
+fn reveal_characteristic(seed: &[u8], token_id: u32) -> Vec<u8> {
+    let mut data = seed.to_vec();
+    data.extend_from_slice(&token_id.to_be_bytes());
+    blake2b_256(&data)[0..8].to_vec()
 }
 
 fn create_test_context(action: Action, nft_error: NftError) -> (Context, TransactionView) {
@@ -253,13 +324,46 @@ fn create_test_context(action: Action, nft_error: NftError) -> (Context, Transac
         .args(Bytes::copy_from_slice(&class_type_args[..]).pack())
         .hash_type(Byte::new(TYPE))
         .build();
+    let class_aggron_data = match action {
+        Action::Update(UpdateCase::Reveal) => Bytes::from(
+            hex::decode(format!(
+                "010000000f00000005000001550002666600038989890023020020{}",
+                hex::encode(blake2b_256(REVEAL_SEED))
+            ))
+            .unwrap(),
+        ),
+        // byte0: increase-only, byte1: decrease-only, byte2: free in [0x00, 0x50],
+        // byte3: issuer-only, byte4: immutable and the others: free
+        Action::Update(UpdateCase::CharacteristicPolicy) => Bytes::from(
+            hex::decode(
+                "010000000f0000000500000155000266660003898989001b0300180100ff0200ff0300500400ff0000ff0300ff0300ff0300ff",
+            )
+            .unwrap(),
+        ),
+        Action::Update(UpdateCase::AddExtInfoRecords) | Action::Update(UpdateCase::Migrate) => {
+            Bytes::from(hex::decode("010000000f00000005000001550002666600038989890000").unwrap())
+        }
+        // the lock policy record: <mode: u8> + <code_hash: [u8; 32]>
+        Action::Update(UpdateCase::LockPolicy) => Bytes::from(
+            hex::decode(format!(
+                "010000000f00000005000001550002666600038989890024040021{}{}",
+                match nft_error {
+                    NftError::LockPolicyViolated => "01",
+                    _ => "00",
+                },
+                hex::encode(lock_script.code_hash().as_slice())
+            ))
+            .unwrap(),
+        ),
+        _ => Bytes::from("0x"),
+    };
     let class_cell_dep_aggron_out_point = context.create_cell(
         CellOutput::new_builder()
             .capacity(2000u64.pack())
             .lock(lock_script.clone())
             .type_(Some(class_aggron_type_script.clone()).pack())
             .build(),
-        Bytes::from("0x"),
+        class_aggron_data,
     );
     let class_cell_aggron_dep = CellDep::new_builder()
         .out_point(class_cell_dep_aggron_out_point.clone())
@@ -271,6 +375,22 @@ fn create_test_context(action: Action, nft_error: NftError) -> (Context, Transac
             UpdateCase::UpdateStateWithIssuer | UpdateCase::UpdateStateWithClass => {
                 Bytes::from(hex::decode("0000000000000000000303").unwrap())
             }
+            UpdateCase::CharacteristicPolicy => {
+                Bytes::from(hex::decode("0110101010101010100000").unwrap())
+            }
+            UpdateCase::PruneExtInfoWithIssuer | UpdateCase::RewriteExtInfoWithClass => {
+                Bytes::from(hex::decode("00000000000000000000000002889900021234").unwrap())
+            }
+            UpdateCase::AddExtInfoRecords => {
+                Bytes::from(hex::decode(format!("0100000000000000000000{}", URI_RECORD)).unwrap())
+            }
+            UpdateCase::LockPolicy => Bytes::from(hex::decode("0100000000000000000000").unwrap()),
+            UpdateCase::Reveal => match nft_error {
+                NftError::NFTCharacteristicNotZero => {
+                    Bytes::from(hex::decode("0011223344556677880800").unwrap())
+                }
+                _ => Bytes::from(hex::decode("0000000000000000000800").unwrap()),
+            },
             _ => match nft_error {
                 NftError::NFTCharacteristicNotSame => {
                     Bytes::from(hex::decode("0000000000000000000800").unwrap())
@@ -392,14 +512,18 @@ fn create_test_context(action: Action, nft_error: NftError) -> (Context, Transac
                 NftError::NoError => vec![nft_input, another_nft_input],
                 _ => vec![nft_input],
             },
-            UpdateCase::UpdateStateWithIssuer => match nft_error {
-                NftError::UpdateStateWithOtherIssuer => vec![another_issuer_input, nft_input],
-                _ => vec![issuer_input, nft_input],
-            },
-            UpdateCase::UpdateStateWithClass => match nft_error {
-                NftError::UpdateStateWithOtherClass => vec![another_class_input, nft_input],
-                _ => vec![class_input_without_type, nft_input],
-            },
+            UpdateCase::UpdateStateWithIssuer | UpdateCase::PruneExtInfoWithIssuer => {
+                match nft_error {
+                    NftError::UpdateStateWithOtherIssuer => vec![another_issuer_input, nft_input],
+                    _ => vec![issuer_input, nft_input],
+                }
+            }
+            UpdateCase::UpdateStateWithClass | UpdateCase::RewriteExtInfoWithClass => {
+                match nft_error {
+                    NftError::UpdateStateWithOtherClass => vec![another_class_input, nft_input],
+                    _ => vec![class_input_without_type, nft_input],
+                }
+            }
             _ => vec![nft_input],
         },
         Action::Destroy(case) => match case {
@@ -422,7 +546,10 @@ fn create_test_context(action: Action, nft_error: NftError) -> (Context, Transac
                 .lock(another_lock_script.clone())
                 .type_(Some(nft_type_script.clone()).pack())
                 .build()],
-            UpdateCase::UpdateStateWithIssuer | UpdateCase::UpdateStateWithClass => vec![
+            UpdateCase::UpdateStateWithIssuer
+            | UpdateCase::UpdateStateWithClass
+            | UpdateCase::PruneExtInfoWithIssuer
+            | UpdateCase::RewriteExtInfoWithClass => vec![
                 CellOutput::new_builder()
                     .capacity(500u64.pack())
                     .lock(lock_script.clone())
@@ -554,6 +681,40 @@ fn create_test_context(action: Action, nft_error: NftError) -> (Context, Transac
             (UpdateCase::UpdateCharacteristic, NftError::NoError) => {
                 vec![Bytes::from(hex::decode("0022334455667788990000").unwrap())]
             }
+            (UpdateCase::Migrate, _) => vec![Bytes::from(
+                hex::decode(format!("0100000000000000000000{}", URI_RECORD)).unwrap(),
+            )],
+            (UpdateCase::LockPolicy, _) => {
+                vec![Bytes::from(hex::decode("0100000000000000000001").unwrap())]
+            }
+            (UpdateCase::AddExtInfoRecords, NftError::NFTExtInfoRecordInvalid) => {
+                vec![Bytes::from(
+                    hex::decode(format!("0100000000000000000000{}0300080005", URI_RECORD)).unwrap(),
+                )]
+            }
+            (UpdateCase::AddExtInfoRecords, _) => {
+                let signer = match nft_error {
+                    NftError::NFTExtInfoSignerMissing => another_lock_script.calc_script_hash(),
+                    _ => lock_script.calc_script_hash(),
+                };
+                vec![Bytes::from(
+                    hex::decode(format!(
+                        "0100000000000000000000{}{}040022{}6767",
+                        URI_RECORD,
+                        ATTRIBUTE_RECORD,
+                        hex::encode(signer.as_slice())
+                    ))
+                    .unwrap(),
+                )]
+            }
+            (UpdateCase::PruneExtInfoWithIssuer, _) => vec![
+                Bytes::new(),
+                Bytes::from(hex::decode("000000000000000000000000028899").unwrap()),
+            ],
+            (UpdateCase::RewriteExtInfoWithClass, _) => vec![
+                Bytes::new(),
+                Bytes::from(hex::decode("00000000000000000000000002556600021234").unwrap()),
+            ],
             (UpdateCase::UpdateStateWithIssuer, _) => {
                 vec![
                     Bytes::new(),
@@ -566,6 +727,38 @@ fn create_test_context(action: Action, nft_error: NftError) -> (Context, Transac
                     Bytes::from(hex::decode("0000000000000000000300").unwrap()),
                 ]
             }
+            (UpdateCase::CharacteristicPolicy, NftError::NFTCharacteristicImmutable) => {
+                vec![Bytes::from(hex::decode("0110101010111010100000").unwrap())]
+            }
+            (UpdateCase::CharacteristicPolicy, NftError::NFTCharacteristicNotIncreased) => {
+                vec![Bytes::from(hex::decode("0105101010101010100000").unwrap())]
+            }
+            (UpdateCase::CharacteristicPolicy, NftError::NFTCharacteristicNotDecreased) => {
+                vec![Bytes::from(hex::decode("0110201010101010100000").unwrap())]
+            }
+            (UpdateCase::CharacteristicPolicy, NftError::NFTCharacteristicOutOfRange) => {
+                vec![Bytes::from(hex::decode("0110106010101010100000").unwrap())]
+            }
+            (UpdateCase::CharacteristicPolicy, NftError::NFTCharacteristicIssuerOnly) => {
+                vec![Bytes::from(hex::decode("0110101020101010100000").unwrap())]
+            }
+            (UpdateCase::CharacteristicPolicy, _) => {
+                vec![Bytes::from(hex::decode("01200540101099aa100000").unwrap())]
+            }
+            (UpdateCase::Reveal, NftError::NFTRevealCharacteristicError) => vec![Bytes::from(
+                hex::decode(format!(
+                    "00{}0800",
+                    hex::encode(reveal_characteristic(REVEAL_SEED, 12))
+                ))
+                .unwrap(),
+            )],
+            (UpdateCase::Reveal, _) => vec![Bytes::from(
+                hex::decode(format!(
+                    "00{}0800",
+                    hex::encode(reveal_characteristic(REVEAL_SEED, 11))
+                ))
+                .unwrap(),
+            )],
             (UpdateCase::UpdateCharacteristic, NftError::NFTCharacteristicNotSame) => {
                 vec![Bytes::from(hex::decode("0022334455667788990800").unwrap())]
             }
@@ -641,6 +834,25 @@ fn create_test_context(action: Action, nft_error: NftError) -> (Context, Transac
         NftError::GroupInputWitnessNoneError => {
             witnesses.push(Bytes::from("12345678"))
         }
+        // The lock policy replaces the anyone-can-pay check with the witness
+        NftError::NoError if action == Action::Update(UpdateCase::LockPolicy) => {
+            witnesses.push(Bytes::from("12345678"))
+        }
+        // The declaration in the input_type: <action: u8> + <params>
+        _ if action == Action::Update(UpdateCase::Reveal) => {
+            let seed = match nft_error {
+                NftError::NFTRevealSeedInvalid => b"another-seed".to_vec(),
+                _ => REVEAL_SEED.to_vec(),
+            };
+            let mut declaration = vec![REVEAL_ACTION];
+            declaration.extend_from_slice(&seed);
+            witnesses.push(declared_witness(declaration))
+        }
+        _ if action == Action::Update(UpdateCase::Migrate) => {
+            witnesses.push(declared_witness(vec![MIGRATE_ACTION]))
+        }
+        NftError::ActionNotMatched => witnesses.push(declared_witness(vec![DESTROY_ACTION])),
+        NftError::ActionDeclarationInvalid => witnesses.push(declared_witness(vec![9])),
         _ => {
             witnesses.push(Bytes::from(hex::decode("5500000010000000550000005500000041000000b69c542c0ee6c4b6d8350514d876ea7d8ef563e406253e959289457204447d2c4eb4e4a993073f5e76d244d2f93f7c108652e3295a9c8d72c12477e095026b9500").unwrap()))
         }
@@ -668,14 +880,32 @@ fn create_test_context(action: Action, nft_error: NftError) -> (Context, Transac
             _ => vec![lock_script_dep, class_type_script_dep, nft_type_script_dep],
         },
         Action::Update(case) => match case {
-            UpdateCase::UpdateStateWithIssuer => match nft_error {
-                NftError::UpdateStateWithoutIssuer => vec![lock_script_dep, nft_type_script_dep],
-                _ => vec![issuer_cell_dep, lock_script_dep, nft_type_script_dep],
-            },
-            UpdateCase::UpdateStateWithClass => match nft_error {
-                NftError::UpdateStateWithoutClass => vec![lock_script_dep, nft_type_script_dep],
+            UpdateCase::UpdateStateWithIssuer | UpdateCase::PruneExtInfoWithIssuer => {
+                match nft_error {
+                    NftError::UpdateStateWithoutIssuer => {
+                        vec![lock_script_dep, nft_type_script_dep]
+                    }
+                    _ => vec![issuer_cell_dep, lock_script_dep, nft_type_script_dep],
+                }
+            }
+            UpdateCase::UpdateStateWithClass | UpdateCase::RewriteExtInfoWithClass => {
+                match nft_error {
+                    NftError::UpdateStateWithoutClass => vec![lock_script_dep, nft_type_script_dep],
+                    _ => vec![class_cell_aggron_dep, lock_script_dep, nft_type_script_dep],
+                }
+            }
+            UpdateCase::Reveal => vec![class_cell_aggron_dep, lock_script_dep, nft_type_script_dep],
+            UpdateCase::Migrate => match nft_error {
+                NftError::NFTMigrationInvalid => {
+                    vec![lock_script_dep, class_cell_aggron_dep, nft_type_script_dep]
+                }
                 _ => vec![class_cell_aggron_dep, lock_script_dep, nft_type_script_dep],
             },
+            UpdateCase::CharacteristicPolicy
+            | UpdateCase::AddExtInfoRecords
+            | UpdateCase::LockPolicy => {
+                vec![lock_script_dep, class_cell_aggron_dep, nft_type_script_dep]
+            }
             _ => vec![lock_script_dep, nft_type_script_dep],
         }, // _ => vec![lock_script_dep, class_type_script_dep, nft_type_script_dep],
     };
@@ -770,6 +1000,21 @@ fn test_update_characteristic_nft_cell_success() {
     println!("consume cycles: {}", cycles);
 }
 
+#[test]
+fn test_update_characteristic_with_policies_success() {
+    let (mut context, tx) = create_test_context(
+        Action::Update(UpdateCase::CharacteristicPolicy),
+        NftError::NoError,
+    );
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
 #[test]
 fn test_update_nft_state_with_issuer_success() {
     let (mut context, tx) = create_test_context(
@@ -800,6 +1045,64 @@ fn test_update_nft_state_with_class_success() {
     println!("consume cycles: {}", cycles);
 }
 
+#[test]
+fn test_reveal_nft_characteristic_success() {
+    let (mut context, tx) =
+        create_test_context(Action::Update(UpdateCase::Reveal), NftError::NoError);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_prune_nft_ext_info_with_issuer_success() {
+    let (mut context, tx) = create_test_context(
+        Action::Update(UpdateCase::PruneExtInfoWithIssuer),
+        NftError::NoError,
+    );
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_rewrite_nft_ext_info_with_class_success() {
+    let (mut context, tx) = create_test_context(
+        Action::Update(UpdateCase::RewriteExtInfoWithClass),
+        NftError::NoError,
+    );
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_add_nft_ext_info_records_success() {
+    let (mut context, tx) = create_test_context(
+        Action::Update(UpdateCase::AddExtInfoRecords),
+        NftError::NoError,
+    );
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
 #[test]
 fn test_destroy_nft_cell_with_default_success() {
     let (mut context, tx) =
@@ -1198,3 +1501,1234 @@ fn test_nft_type_args_invalid_error() {
     let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
     assert_script_error(err, TYPE_ARGS_INVALID);
 }
+
+#[test]
+fn test_reveal_nft_characteristic_not_zero_error() {
+    let (mut context, tx) = create_test_context(
+        Action::Update(UpdateCase::Reveal),
+        NftError::NFTCharacteristicNotZero,
+    );
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_CHARACTERISTIC_NOT_ZERO);
+}
+
+#[test]
+fn test_reveal_nft_seed_invalid_error() {
+    let (mut context, tx) = create_test_context(
+        Action::Update(UpdateCase::Reveal),
+        NftError::NFTRevealSeedInvalid,
+    );
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_REVEAL_SEED_INVALID);
+}
+
+#[test]
+fn test_reveal_nft_characteristic_error() {
+    let (mut context, tx) = create_test_context(
+        Action::Update(UpdateCase::Reveal),
+        NftError::NFTRevealCharacteristicError,
+    );
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_REVEAL_CHARACTERISTIC_ERROR);
+}
+
+#[test]
+fn test_update_nft_characteristic_immutable_error() {
+    let (mut context, tx) = create_test_context(
+        Action::Update(UpdateCase::CharacteristicPolicy),
+        NftError::NFTCharacteristicImmutable,
+    );
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_CHARACTERISTIC_IMMUTABLE);
+}
+
+#[test]
+fn test_update_nft_characteristic_not_increased_error() {
+    let (mut context, tx) = create_test_context(
+        Action::Update(UpdateCase::CharacteristicPolicy),
+        NftError::NFTCharacteristicNotIncreased,
+    );
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_CHARACTERISTIC_NOT_INCREASED);
+}
+
+#[test]
+fn test_update_nft_characteristic_not_decreased_error() {
+    let (mut context, tx) = create_test_context(
+        Action::Update(UpdateCase::CharacteristicPolicy),
+        NftError::NFTCharacteristicNotDecreased,
+    );
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_CHARACTERISTIC_NOT_DECREASED);
+}
+
+#[test]
+fn test_update_nft_characteristic_out_of_range_error() {
+    let (mut context, tx) = create_test_context(
+        Action::Update(UpdateCase::CharacteristicPolicy),
+        NftError::NFTCharacteristicOutOfRange,
+    );
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_CHARACTERISTIC_OUT_OF_RANGE);
+}
+
+#[test]
+fn test_update_nft_characteristic_issuer_only_error() {
+    let (mut context, tx) = create_test_context(
+        Action::Update(UpdateCase::CharacteristicPolicy),
+        NftError::NFTCharacteristicIssuerOnly,
+    );
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_CHARACTERISTIC_ISSUER_ONLY);
+}
+
+#[test]
+fn test_prune_nft_ext_info_without_issuer_error() {
+    let (mut context, tx) = create_test_context(
+        Action::Update(UpdateCase::PruneExtInfoWithIssuer),
+        NftError::UpdateStateWithoutIssuer,
+    );
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_EXT_INFO_LEN_ERROR);
+}
+
+#[test]
+fn test_rewrite_nft_ext_info_with_other_class_error() {
+    let (mut context, tx) = create_test_context(
+        Action::Update(UpdateCase::RewriteExtInfoWithClass),
+        NftError::UpdateStateWithOtherClass,
+    );
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_EXT_INFO_CANNOT_MODIFY);
+}
+
+#[test]
+fn test_add_nft_ext_info_truncated_record_error() {
+    let (mut context, tx) = create_test_context(
+        Action::Update(UpdateCase::AddExtInfoRecords),
+        NftError::NFTExtInfoRecordInvalid,
+    );
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_EXT_INFO_RECORD_INVALID);
+}
+
+#[test]
+fn test_add_nft_ext_info_annotation_without_signer_error() {
+    let (mut context, tx) = create_test_context(
+        Action::Update(UpdateCase::AddExtInfoRecords),
+        NftError::NFTExtInfoSignerMissing,
+    );
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_EXT_INFO_SIGNER_MISSING);
+}
+
+#[test]
+fn test_update_nft_with_lock_policy_and_witness_none_success() {
+    let (mut context, tx) =
+        create_test_context(Action::Update(UpdateCase::LockPolicy), NftError::NoError);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_update_nft_lock_policy_violated_error() {
+    let (mut context, tx) = create_test_context(
+        Action::Update(UpdateCase::LockPolicy),
+        NftError::LockPolicyViolated,
+    );
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, LOCK_POLICY_VIOLATED);
+}
+
+#[test]
+fn test_migrate_nft_with_class_success() {
+    let (mut context, tx) =
+        create_test_context(Action::Update(UpdateCase::Migrate), NftError::NoError);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_migrate_nft_without_approval_error() {
+    let (mut context, tx) = create_test_context(
+        Action::Update(UpdateCase::Migrate),
+        NftError::NFTMigrationInvalid,
+    );
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_MIGRATION_INVALID);
+}
+
+#[test]
+fn test_update_nft_with_declared_destroy_error() {
+    let (mut context, tx) = create_test_context(
+        Action::Update(UpdateCase::Claim),
+        NftError::ActionNotMatched,
+    );
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ACTION_NOT_MATCHED);
+}
+
+#[test]
+fn test_update_nft_with_unknown_declared_action_error() {
+    let (mut context, tx) = create_test_context(
+        Action::Update(UpdateCase::Claim),
+        NftError::ActionDeclarationInvalid,
+    );
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ACTION_DECLARATION_INVALID);
+}
+
+// The benchmark of the nft-type script which queries the transaction index many times
+const BENCHMARK_NFT_COUNT: u32 = 100;
+// The max cycles of a block
+const BENCHMARK_MAX_CYCLES: u64 = 3_500_000_000;
+
+fn create_batch_claim_context(count: u32) -> (Context, TransactionView) {
+    let mut context = Context::default();
+
+    let nft_bin: Bytes = Loader::default().load_binary("nft-type");
+    let nft_out_point = context.deploy_cell(nft_bin);
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+
+    let lock_script = context
+        .build_script(&always_success_out_point, Default::default())
+        .expect("script");
+    let class_type_args = hex::decode("157a3633c3477d84b604a25e5fca5ca681762c1000000008").unwrap();
+
+    let mut inputs = vec![];
+    let mut outputs = vec![];
+    let mut outputs_data = vec![];
+    for token_id in 0..count {
+        let mut nft_type_args = class_type_args.clone();
+        nft_type_args.extend_from_slice(&token_id.to_be_bytes());
+        let nft_type_script = context
+            .build_script(&nft_out_point, Bytes::from(nft_type_args))
+            .expect("script");
+        let nft_cell = CellOutput::new_builder()
+            .capacity(500u64.pack())
+            .lock(lock_script.clone())
+            .type_(Some(nft_type_script).pack())
+            .build();
+        let nft_input_out_point = context.create_cell(
+            nft_cell.clone(),
+            Bytes::from(hex::decode("0000000000000000000000").unwrap()),
+        );
+        inputs.push(
+            CellInput::new_builder()
+                .previous_output(nft_input_out_point)
+                .build(),
+        );
+        outputs.push(nft_cell);
+        outputs_data.push(Bytes::from(hex::decode("0000000000000000000001").unwrap()));
+    }
+
+    let mut witnesses = vec![Bytes::from(hex::decode("5500000010000000550000005500000041000000b69c542c0ee6c4b6d8350514d876ea7d8ef563e406253e959289457204447d2c4eb4e4a993073f5e76d244d2f93f7c108652e3295a9c8d72c12477e095026b9500").unwrap())];
+    for _ in 1..count {
+        witnesses.push(Bytes::from("0x"))
+    }
+
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(always_success_out_point)
+                .build(),
+        )
+        .cell_dep(CellDep::new_builder().out_point(nft_out_point).build())
+        .witnesses(witnesses.pack())
+        .build();
+    (context, tx)
+}
+
+#[test]
+fn test_batch_claim_nft_cells_cycles() {
+    let (mut context, tx) = create_batch_claim_context(BENCHMARK_NFT_COUNT);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, BENCHMARK_MAX_CYCLES)
+        .expect("pass verification");
+    println!(
+        "batch claim {} nfts consume cycles: {}, {} per nft",
+        BENCHMARK_NFT_COUNT,
+        cycles,
+        cycles / BENCHMARK_NFT_COUNT as u64
+    );
+}
+
+// The nfts minted in a batch share the transaction index, so the cycles per nft of the batch mint
+// must stay close to the cycles of minting a single nft instead of growing with the batch size
+const BATCH_MINT_MAX_CYCLES_RATIO: u64 = 3;
+
+fn create_batch_mint_context(count: u32) -> (Context, TransactionView) {
+    let mut context = Context::default();
+
+    let nft_bin: Bytes = Loader::default().load_binary("nft-type");
+    let nft_out_point = context.deploy_cell(nft_bin);
+    let class_bin: Bytes = Loader::default().load_binary("class-type");
+    let class_out_point = context.deploy_cell(class_bin);
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+
+    let lock_script = context
+        .build_script(&always_success_out_point, Default::default())
+        .expect("script");
+    let class_type_args = hex::decode("157a3633c3477d84b604a25e5fca5ca681762c1000000008").unwrap();
+    let class_type_script = Script::new_builder()
+        .code_hash(CLASS_TYPE_CODE_HASH.pack())
+        .args(Bytes::from(class_type_args.clone()).pack())
+        .hash_type(Byte::new(TYPE))
+        .build();
+
+    // The class without the total limit: <version: 0> + <total: 0> + <issued> + <configure: 0>
+    let class_data = |issued: u32| {
+        let mut data = hex::decode("0000000000").unwrap();
+        data.extend_from_slice(&issued.to_be_bytes());
+        data.extend(hex::decode("00000155000266660003898989").unwrap());
+        Bytes::from(data)
+    };
+    let class_cell = CellOutput::new_builder()
+        .capacity(2000u64.pack())
+        .lock(lock_script.clone())
+        .type_(Some(class_type_script).pack())
+        .build();
+    let class_input_out_point = context.create_cell(class_cell.clone(), class_data(0));
+
+    let mut outputs = vec![class_cell];
+    let mut outputs_data = vec![class_data(count)];
+    for token_id in 0..count {
+        let mut nft_type_args = class_type_args.clone();
+        nft_type_args.extend_from_slice(&token_id.to_be_bytes());
+        let nft_type_script = context
+            .build_script(&nft_out_point, Bytes::from(nft_type_args))
+            .expect("script");
+        outputs.push(
+            CellOutput::new_builder()
+                .capacity(500u64.pack())
+                .lock(lock_script.clone())
+                .type_(Some(nft_type_script).pack())
+                .build(),
+        );
+        outputs_data.push(Bytes::from(hex::decode("0000000000000000000000").unwrap()));
+    }
+
+    let tx = TransactionBuilder::default()
+        .input(
+            CellInput::new_builder()
+                .previous_output(class_input_out_point)
+                .build(),
+        )
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(always_success_out_point)
+                .build(),
+        )
+        .cell_dep(CellDep::new_builder().out_point(class_out_point).build())
+        .cell_dep(CellDep::new_builder().out_point(nft_out_point).build())
+        .witness(
+            WitnessArgs::new_builder()
+                .lock(Some(Bytes::new()).pack())
+                .build()
+                .as_bytes()
+                .pack(),
+        )
+        .build();
+    (context, tx)
+}
+
+// The batch mint spends the class cell under the pinned class type, see CLASS_TYPE_CODE_HASH
+#[test]
+#[ignore]
+fn test_batch_mint_nft_cells_cycles() {
+    let verify = |count| {
+        let (mut context, tx) = create_batch_mint_context(count);
+        let tx = context.complete_tx(tx);
+        context
+            .verify_tx(&tx, BENCHMARK_MAX_CYCLES)
+            .expect("pass verification")
+    };
+    let single_cycles = verify(1);
+    let batch_cycles = verify(BENCHMARK_NFT_COUNT);
+    let cycles_per_nft = batch_cycles / BENCHMARK_NFT_COUNT as u64;
+    println!(
+        "mint 1 nft consume cycles: {}, batch mint {} nfts: {}, {} per nft",
+        single_cycles, BENCHMARK_NFT_COUNT, batch_cycles, cycles_per_nft
+    );
+    assert!(cycles_per_nft < single_cycles * BATCH_MINT_MAX_CYCLES_RATIO);
+}
+
+// The nft with the large ext info should be as cheap to transfer as the one without ext info
+const LARGE_EXT_INFO_LEN: usize = 4096;
+const LARGE_EXT_INFO_MAX_EXTRA_CYCLES: u64 = 200_000;
+
+fn create_transfer_context(ext_info_len: usize) -> (Context, TransactionView) {
+    let mut context = Context::default();
+
+    let nft_bin: Bytes = Loader::default().load_binary("nft-type");
+    let nft_out_point = context.deploy_cell(nft_bin);
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+
+    let lock_script = context
+        .build_script(&always_success_out_point, Default::default())
+        .expect("script");
+    let another_lock_script = context
+        .build_script(
+            &always_success_out_point,
+            Bytes::from(hex::decode("12").unwrap()),
+        )
+        .expect("script");
+    let nft_type_script = context
+        .build_script(
+            &nft_out_point,
+            Bytes::from(
+                hex::decode("157a3633c3477d84b604a25e5fca5ca681762c10000000080000000b").unwrap(),
+            ),
+        )
+        .expect("script");
+
+    let mut nft_data = hex::decode("0000000000000000000000").unwrap();
+    nft_data.extend_from_slice(&vec![0xab; ext_info_len]);
+    let nft_input_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(50000u64.pack())
+            .lock(lock_script)
+            .type_(Some(nft_type_script.clone()).pack())
+            .build(),
+        Bytes::from(nft_data.clone()),
+    );
+
+    let tx = TransactionBuilder::default()
+        .input(
+            CellInput::new_builder()
+                .previous_output(nft_input_out_point)
+                .build(),
+        )
+        .output(
+            CellOutput::new_builder()
+                .capacity(50000u64.pack())
+                .lock(another_lock_script)
+                .type_(Some(nft_type_script).pack())
+                .build(),
+        )
+        .output_data(Bytes::from(nft_data).pack())
+        .cell_dep(CellDep::new_builder().out_point(always_success_out_point).build())
+        .cell_dep(CellDep::new_builder().out_point(nft_out_point).build())
+        .witness(Bytes::from(hex::decode("5500000010000000550000005500000041000000b69c542c0ee6c4b6d8350514d876ea7d8ef563e406253e959289457204447d2c4eb4e4a993073f5e76d244d2f93f7c108652e3295a9c8d72c12477e095026b9500").unwrap()).pack())
+        .build();
+    (context, tx)
+}
+
+#[test]
+fn test_transfer_nft_with_large_ext_info_cycles() {
+    let verify = |ext_info_len| {
+        let (mut context, tx) = create_transfer_context(ext_info_len);
+        let tx = context.complete_tx(tx);
+        context
+            .verify_tx(&tx, MAX_CYCLES)
+            .expect("pass verification")
+    };
+    let cycles = verify(0);
+    let large_ext_info_cycles = verify(LARGE_EXT_INFO_LEN);
+    println!(
+        "transfer nft consume cycles: {}, with {} bytes ext info: {}",
+        cycles, LARGE_EXT_INFO_LEN, large_ext_info_cycles
+    );
+    assert!(large_ext_info_cycles - cycles < LARGE_EXT_INFO_MAX_EXTRA_CYCLES);
+}
+
+// The recipe of the crafted class: two nfts of the class 1 and one nft of the class 2
+const INGREDIENT_CLASS_ARGS: [&str; 2] = [
+    "157a3633c3477d84b604a25e5fca5ca681762c1000000001",
+    "157a3633c3477d84b604a25e5fca5ca681762c1000000002",
+];
+const CRAFTED_CLASS_ARGS: &str = "157a3633c3477d84b604a25e5fca5ca681762c1000000009";
+// Another crafted class with the same recipe
+const OTHER_CRAFTED_CLASS_ARGS: &str = "157a3633c3477d84b604a25e5fca5ca681762c100000000b";
+
+#[derive(PartialEq, Clone, Copy)]
+enum CraftCase {
+    Craft,
+    CraftWithIndestructibleIngredients,
+    IngredientMissing,
+    TokenIdNotDestroyed,
+    TokenIdIssued,
+    IngredientsShared,
+    RecipeMissing,
+}
+
+fn create_crafting_context(case: CraftCase) -> (Context, TransactionView) {
+    let mut context = Context::default();
+
+    let nft_bin: Bytes = Loader::default().load_binary("nft-type");
+    let nft_out_point = context.deploy_cell(nft_bin);
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+
+    let lock_script = context
+        .build_script(&always_success_out_point, Bytes::from("crafter"))
+        .expect("script");
+    let mut nft_type = |class_args: &str, token_id: u32| {
+        let mut nft_type_args = hex::decode(class_args).unwrap();
+        nft_type_args.extend_from_slice(&token_id.to_be_bytes());
+        context
+            .build_script(&nft_out_point, Bytes::from(nft_type_args))
+            .expect("script")
+    };
+    let mut ingredient_types = vec![
+        nft_type(INGREDIENT_CLASS_ARGS[0], 3),
+        nft_type(INGREDIENT_CLASS_ARGS[0], 4),
+        nft_type(INGREDIENT_CLASS_ARGS[1], 7),
+    ];
+    if case == CraftCase::IngredientMissing {
+        ingredient_types.pop();
+    }
+    let crafted_token_id = match case {
+        CraftCase::TokenIdNotDestroyed => 7,
+        _ => 3,
+    };
+    let mut crafted_types = vec![nft_type(CRAFTED_CLASS_ARGS, crafted_token_id)];
+    // The same destroyed ingredients are claimed by the recipes of both crafted classes
+    if case == CraftCase::IngredientsShared {
+        crafted_types.push(nft_type(OTHER_CRAFTED_CLASS_ARGS, crafted_token_id));
+    }
+
+    // The class has issued the token ids 0..5 which the crafted token id must not collide with
+    let issued = match case {
+        CraftCase::TokenIdIssued => 5u32,
+        _ => 0,
+    };
+    // the recipe record: (<class_type_args: [u8; 24]> + <count: u8>) * n
+    let class_data = match case {
+        CraftCase::RecipeMissing => {
+            format!("010000000f{:08x}000001550002666600038989890000", issued)
+        }
+        _ => format!(
+            "010000000f{:08x}000001550002666600038989890035050032{}02{}01",
+            issued, INGREDIENT_CLASS_ARGS[0], INGREDIENT_CLASS_ARGS[1]
+        ),
+    };
+    let mut class_cell_dep = |class_args: &str| {
+        let class_type_script = Script::new_builder()
+            .code_hash(CLASS_TYPE_CODE_HASH.pack())
+            .args(Bytes::from(hex::decode(class_args).unwrap()).pack())
+            .hash_type(Byte::new(TYPE))
+            .build();
+        let out_point = context.create_cell(
+            CellOutput::new_builder()
+                .capacity(2000u64.pack())
+                .lock(lock_script.clone())
+                .type_(Some(class_type_script).pack())
+                .build(),
+            Bytes::from(hex::decode(&class_data).unwrap()),
+        );
+        CellDep::new_builder().out_point(out_point).build()
+    };
+    let mut class_cell_deps = vec![class_cell_dep(CRAFTED_CLASS_ARGS)];
+    if case == CraftCase::IngredientsShared {
+        class_cell_deps.push(class_cell_dep(OTHER_CRAFTED_CLASS_ARGS));
+    }
+
+    // The ingredients disallow destroying before claim but the recipe approves the destroying
+    let ingredient_data = match case {
+        CraftCase::CraftWithIndestructibleIngredients => "0000000000000000004000",
+        _ => "0000000000000000000000",
+    };
+    let inputs: Vec<CellInput> = ingredient_types
+        .into_iter()
+        .map(|ingredient_type| {
+            let out_point = context.create_cell(
+                CellOutput::new_builder()
+                    .capacity(500u64.pack())
+                    .lock(lock_script.clone())
+                    .type_(Some(ingredient_type).pack())
+                    .build(),
+                Bytes::from(hex::decode(ingredient_data).unwrap()),
+            );
+            CellInput::new_builder().previous_output(out_point).build()
+        })
+        .collect();
+    let outputs: Vec<CellOutput> = crafted_types
+        .into_iter()
+        .map(|crafted_type| {
+            CellOutput::new_builder()
+                .capacity(500u64.pack())
+                .lock(lock_script.clone())
+                .type_(Some(crafted_type).pack())
+                .build()
+        })
+        .collect();
+    let outputs_data: Vec<Bytes> = outputs
+        .iter()
+        .map(|_| Bytes::from(hex::decode("0100000000000000000000").unwrap()))
+        .collect();
+
+    let mut witnesses = vec![WitnessArgs::new_builder()
+        .lock(Some(Bytes::new()).pack())
+        .build()
+        .as_bytes()];
+    witnesses.resize(inputs.len(), Bytes::new());
+
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(always_success_out_point)
+                .build(),
+        )
+        .cell_dep(CellDep::new_builder().out_point(nft_out_point).build())
+        .cell_deps(class_cell_deps)
+        .witnesses(witnesses.pack())
+        .build();
+    (context, tx)
+}
+
+#[test]
+fn test_craft_nft_with_recipe_success() {
+    let (mut context, tx) = create_crafting_context(CraftCase::Craft);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_craft_nft_with_indestructible_ingredients_success() {
+    let (mut context, tx) = create_crafting_context(CraftCase::CraftWithIndestructibleIngredients);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_craft_nft_with_ingredient_missing_error() {
+    let (mut context, tx) = create_crafting_context(CraftCase::IngredientMissing);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_RECIPE_NOT_SATISFIED);
+}
+
+#[test]
+fn test_craft_nft_with_token_id_not_destroyed_error() {
+    let (mut context, tx) = create_crafting_context(CraftCase::TokenIdNotDestroyed);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_RECIPE_NOT_SATISFIED);
+}
+
+#[test]
+fn test_craft_nft_with_issued_token_id_error() {
+    let (mut context, tx) = create_crafting_context(CraftCase::TokenIdIssued);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_RECIPE_NOT_SATISFIED);
+}
+
+#[test]
+fn test_craft_nfts_with_shared_ingredients_error() {
+    let (mut context, tx) = create_crafting_context(CraftCase::IngredientsShared);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_RECIPE_NOT_SATISFIED);
+}
+
+#[test]
+fn test_craft_nft_without_recipe_error() {
+    let (mut context, tx) = create_crafting_context(CraftCase::RecipeMissing);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, CLASS_CELLS_COUNT_ERROR);
+}
+
+const PROVENANCE_NFT_TYPE_ARGS: &str = "157a3633c3477d84b604a25e5fca5ca681762c100000000a00000005";
+
+#[derive(PartialEq, Clone, Copy)]
+enum ProvenanceCase {
+    Transfer,
+    TransferWithoutProvenanceUpdate,
+    AddExtInfoWithoutTransfer,
+    ProvenanceUpdatedWithoutTransfer,
+    ProvenanceRecordAppended,
+}
+
+// The provenance record: <kind: 5> + <size: u16> + <transfer_count: u32> + <owners_hash: [u8; 32]>
+fn provenance_record(transfer_count: u32, owners_hash: &[u8; 32]) -> Vec<u8> {
+    let mut record = hex::decode("050024").unwrap();
+    record.extend_from_slice(&transfer_count.to_be_bytes());
+    record.extend_from_slice(owners_hash);
+    record
+}
+
+fn create_provenance_context(case: ProvenanceCase) -> (Context, TransactionView) {
+    let mut context = Context::default();
+
+    let nft_bin: Bytes = Loader::default().load_binary("nft-type");
+    let nft_out_point = context.deploy_cell(nft_bin);
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+
+    let lock_script = context
+        .build_script(&always_success_out_point, Bytes::from("previous-owner"))
+        .expect("script");
+    let another_lock_script = context
+        .build_script(&always_success_out_point, Bytes::from("next-owner"))
+        .expect("script");
+    let nft_type_script = context
+        .build_script(
+            &nft_out_point,
+            Bytes::from(hex::decode(PROVENANCE_NFT_TYPE_ARGS).unwrap()),
+        )
+        .expect("script");
+
+    // The class enables the provenance with the empty record of the kind 7
+    let class_type_script = Script::new_builder()
+        .code_hash(CLASS_TYPE_CODE_HASH.pack())
+        .args(Bytes::from(hex::decode(&PROVENANCE_NFT_TYPE_ARGS[0..48]).unwrap()).pack())
+        .hash_type(Byte::new(TYPE))
+        .build();
+    let class_cell_dep_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(2000u64.pack())
+            .lock(lock_script.clone())
+            .type_(Some(class_type_script).pack())
+            .build(),
+        Bytes::from(hex::decode("010000000f00000005000001550002666600038989890003070000").unwrap()),
+    );
+
+    let owners_hash = [0x5a; 32];
+    let mut input_data = hex::decode("0100000000000000000000").unwrap();
+    input_data.extend(provenance_record(2, &owners_hash));
+
+    let mut transferred_owners = owners_hash.to_vec();
+    transferred_owners.extend_from_slice(&blake2b_256(lock_script.as_slice()));
+    let transferred_record = provenance_record(3, &blake2b_256(&transferred_owners));
+
+    let mut output_data = hex::decode("0100000000000000000000").unwrap();
+    match case {
+        ProvenanceCase::Transfer | ProvenanceCase::ProvenanceUpdatedWithoutTransfer => {
+            output_data.extend(transferred_record.clone())
+        }
+        _ => output_data.extend(provenance_record(2, &owners_hash)),
+    }
+    match case {
+        ProvenanceCase::AddExtInfoWithoutTransfer => {
+            output_data.extend(hex::decode(URI_RECORD).unwrap())
+        }
+        ProvenanceCase::ProvenanceRecordAppended => output_data.extend(transferred_record),
+        _ => (),
+    }
+    let output_lock_script = match case {
+        ProvenanceCase::Transfer | ProvenanceCase::TransferWithoutProvenanceUpdate => {
+            another_lock_script
+        }
+        _ => lock_script.clone(),
+    };
+
+    let nft_input_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(500u64.pack())
+            .lock(lock_script)
+            .type_(Some(nft_type_script.clone()).pack())
+            .build(),
+        Bytes::from(input_data),
+    );
+
+    let tx = TransactionBuilder::default()
+        .input(
+            CellInput::new_builder()
+                .previous_output(nft_input_out_point)
+                .build(),
+        )
+        .output(
+            CellOutput::new_builder()
+                .capacity(500u64.pack())
+                .lock(output_lock_script)
+                .type_(Some(nft_type_script).pack())
+                .build(),
+        )
+        .output_data(Bytes::from(output_data).pack())
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(always_success_out_point)
+                .build(),
+        )
+        .cell_dep(CellDep::new_builder().out_point(nft_out_point).build())
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(class_cell_dep_out_point)
+                .build(),
+        )
+        .witness(
+            WitnessArgs::new_builder()
+                .lock(Some(Bytes::new()).pack())
+                .build()
+                .as_bytes()
+                .pack(),
+        )
+        .build();
+    (context, tx)
+}
+
+#[test]
+fn test_transfer_nft_with_provenance_success() {
+    let (mut context, tx) = create_provenance_context(ProvenanceCase::Transfer);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_add_nft_ext_info_after_provenance_success() {
+    let (mut context, tx) = create_provenance_context(ProvenanceCase::AddExtInfoWithoutTransfer);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_transfer_nft_without_provenance_update_error() {
+    let (mut context, tx) =
+        create_provenance_context(ProvenanceCase::TransferWithoutProvenanceUpdate);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_PROVENANCE_INVALID);
+}
+
+#[test]
+fn test_update_nft_provenance_without_transfer_error() {
+    let (mut context, tx) =
+        create_provenance_context(ProvenanceCase::ProvenanceUpdatedWithoutTransfer);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_PROVENANCE_INVALID);
+}
+
+#[test]
+fn test_append_nft_provenance_record_error() {
+    let (mut context, tx) = create_provenance_context(ProvenanceCase::ProvenanceRecordAppended);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_PROVENANCE_INVALID);
+}
+
+const COOLDOWN_NFT_TYPE_ARGS: &str = "157a3633c3477d84b604a25e5fca5ca681762c100000000b00000005";
+// The transfer cooldown records: <metric: u8> + <period: u64>, 100 blocks or 2 epochs
+const BLOCKS_COOLDOWN_RECORD: &str = "080009000000000000000064";
+const EPOCHS_COOLDOWN_RECORD: &str = "080009010000000000000002";
+// The relative since: the flags 0x80 of the block number and the flags 0xa0 of the epoch
+const SINCE_RELATIVE_BLOCKS: u64 = 0x8000_0000_0000_0000;
+const SINCE_RELATIVE_EPOCHS: u64 = 0xa000_0000_0000_0000;
+// The epoch with the length 1 and the index 0
+const SINCE_EPOCH_LENGTH_ONE: u64 = 0x0000_0100_0000_0000;
+
+#[derive(PartialEq, Clone, Copy)]
+enum CooldownCase {
+    TransferAfterBlocks,
+    TransferAfterEpochs,
+    UpdateWithoutTransfer,
+    TransferBeforeBlocks,
+    TransferBeforeEpochs,
+    TransferWithAbsoluteSince,
+    TransferWithEpochsSinceForBlocks,
+}
+
+fn create_cooldown_context(case: CooldownCase) -> (Context, TransactionView) {
+    let mut context = Context::default();
+
+    let nft_bin: Bytes = Loader::default().load_binary("nft-type");
+    let nft_out_point = context.deploy_cell(nft_bin);
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+
+    let lock_script = context
+        .build_script(&always_success_out_point, Bytes::from("holder"))
+        .expect("script");
+    let another_lock_script = context
+        .build_script(&always_success_out_point, Bytes::from("buyer"))
+        .expect("script");
+    let nft_type_script = context
+        .build_script(
+            &nft_out_point,
+            Bytes::from(hex::decode(COOLDOWN_NFT_TYPE_ARGS).unwrap()),
+        )
+        .expect("script");
+
+    let cooldown_record = match case {
+        CooldownCase::TransferAfterEpochs | CooldownCase::TransferBeforeEpochs => {
+            EPOCHS_COOLDOWN_RECORD
+        }
+        _ => BLOCKS_COOLDOWN_RECORD,
+    };
+    let class_type_script = Script::new_builder()
+        .code_hash(CLASS_TYPE_CODE_HASH.pack())
+        .args(Bytes::from(hex::decode(&COOLDOWN_NFT_TYPE_ARGS[0..48]).unwrap()).pack())
+        .hash_type(Byte::new(TYPE))
+        .build();
+    let class_cell_dep_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(2000u64.pack())
+            .lock(lock_script.clone())
+            .type_(Some(class_type_script).pack())
+            .build(),
+        Bytes::from(
+            hex::decode(format!(
+                "010000000f0000000500000155000266660003898989000c{}",
+                cooldown_record
+            ))
+            .unwrap(),
+        ),
+    );
+
+    let since = match case {
+        CooldownCase::TransferAfterBlocks => SINCE_RELATIVE_BLOCKS | 100,
+        CooldownCase::TransferBeforeBlocks => SINCE_RELATIVE_BLOCKS | 99,
+        CooldownCase::TransferAfterEpochs => SINCE_RELATIVE_EPOCHS | SINCE_EPOCH_LENGTH_ONE | 2,
+        CooldownCase::TransferBeforeEpochs => SINCE_RELATIVE_EPOCHS | SINCE_EPOCH_LENGTH_ONE | 1,
+        CooldownCase::TransferWithAbsoluteSince => 100,
+        CooldownCase::TransferWithEpochsSinceForBlocks => {
+            SINCE_RELATIVE_EPOCHS | SINCE_EPOCH_LENGTH_ONE | 100
+        }
+        CooldownCase::UpdateWithoutTransfer => 0,
+    };
+    let output_lock_script = match case {
+        CooldownCase::UpdateWithoutTransfer => lock_script.clone(),
+        _ => another_lock_script,
+    };
+    // The nft is claimed without transfer before the cooldown
+    let output_data = match case {
+        CooldownCase::UpdateWithoutTransfer => "0100000000000000000001",
+        _ => "0100000000000000000000",
+    };
+
+    let nft_input_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(500u64.pack())
+            .lock(lock_script)
+            .type_(Some(nft_type_script.clone()).pack())
+            .build(),
+        Bytes::from(hex::decode("0100000000000000000000").unwrap()),
+    );
+
+    let tx = TransactionBuilder::default()
+        .input(
+            CellInput::new_builder()
+                .previous_output(nft_input_out_point)
+                .since(since.pack())
+                .build(),
+        )
+        .output(
+            CellOutput::new_builder()
+                .capacity(500u64.pack())
+                .lock(output_lock_script)
+                .type_(Some(nft_type_script).pack())
+                .build(),
+        )
+        .output_data(Bytes::from(hex::decode(output_data).unwrap()).pack())
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(always_success_out_point)
+                .build(),
+        )
+        .cell_dep(CellDep::new_builder().out_point(nft_out_point).build())
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(class_cell_dep_out_point)
+                .build(),
+        )
+        .witness(
+            WitnessArgs::new_builder()
+                .lock(Some(Bytes::new()).pack())
+                .build()
+                .as_bytes()
+                .pack(),
+        )
+        .build();
+    (context, tx)
+}
+
+#[test]
+fn test_transfer_nft_after_blocks_cooldown_success() {
+    let (mut context, tx) = create_cooldown_context(CooldownCase::TransferAfterBlocks);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_transfer_nft_after_epochs_cooldown_success() {
+    let (mut context, tx) = create_cooldown_context(CooldownCase::TransferAfterEpochs);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_update_nft_without_transfer_before_cooldown_success() {
+    let (mut context, tx) = create_cooldown_context(CooldownCase::UpdateWithoutTransfer);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_transfer_nft_before_blocks_cooldown_error() {
+    let (mut context, tx) = create_cooldown_context(CooldownCase::TransferBeforeBlocks);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_TRANSFER_COOLDOWN_NOT_PASSED);
+}
+
+#[test]
+fn test_transfer_nft_before_epochs_cooldown_error() {
+    let (mut context, tx) = create_cooldown_context(CooldownCase::TransferBeforeEpochs);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_TRANSFER_COOLDOWN_NOT_PASSED);
+}
+
+#[test]
+fn test_transfer_nft_with_absolute_since_error() {
+    let (mut context, tx) = create_cooldown_context(CooldownCase::TransferWithAbsoluteSince);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_TRANSFER_COOLDOWN_NOT_PASSED);
+}
+
+#[test]
+fn test_transfer_nft_with_since_of_other_metric_error() {
+    let (mut context, tx) = create_cooldown_context(CooldownCase::TransferWithEpochsSinceForBlocks);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_TRANSFER_COOLDOWN_NOT_PASSED);
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum MintCase {
+    SeededClass,
+    SeededClassWithCharacteristic,
+}
+
+fn create_minting_context(case: MintCase) -> (Context, TransactionView) {
+    let mut context = Context::default();
+
+    let nft_bin: Bytes = Loader::default().load_binary("nft-type");
+    let nft_out_point = context.deploy_cell(nft_bin);
+    let class_bin: Bytes = Loader::default().load_binary("class-type");
+    let class_out_point = context.deploy_cell(class_bin);
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+
+    let lock_script = context
+        .build_script(&always_success_out_point, Bytes::from("issuer"))
+        .expect("script");
+    let class_args = "157a3633c3477d84b604a25e5fca5ca681762c100000000c";
+    let class_type_script = Script::new_builder()
+        .code_hash(CLASS_TYPE_CODE_HASH.pack())
+        .args(Bytes::from(hex::decode(class_args).unwrap()).pack())
+        .hash_type(Byte::new(TYPE))
+        .build();
+    let nft_type_script = context
+        .build_script(
+            &nft_out_point,
+            Bytes::from(hex::decode(format!("{}00000005", class_args)).unwrap()),
+        )
+        .expect("script");
+
+    // The seed hash record of the class: <hash: [u8; 32]>
+    let class_data = |issued: &str| {
+        hex::decode(format!(
+            "010000000f{}000001550002666600038989890023020020{}",
+            issued,
+            hex::encode(blake2b_256(REVEAL_SEED))
+        ))
+        .unwrap()
+    };
+    let class_input_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(2000u64.pack())
+            .lock(lock_script.clone())
+            .type_(Some(class_type_script.clone()).pack())
+            .build(),
+        Bytes::from(class_data("00000005")),
+    );
+
+    // The characteristic of the nft of a seeded class is revealed after minting
+    let nft_data = match case {
+        MintCase::SeededClass => "0100000000000000000000",
+        MintCase::SeededClassWithCharacteristic => "0100000000000000010000",
+    };
+
+    let tx = TransactionBuilder::default()
+        .input(
+            CellInput::new_builder()
+                .previous_output(class_input_out_point)
+                .build(),
+        )
+        .output(
+            CellOutput::new_builder()
+                .capacity(2000u64.pack())
+                .lock(lock_script.clone())
+                .type_(Some(class_type_script).pack())
+                .build(),
+        )
+        .output(
+            CellOutput::new_builder()
+                .capacity(500u64.pack())
+                .lock(lock_script)
+                .type_(Some(nft_type_script).pack())
+                .build(),
+        )
+        .output_data(Bytes::from(class_data("00000006")).pack())
+        .output_data(Bytes::from(hex::decode(nft_data).unwrap()).pack())
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(always_success_out_point)
+                .build(),
+        )
+        .cell_dep(CellDep::new_builder().out_point(class_out_point).build())
+        .cell_dep(CellDep::new_builder().out_point(nft_out_point).build())
+        .witness(
+            WitnessArgs::new_builder()
+                .lock(Some(Bytes::new()).pack())
+                .build()
+                .as_bytes()
+                .pack(),
+        )
+        .build();
+    (context, tx)
+}
+
+// Minting spends the class cell, whose pinned class type cannot be deployed in the test context
+#[test]
+#[ignore]
+fn test_mint_nft_of_seeded_class_success() {
+    let (mut context, tx) = create_minting_context(MintCase::SeededClass);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+#[ignore]
+fn test_mint_nft_of_seeded_class_with_characteristic_error() {
+    let (mut context, tx) = create_minting_context(MintCase::SeededClassWithCharacteristic);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, NFT_CHARACTERISTIC_NOT_ZERO);
+}
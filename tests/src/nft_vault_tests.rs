@@ -0,0 +1,295 @@
+use crate::{assert_script_error, assert_script_errors, Loader};
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::{builtin::ALWAYS_SUCCESS, context::Context};
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+// error numbers
+const LOCKED_NFT_CANNOT_TRANSFER: i8 = 36;
+const VAULT_ARGS_INVALID: i8 = 69;
+const VAULT_SHARES_INVALID: i8 = 70;
+const VAULT_RELEASE_INVALID: i8 = 71;
+
+const SUPPLY: u128 = 1_000;
+
+#[derive(PartialEq)]
+enum VaultCase {
+    Lock,
+    LockWithWrongSupply,
+    LockLockedNft,
+    LockWithoutShares,
+    LockFakeNft,
+    Release,
+    ReleaseWithoutFullSupply,
+    TransferShares,
+    InflateShares,
+    ArgsInvalid,
+}
+
+fn create_test_context(case: VaultCase) -> (Context, TransactionView) {
+    // deploy contracts
+    let mut context = Context::default();
+    let vault_bin: Bytes = Loader::default().load_binary("nft-vault");
+    let vault_out_point = context.deploy_cell(vault_bin);
+    let nft_bin: Bytes = Loader::default().load_binary("nft-type");
+    let nft_out_point = context.deploy_cell(nft_bin);
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+
+    // prepare scripts
+    let owner_lock = context
+        .build_script(&always_success_out_point, Bytes::from("owner"))
+        .expect("script");
+    let holder_lock = context
+        .build_script(&always_success_out_point, Bytes::from("holder"))
+        .expect("script");
+    let nft_type = context
+        .build_script(
+            &nft_out_point,
+            Bytes::from(
+                hex::decode("f90f9c38b0ea0815156bbc340c910d0a21af57c20000000000000011").unwrap(),
+            ),
+        )
+        .expect("script");
+    // The fake nft has the args of the nft but another type code
+    let nft_type = match case {
+        VaultCase::LockFakeNft => context
+            .build_script(&always_success_out_point, nft_type.args().raw_data())
+            .expect("script"),
+        _ => nft_type,
+    };
+    let nft_type_hash = nft_type.calc_script_hash();
+
+    // vault lock args: <nft_type_hash: [u8; 32]> + <supply: u128> + <nft_code_hash: [u8; 32]> +
+    // <nft_hash_type: u8>
+    let mut vault_args = nft_type_hash.as_slice().to_vec();
+    match case {
+        VaultCase::ArgsInvalid => vault_args.extend_from_slice(&0u128.to_be_bytes()),
+        _ => vault_args.extend_from_slice(&SUPPLY.to_be_bytes()),
+    }
+    let nft_code = context
+        .build_script(&nft_out_point, Bytes::new())
+        .expect("script");
+    vault_args.extend_from_slice(nft_code.code_hash().as_slice());
+    vault_args.extend_from_slice(nft_code.hash_type().as_slice());
+    let vault_lock = context
+        .build_script(&vault_out_point, Bytes::from(vault_args))
+        .expect("script");
+    // shares type args: <nft_type_hash: [u8; 32]>
+    let shares_type = context
+        .build_script(&vault_out_point, nft_type_hash.as_bytes())
+        .expect("script");
+
+    let nft_cell = |lock: &Script| {
+        CellOutput::new_builder()
+            .capacity(500u64.pack())
+            .lock(lock.clone())
+            .type_(Some(nft_type.clone()).pack())
+            .build()
+    };
+    let shares_cell = |lock: &Script| {
+        CellOutput::new_builder()
+            .capacity(200u64.pack())
+            .lock(lock.clone())
+            .type_(Some(shares_type.clone()).pack())
+            .build()
+    };
+    // sUDT data: <amount: u128 little endian>
+    let shares_data = |amount: u128| Bytes::from(amount.to_le_bytes().to_vec());
+    let nft_data = match case {
+        VaultCase::LockLockedNft => Bytes::from(hex::decode("0000000000000000000002").unwrap()),
+        _ => Bytes::from(hex::decode("0000000000000000000000").unwrap()),
+    };
+
+    // prepare cells
+    let (input_cells, outputs, outputs_data) = match case {
+        VaultCase::Lock
+        | VaultCase::LockLockedNft
+        | VaultCase::LockWithWrongSupply
+        | VaultCase::LockFakeNft => {
+            let minted = match case {
+                VaultCase::LockWithWrongSupply => SUPPLY - 1,
+                _ => SUPPLY,
+            };
+            (
+                vec![(nft_cell(&owner_lock), nft_data.clone())],
+                vec![nft_cell(&vault_lock), shares_cell(&owner_lock)],
+                vec![nft_data, shares_data(minted)],
+            )
+        }
+        // The nft goes into the vault without the shares, and it could never be released
+        VaultCase::LockWithoutShares => (
+            vec![(nft_cell(&owner_lock), nft_data.clone())],
+            vec![nft_cell(&vault_lock)],
+            vec![nft_data],
+        ),
+        VaultCase::Release | VaultCase::ReleaseWithoutFullSupply | VaultCase::ArgsInvalid => {
+            let burned = match case {
+                VaultCase::ReleaseWithoutFullSupply => SUPPLY - 1,
+                _ => SUPPLY,
+            };
+            (
+                vec![
+                    (nft_cell(&vault_lock), nft_data.clone()),
+                    (shares_cell(&holder_lock), shares_data(burned)),
+                ],
+                vec![nft_cell(&holder_lock)],
+                vec![nft_data],
+            )
+        }
+        VaultCase::TransferShares | VaultCase::InflateShares => {
+            let transferred = match case {
+                VaultCase::InflateShares => SUPPLY + 1,
+                _ => SUPPLY,
+            };
+            (
+                vec![
+                    (shares_cell(&owner_lock), shares_data(600)),
+                    (shares_cell(&owner_lock), shares_data(400)),
+                ],
+                vec![shares_cell(&holder_lock)],
+                vec![shares_data(transferred)],
+            )
+        }
+    };
+    let inputs: Vec<CellInput> = input_cells
+        .into_iter()
+        .map(|(output, data)| {
+            let out_point = context.create_cell(output, data);
+            CellInput::new_builder().previous_output(out_point).build()
+        })
+        .collect();
+
+    // The nft type checks the witness lock of the first input
+    let mut witnesses = vec![WitnessArgs::new_builder()
+        .lock(Some(Bytes::new()).pack())
+        .build()
+        .as_bytes()];
+    witnesses.resize(inputs.len(), Bytes::new());
+
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(always_success_out_point)
+                .build(),
+        )
+        .cell_dep(CellDep::new_builder().out_point(vault_out_point).build())
+        .cell_dep(CellDep::new_builder().out_point(nft_out_point).build())
+        .witnesses(witnesses.pack())
+        .build();
+    (context, tx)
+}
+
+#[test]
+fn test_lock_nft_into_vault_success() {
+    let (mut context, tx) = create_test_context(VaultCase::Lock);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_lock_nft_into_vault_with_wrong_supply_error() {
+    let (mut context, tx) = create_test_context(VaultCase::LockWithWrongSupply);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, VAULT_SHARES_INVALID);
+}
+
+#[test]
+fn test_lock_locked_nft_into_vault_error() {
+    let (mut context, tx) = create_test_context(VaultCase::LockLockedNft);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, LOCKED_NFT_CANNOT_TRANSFER);
+}
+
+#[test]
+fn test_lock_nft_into_vault_without_shares_error() {
+    let (mut context, tx) = create_test_context(VaultCase::LockWithoutShares);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, VAULT_SHARES_INVALID);
+}
+
+#[test]
+fn test_lock_fake_nft_into_vault_error() {
+    let (mut context, tx) = create_test_context(VaultCase::LockFakeNft);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, VAULT_ARGS_INVALID);
+}
+
+#[test]
+fn test_release_nft_from_vault_success() {
+    let (mut context, tx) = create_test_context(VaultCase::Release);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_release_nft_without_full_supply_error() {
+    let (mut context, tx) = create_test_context(VaultCase::ReleaseWithoutFullSupply);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_errors(err, &[VAULT_RELEASE_INVALID, VAULT_SHARES_INVALID]);
+}
+
+#[test]
+fn test_transfer_vault_shares_success() {
+    let (mut context, tx) = create_test_context(VaultCase::TransferShares);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_inflate_vault_shares_error() {
+    let (mut context, tx) = create_test_context(VaultCase::InflateShares);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, VAULT_SHARES_INVALID);
+}
+
+#[test]
+fn test_nft_vault_args_invalid_error() {
+    let (mut context, tx) = create_test_context(VaultCase::ArgsInvalid);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, VAULT_ARGS_INVALID);
+}
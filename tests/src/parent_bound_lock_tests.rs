@@ -0,0 +1,341 @@
+use crate::{assert_script_error, Loader};
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::{builtin::ALWAYS_SUCCESS, context::Context};
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+// error numbers
+const PARENT_BOUND_LOCK_ARGS_INVALID: i8 = 72;
+const PARENT_INPUT_MISSING: i8 = 73;
+const PARENT_BOUND_CHILD_ORPHANED: i8 = 74;
+const PARENT_BOUND_CHILD_DETACHED: i8 = 81;
+
+const NFT_DATA: &str = "0000000000000000000000";
+const PRICE: u64 = 1000;
+
+#[derive(PartialEq)]
+enum NestingCase {
+    MoveParentWithChildren,
+    MoveParentWithGrandchild,
+    DetachChild,
+    SpendChildWithoutParent,
+    DestroyParentWithBoundChild,
+    ArgsInvalid,
+    BuyParentWithChildren,
+    DetachChildOfBoughtParent,
+    DetachChildOfListedParent,
+}
+
+fn create_test_context(case: NestingCase) -> (Context, TransactionView) {
+    // deploy contracts
+    let mut context = Context::default();
+    let parent_bound_lock_bin: Bytes = Loader::default().load_binary("parent-bound-lock");
+    let parent_bound_lock_out_point = context.deploy_cell(parent_bound_lock_bin);
+    let nft_bin: Bytes = Loader::default().load_binary("nft-type");
+    let nft_out_point = context.deploy_cell(nft_bin);
+    let sale_lock_bin: Bytes = Loader::default().load_binary("nft-sale-lock");
+    let sale_lock_out_point = context.deploy_cell(sale_lock_bin);
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+
+    // prepare scripts
+    let owner_lock = context
+        .build_script(&always_success_out_point, Bytes::from("owner"))
+        .expect("script");
+    let new_owner_lock = context
+        .build_script(&always_success_out_point, Bytes::from("new-owner"))
+        .expect("script");
+    let mut nft_type = |token_id: u32| {
+        let mut args = hex::decode("f90f9c38b0ea0815156bbc340c910d0a21af57c200000000").unwrap();
+        args.extend_from_slice(&token_id.to_be_bytes());
+        context
+            .build_script(&nft_out_point, Bytes::from(args))
+            .expect("script")
+    };
+    let parent_type = nft_type(1);
+    let child_type = nft_type(2);
+    let another_child_type = nft_type(3);
+
+    // parent-bound lock args: <parent_type_hash: [u8; 32]> + <owner_lock_code_hash: [u8; 32]> +
+    // <owner_lock_hash_type: u8>
+    let parent_bound_args = |parent_type: &Script| {
+        let mut args = parent_type.calc_script_hash().as_slice().to_vec();
+        args.extend_from_slice(owner_lock.code_hash().as_slice());
+        args.extend_from_slice(owner_lock.hash_type().as_slice());
+        Bytes::from(args)
+    };
+    let mut child_args = parent_bound_args(&parent_type);
+    if case == NestingCase::ArgsInvalid {
+        child_args.truncate(31);
+    }
+    let child_lock = context
+        .build_script(&parent_bound_lock_out_point, child_args)
+        .expect("script");
+    // The grandchild is bound to the child which is bound to the parent
+    let grandchild_lock = context
+        .build_script(&parent_bound_lock_out_point, parent_bound_args(&child_type))
+        .expect("script");
+
+    // The parent is listed under the sale lock of the owner, and anyone can buy it with the
+    // payment at the same index: <seller_lock_hash: [u8; 32]> + <price: u128> + <nft_code_hash:
+    // [u8; 32]> + <nft_hash_type: u8>
+    let mut sale_args = owner_lock.calc_script_hash().as_slice().to_vec();
+    sale_args.extend_from_slice(&(PRICE as u128).to_be_bytes());
+    sale_args.extend_from_slice(parent_type.code_hash().as_slice());
+    sale_args.extend_from_slice(parent_type.hash_type().as_slice());
+    let sale_lock = context
+        .build_script(&sale_lock_out_point, Bytes::from(sale_args))
+        .expect("script");
+
+    let nft_cell = |lock: &Script, type_: &Script| {
+        CellOutput::new_builder()
+            .capacity(500u64.pack())
+            .lock(lock.clone())
+            .type_(Some(type_.clone()).pack())
+            .build()
+    };
+    let plain_cell = |lock: &Script, capacity: u64| {
+        CellOutput::new_builder()
+            .capacity(capacity.pack())
+            .lock(lock.clone())
+            .build()
+    };
+
+    // prepare cells
+    let (input_cells, outputs) =
+        match case {
+            NestingCase::MoveParentWithChildren => (
+                vec![
+                    nft_cell(&owner_lock, &parent_type),
+                    nft_cell(&child_lock, &child_type),
+                    nft_cell(&child_lock, &another_child_type),
+                ],
+                vec![
+                    nft_cell(&new_owner_lock, &parent_type),
+                    nft_cell(&child_lock, &child_type),
+                    nft_cell(&child_lock, &another_child_type),
+                ],
+            ),
+            NestingCase::MoveParentWithGrandchild => (
+                vec![
+                    nft_cell(&owner_lock, &parent_type),
+                    nft_cell(&child_lock, &child_type),
+                    nft_cell(&grandchild_lock, &another_child_type),
+                ],
+                vec![
+                    nft_cell(&new_owner_lock, &parent_type),
+                    nft_cell(&child_lock, &child_type),
+                    nft_cell(&grandchild_lock, &another_child_type),
+                ],
+            ),
+            NestingCase::DetachChild | NestingCase::ArgsInvalid => (
+                vec![
+                    nft_cell(&owner_lock, &parent_type),
+                    nft_cell(&child_lock, &child_type),
+                ],
+                vec![
+                    nft_cell(&owner_lock, &parent_type),
+                    nft_cell(&owner_lock, &child_type),
+                ],
+            ),
+            NestingCase::SpendChildWithoutParent => (
+                vec![nft_cell(&child_lock, &child_type)],
+                vec![nft_cell(&owner_lock, &child_type)],
+            ),
+            NestingCase::DestroyParentWithBoundChild => (
+                vec![
+                    nft_cell(&owner_lock, &parent_type),
+                    nft_cell(&child_lock, &child_type),
+                ],
+                vec![nft_cell(&child_lock, &child_type)],
+            ),
+            NestingCase::BuyParentWithChildren => (
+                vec![
+                    nft_cell(&sale_lock, &parent_type),
+                    nft_cell(&child_lock, &child_type),
+                    plain_cell(&new_owner_lock, 10_000),
+                ],
+                vec![
+                    plain_cell(&owner_lock, PRICE),
+                    nft_cell(&new_owner_lock, &parent_type),
+                    nft_cell(&child_lock, &child_type),
+                ],
+            ),
+            // The buyer of the parent takes the child away from the parent
+            NestingCase::DetachChildOfBoughtParent => (
+                vec![
+                    nft_cell(&sale_lock, &parent_type),
+                    nft_cell(&child_lock, &child_type),
+                    plain_cell(&new_owner_lock, 10_000),
+                ],
+                vec![
+                    plain_cell(&owner_lock, PRICE),
+                    nft_cell(&new_owner_lock, &parent_type),
+                    nft_cell(&new_owner_lock, &child_type),
+                ],
+            ),
+            // Anyone pays the seller to take the child away, and the parent is listed again
+            NestingCase::DetachChildOfListedParent => (
+                vec![
+                    nft_cell(&sale_lock, &parent_type),
+                    nft_cell(&child_lock, &child_type),
+                    plain_cell(&new_owner_lock, 10_000),
+                ],
+                vec![
+                    plain_cell(&owner_lock, PRICE),
+                    nft_cell(&sale_lock, &parent_type),
+                    nft_cell(&new_owner_lock, &child_type),
+                ],
+            ),
+        };
+    let inputs: Vec<CellInput> = input_cells
+        .into_iter()
+        .map(|output| {
+            let out_point =
+                context.create_cell(output, Bytes::from(hex::decode(NFT_DATA).unwrap()));
+            CellInput::new_builder().previous_output(out_point).build()
+        })
+        .collect();
+    let outputs_data: Vec<Bytes> = outputs
+        .iter()
+        .map(|_| Bytes::from(hex::decode(NFT_DATA).unwrap()))
+        .collect();
+
+    // The nft type checks the witness lock of the first input under the lock of every nft
+    let witnesses: Vec<Bytes> = inputs
+        .iter()
+        .map(|_| {
+            WitnessArgs::new_builder()
+                .lock(Some(Bytes::new()).pack())
+                .build()
+                .as_bytes()
+        })
+        .collect();
+
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(always_success_out_point)
+                .build(),
+        )
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(parent_bound_lock_out_point)
+                .build(),
+        )
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(sale_lock_out_point)
+                .build(),
+        )
+        .cell_dep(CellDep::new_builder().out_point(nft_out_point).build())
+        .witnesses(witnesses.pack())
+        .build();
+    (context, tx)
+}
+
+#[test]
+fn test_move_parent_with_children_success() {
+    let (mut context, tx) = create_test_context(NestingCase::MoveParentWithChildren);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_move_parent_with_grandchild_success() {
+    let (mut context, tx) = create_test_context(NestingCase::MoveParentWithGrandchild);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_detach_child_from_parent_success() {
+    let (mut context, tx) = create_test_context(NestingCase::DetachChild);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_spend_child_without_parent_error() {
+    let (mut context, tx) = create_test_context(NestingCase::SpendChildWithoutParent);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, PARENT_INPUT_MISSING);
+}
+
+#[test]
+fn test_destroy_parent_with_bound_child_error() {
+    let (mut context, tx) = create_test_context(NestingCase::DestroyParentWithBoundChild);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, PARENT_BOUND_CHILD_ORPHANED);
+}
+
+#[test]
+fn test_parent_bound_lock_args_invalid_error() {
+    let (mut context, tx) = create_test_context(NestingCase::ArgsInvalid);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, PARENT_BOUND_LOCK_ARGS_INVALID);
+}
+
+#[test]
+fn test_buy_parent_with_children_success() {
+    let (mut context, tx) = create_test_context(NestingCase::BuyParentWithChildren);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_detach_child_of_bought_parent_error() {
+    let (mut context, tx) = create_test_context(NestingCase::DetachChildOfBoughtParent);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, PARENT_BOUND_CHILD_DETACHED);
+}
+
+#[test]
+fn test_detach_child_of_listed_parent_error() {
+    let (mut context, tx) = create_test_context(NestingCase::DetachChildOfListedParent);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, PARENT_BOUND_CHILD_DETACHED);
+}
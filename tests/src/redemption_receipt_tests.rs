@@ -0,0 +1,290 @@
+use crate::{assert_script_error, assert_script_errors, Loader, CLASS_TYPE_CODE_HASH};
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::{builtin::ALWAYS_SUCCESS, context::Context};
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+const TYPE: u8 = 1;
+
+// error numbers
+const REDEMPTION_RECEIPT_MISSING: i8 = 76;
+const REDEMPTION_RECEIPT_INVALID: i8 = 77;
+
+const CLASS_TYPE_ARGS: &str = "157a3633c3477d84b604a25e5fca5ca681762c1000000003";
+
+#[derive(PartialEq)]
+enum RedemptionCase {
+    Redeem,
+    ReceiptMissing,
+    RedeemerMismatch,
+    ReceiptWithoutBurn,
+    DuplicateReceipts,
+    DestroyReceipt,
+    TransferReceipt,
+    FakeNftBurned,
+}
+
+fn create_test_context(case: RedemptionCase) -> (Context, TransactionView) {
+    // deploy contracts
+    let mut context = Context::default();
+    let receipt_bin: Bytes = Loader::default().load_binary("redemption-receipt");
+    let receipt_out_point = context.deploy_cell(receipt_bin);
+    let nft_bin: Bytes = Loader::default().load_binary("nft-type");
+    let nft_out_point = context.deploy_cell(nft_bin);
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+
+    // prepare scripts
+    let redeemer_lock = context
+        .build_script(&always_success_out_point, Bytes::from("redeemer"))
+        .expect("script");
+    let merchant_lock = context
+        .build_script(&always_success_out_point, Bytes::from("merchant"))
+        .expect("script");
+    let class_args = hex::decode(CLASS_TYPE_ARGS).unwrap();
+    let mut nft_type_args = class_args.clone();
+    nft_type_args.extend_from_slice(&5u32.to_be_bytes());
+    let nft_type = context
+        .build_script(&nft_out_point, Bytes::from(nft_type_args.clone()))
+        .expect("script");
+    // The fake nft has the args of the nft but another type code
+    let fake_nft_type = context
+        .build_script(&always_success_out_point, Bytes::from(nft_type_args))
+        .expect("script");
+    // receipt type args: <class_type_args: [u8; 24]> + <nft_code_hash: [u8; 32]> +
+    // <nft_hash_type: u8>
+    let mut receipt_args = class_args.clone();
+    receipt_args.extend_from_slice(nft_type.code_hash().as_slice());
+    receipt_args.extend_from_slice(nft_type.hash_type().as_slice());
+    let receipt_type = context
+        .build_script(&receipt_out_point, Bytes::from(receipt_args))
+        .expect("script");
+
+    // the redemption record: <code_hash: [u8; 32]> + <hash_type: u8>
+    let class_data = format!(
+        "010000000f00000005000001550002666600038989890024060021{}{}",
+        hex::encode(receipt_type.code_hash().as_slice()),
+        hex::encode(receipt_type.hash_type().as_slice())
+    );
+    let class_type = Script::new_builder()
+        .code_hash(CLASS_TYPE_CODE_HASH.pack())
+        .args(Bytes::from(class_args).pack())
+        .hash_type(Byte::new(TYPE))
+        .build();
+    let class_cell_dep_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(2000u64.pack())
+            .lock(merchant_lock.clone())
+            .type_(Some(class_type).pack())
+            .build(),
+        Bytes::from(hex::decode(class_data).unwrap()),
+    );
+
+    // receipt data: <nft_type_hash: [u8; 32]> + <redeemer_lock_hash: [u8; 32]>
+    let mut receipt_data = match case {
+        RedemptionCase::FakeNftBurned => fake_nft_type.calc_script_hash().as_slice().to_vec(),
+        _ => nft_type.calc_script_hash().as_slice().to_vec(),
+    };
+    match case {
+        RedemptionCase::RedeemerMismatch => {
+            receipt_data.extend_from_slice(merchant_lock.calc_script_hash().as_slice())
+        }
+        _ => receipt_data.extend_from_slice(redeemer_lock.calc_script_hash().as_slice()),
+    }
+    let receipt_data = Bytes::from(receipt_data);
+    let nft_data = Bytes::from(hex::decode("0100000000000000000000").unwrap());
+
+    let nft_cell = |lock: &Script| {
+        CellOutput::new_builder()
+            .capacity(500u64.pack())
+            .lock(lock.clone())
+            .type_(Some(nft_type.clone()).pack())
+            .build()
+    };
+    let receipt_cell = |lock: &Script| {
+        CellOutput::new_builder()
+            .capacity(200u64.pack())
+            .lock(lock.clone())
+            .type_(Some(receipt_type.clone()).pack())
+            .build()
+    };
+    let plain_cell = CellOutput::new_builder()
+        .capacity(500u64.pack())
+        .lock(redeemer_lock.clone())
+        .build();
+
+    // prepare cells
+    let (input_cells, outputs, outputs_data) = match case {
+        RedemptionCase::Redeem | RedemptionCase::RedeemerMismatch => (
+            vec![(nft_cell(&redeemer_lock), nft_data)],
+            vec![receipt_cell(&redeemer_lock)],
+            vec![receipt_data],
+        ),
+        RedemptionCase::ReceiptMissing => (
+            vec![(nft_cell(&redeemer_lock), nft_data)],
+            vec![plain_cell],
+            vec![Bytes::new()],
+        ),
+        RedemptionCase::ReceiptWithoutBurn => (
+            vec![(nft_cell(&redeemer_lock), nft_data.clone())],
+            vec![nft_cell(&merchant_lock), receipt_cell(&redeemer_lock)],
+            vec![nft_data, receipt_data],
+        ),
+        // One burned nft is redeemed twice with a receipt for the merchant as well
+        RedemptionCase::DuplicateReceipts => (
+            vec![(nft_cell(&redeemer_lock), nft_data)],
+            vec![receipt_cell(&redeemer_lock), receipt_cell(&merchant_lock)],
+            vec![receipt_data.clone(), receipt_data],
+        ),
+        RedemptionCase::DestroyReceipt => (
+            vec![(receipt_cell(&redeemer_lock), receipt_data)],
+            vec![plain_cell],
+            vec![Bytes::new()],
+        ),
+        // The fake nft is burned to forge a receipt of the class
+        RedemptionCase::FakeNftBurned => (
+            vec![(
+                CellOutput::new_builder()
+                    .capacity(500u64.pack())
+                    .lock(redeemer_lock.clone())
+                    .type_(Some(fake_nft_type.clone()).pack())
+                    .build(),
+                nft_data,
+            )],
+            vec![receipt_cell(&redeemer_lock)],
+            vec![receipt_data],
+        ),
+        RedemptionCase::TransferReceipt => (
+            vec![(receipt_cell(&redeemer_lock), receipt_data.clone())],
+            vec![receipt_cell(&merchant_lock)],
+            vec![receipt_data],
+        ),
+    };
+    let inputs: Vec<CellInput> = input_cells
+        .into_iter()
+        .map(|(output, data)| {
+            let out_point = context.create_cell(output, data);
+            CellInput::new_builder().previous_output(out_point).build()
+        })
+        .collect();
+
+    // The nft type checks the witness lock of the first input
+    let witnesses = vec![WitnessArgs::new_builder()
+        .lock(Some(Bytes::new()).pack())
+        .build()
+        .as_bytes()];
+
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(always_success_out_point)
+                .build(),
+        )
+        .cell_dep(CellDep::new_builder().out_point(receipt_out_point).build())
+        .cell_dep(CellDep::new_builder().out_point(nft_out_point).build())
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(class_cell_dep_out_point)
+                .build(),
+        )
+        .witnesses(witnesses.pack())
+        .build();
+    (context, tx)
+}
+
+#[test]
+fn test_redeem_nft_with_receipt_success() {
+    let (mut context, tx) = create_test_context(RedemptionCase::Redeem);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_redeem_nft_without_receipt_error() {
+    let (mut context, tx) = create_test_context(RedemptionCase::ReceiptMissing);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, REDEMPTION_RECEIPT_MISSING);
+}
+
+#[test]
+fn test_redeem_nft_with_receipt_of_other_redeemer_error() {
+    let (mut context, tx) = create_test_context(RedemptionCase::RedeemerMismatch);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_errors(err, &[
+        REDEMPTION_RECEIPT_MISSING,
+        REDEMPTION_RECEIPT_INVALID,
+    ]);
+}
+
+#[test]
+fn test_create_receipt_without_burning_nft_error() {
+    let (mut context, tx) = create_test_context(RedemptionCase::ReceiptWithoutBurn);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, REDEMPTION_RECEIPT_INVALID);
+}
+
+#[test]
+fn test_redeem_nft_with_duplicate_receipts_error() {
+    let (mut context, tx) = create_test_context(RedemptionCase::DuplicateReceipts);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, REDEMPTION_RECEIPT_INVALID);
+}
+
+#[test]
+fn test_destroy_redemption_receipt_success() {
+    let (mut context, tx) = create_test_context(RedemptionCase::DestroyReceipt);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_transfer_redemption_receipt_success() {
+    let (mut context, tx) = create_test_context(RedemptionCase::TransferReceipt);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_create_receipt_by_burning_fake_nft_error() {
+    let (mut context, tx) = create_test_context(RedemptionCase::FakeNftBurned);
+
+    let tx = context.complete_tx(tx);
+    // run
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, REDEMPTION_RECEIPT_INVALID);
+}